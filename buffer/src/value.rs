@@ -983,7 +983,13 @@ mod alloc_support {
                     ValueKind::F32(v) => v.stream(stream)?,
                     ValueKind::F64(v) => v.stream(stream)?,
                     ValueKind::Text(v) => v.stream(stream)?,
-                    ValueKind::Binary(v) => v.stream(stream)?,
+                    // `BinaryBuf` streams itself as a sequence of bytes, so replay
+                    // the original binary tokens directly
+                    ValueKind::Binary(v) => {
+                        stream.binary_begin(Some(v.len()))?;
+                        stream.binary_fragment(v.get())?;
+                        stream.binary_end()?;
+                    }
                     ValueKind::Map {
                         len,
                         num_entries_hint,
@@ -1114,11 +1120,41 @@ mod alloc_support {
     #[cfg(test)]
     mod tests {
         use super::*;
-        use crate::std::vec;
+        use crate::{alloc::format, std::vec};
 
         use sval::Stream as _;
         use sval_derive::*;
 
+        #[test]
+        fn buffer_conformance() {
+            sval_test::conformance::Suite::new().run(ValueBuf::new, |case, buf| {
+                assert!(buf.is_complete());
+
+                sval::validate(&buf).unwrap();
+
+                assert_eq!(
+                    format!("{:?}", buf),
+                    format!("{:?}", ValueBuf::collect(&buf).unwrap()),
+                    "{}",
+                    case.name()
+                );
+            });
+        }
+
+        #[test]
+        fn buffer_generated() {
+            sval_test::generate::check(512, |value| {
+                let buf = ValueBuf::collect(value).unwrap();
+
+                assert!(buf.is_complete());
+
+                assert_eq!(
+                    format!("{:?}", buf),
+                    format!("{:?}", ValueBuf::collect(&buf).unwrap())
+                );
+            });
+        }
+
         #[test]
         fn buffer_primitive() {
             for (value, expected) in [
@@ -1334,6 +1370,26 @@ mod alloc_support {
             assert_eq!(expected, value.parts);
         }
 
+        #[test]
+        fn buffer_binary() {
+            let mut value = ValueBuf::new();
+
+            value.binary_begin(None).unwrap();
+            value.binary_fragment(b"ab").unwrap();
+            value.binary_fragment_computed(b"c").unwrap();
+            value.binary_end().unwrap();
+
+            // Binary is replayed as binary rather than as a sequence of bytes
+            sval_test::assert_tokens(
+                &value,
+                &[
+                    sval_test::Token::BinaryBegin(Some(3)),
+                    sval_test::Token::BinaryFragment(b"abc"),
+                    sval_test::Token::BinaryEnd,
+                ],
+            );
+        }
+
         #[test]
         fn buffer_record() {
             let mut value = ValueBuf::new();
@@ -1561,5 +1617,249 @@ mod alloc_support {
                 assert_eq!(value_1.parts, value_2.parts, "{:?}", value_1);
             }
         }
+
+        #[test]
+        fn buffer_from_value() {
+            use crate::std::{collections::BTreeMap, string::String};
+
+            let mut map = BTreeMap::new();
+            map.insert(String::from("a"), (1u8, Some(true)));
+            map.insert(String::from("b"), (2u8, None));
+
+            let expected = vec![map];
+            let value = ValueBuf::collect(&expected).unwrap();
+
+            assert_eq!(
+                expected,
+                sval::from_value::<Vec<BTreeMap<String, (u8, Option<bool>)>>>(&value).unwrap()
+            );
+        }
+
+        #[test]
+        fn buffer_from_value_derive() {
+            use crate::std::string::String;
+
+            const CONTAINER: sval::Tag = sval::Tag::new("container");
+            const FIELD: sval::Tag = sval::Tag::new("field");
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(tag = "CONTAINER")]
+            struct Record {
+                #[sval(rename = "renamed")]
+                a: i32,
+                #[sval(field_tag = "FIELD")]
+                b: Option<String>,
+                #[sval(tag = "sval::tags::NUMBER")]
+                c: u64,
+                d: Newtype,
+                e: Tuple,
+                f: Vec<Enum>,
+                g: Generic<u8>,
+            }
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            enum Generic<T> {
+                A { a: T },
+                B(T),
+            }
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            struct Newtype(bool);
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            struct Tuple(i32, #[sval(tag = "sval::tags::NUMBER")] u8);
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            struct Unit;
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(tag = "CONTAINER")]
+            enum Enum {
+                Unit,
+                Newtype(i32),
+                Tuple(i32, bool),
+                Record { a: i32, b: Unit },
+            }
+
+            let expected = Record {
+                a: 42,
+                b: Some(String::from("text")),
+                c: 7,
+                d: Newtype(true),
+                e: Tuple(-1, 2),
+                f: vec![
+                    Enum::Unit,
+                    Enum::Newtype(1),
+                    Enum::Tuple(2, false),
+                    Enum::Record { a: 3, b: Unit },
+                ],
+                g: Generic::A { a: 1 },
+            };
+
+            let value = ValueBuf::collect(&expected).unwrap();
+
+            assert_eq!(expected, sval::from_value::<Record>(&value).unwrap());
+        }
+
+        #[test]
+        fn buffer_from_value_derive_skip() {
+            #[derive(Debug, Default, PartialEq)]
+            struct NotValue(i32);
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            struct Record {
+                a: i32,
+                #[sval(skip)]
+                b: NotValue,
+                #[sval(skip_if = "Option::is_none")]
+                c: Option<bool>,
+                #[sval(skip_if = "Vec::is_empty")]
+                d: Vec<i32>,
+            }
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            struct Tuple(i32, #[sval(skip)] NotValue, bool);
+
+            let skipped = Record {
+                a: 1,
+                b: NotValue(2),
+                c: None,
+                d: vec![],
+            };
+            let value = ValueBuf::collect(&skipped).unwrap();
+            assert_eq!(
+                Record {
+                    a: 1,
+                    b: NotValue(0),
+                    c: None,
+                    d: vec![],
+                },
+                sval::from_value::<Record>(&value).unwrap()
+            );
+
+            let expected = Record {
+                a: 1,
+                b: NotValue(0),
+                c: Some(true),
+                d: vec![1, 2],
+            };
+            let value = ValueBuf::collect(&expected).unwrap();
+            assert_eq!(expected, sval::from_value::<Record>(&value).unwrap());
+
+            let value = ValueBuf::collect(&Tuple(1, NotValue(2), true)).unwrap();
+            assert_eq!(
+                Tuple(1, NotValue(0), true),
+                sval::from_value::<Tuple>(&value).unwrap()
+            );
+        }
+
+        #[test]
+        fn buffer_from_value_derive_rename_all() {
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(rename_all = "camelCase")]
+            struct Record {
+                field_a: i32,
+                field_b: Enum,
+            }
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(rename_all = "kebab-case")]
+            enum Enum {
+                VariantA,
+                #[sval(rename_all = "SCREAMING_SNAKE_CASE")]
+                VariantB {
+                    field_a: i32,
+                },
+            }
+
+            for expected in [
+                Record {
+                    field_a: 1,
+                    field_b: Enum::VariantA,
+                },
+                Record {
+                    field_a: 1,
+                    field_b: Enum::VariantB { field_a: 2 },
+                },
+            ] {
+                let value = ValueBuf::collect(&expected).unwrap();
+
+                assert_eq!(expected, sval::from_value::<Record>(&value).unwrap());
+            }
+        }
+
+        #[test]
+        fn buffer_from_value_derive_transparent() {
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(transparent)]
+            struct Newtype(i32);
+
+            #[derive(Value, FromValue, Debug, PartialEq)]
+            #[sval(transparent)]
+            struct Named {
+                a: Vec<Newtype>,
+            }
+
+            let expected = Named {
+                a: vec![Newtype(1), Newtype(2)],
+            };
+            let value = ValueBuf::collect(&expected).unwrap();
+
+            assert_eq!(expected, sval::from_value::<Named>(&value).unwrap());
+            assert_eq!(vec![1, 2], sval::from_value::<Vec<i32>>(&value).unwrap());
+        }
+
+        #[test]
+        fn buffer_from_value_derive_invalid() {
+            mod source {
+                use sval_derive::*;
+
+                #[derive(Value)]
+                pub struct Missing {
+                    pub a: i32,
+                }
+
+                #[derive(Value)]
+                pub struct Unknown {
+                    pub a: i32,
+                    pub b: i32,
+                    pub c: i32,
+                }
+
+                #[derive(Value)]
+                pub enum Enum {
+                    C,
+                }
+            }
+
+            #[derive(FromValue, Debug)]
+            #[allow(dead_code)]
+            struct Record {
+                a: i32,
+                b: i32,
+            }
+
+            #[derive(FromValue, Debug)]
+            #[allow(dead_code)]
+            enum Enum {
+                A,
+                B,
+            }
+
+            let err =
+                sval::from_value::<Record>(&ValueBuf::collect(&source::Missing { a: 1 }).unwrap())
+                    .unwrap_err();
+            assert_eq!(Some("missing field `b`"), err.message());
+
+            let err = sval::from_value::<Record>(
+                &ValueBuf::collect(&source::Unknown { a: 1, b: 2, c: 3 }).unwrap(),
+            )
+            .unwrap_err();
+            assert_eq!(Some("unknown field `c`"), err.message());
+
+            let err = sval::from_value::<Enum>(&ValueBuf::collect(&source::Enum::C).unwrap())
+                .unwrap_err();
+            assert_eq!(Some("unknown variant `C`"), err.message());
+        }
     }
 }
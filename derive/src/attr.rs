@@ -1,6 +1,140 @@
-use syn::{Attribute, DeriveInput, Field, Lit, Meta, MetaList, NestedMeta};
+use syn::{Attribute, Data, DeriveInput, Field, Fields, Lit, Meta, MetaList, NestedMeta, Variant};
 
-pub(crate) fn field_name(field: &Field) -> String {
+/**
+A case convention applied to the labels of fields or variants.
+*/
+#[derive(Clone, Copy)]
+pub(crate) enum RenameAll {
+    Camel,
+    Snake,
+    Kebab,
+    ScreamingSnake,
+    Pascal,
+}
+
+impl RenameAll {
+    fn parse(case: &str) -> Option<Self> {
+        match case {
+            "camelCase" => Some(RenameAll::Camel),
+            "snake_case" => Some(RenameAll::Snake),
+            "kebab-case" => Some(RenameAll::Kebab),
+            "SCREAMING_SNAKE_CASE" => Some(RenameAll::ScreamingSnake),
+            "PascalCase" => Some(RenameAll::Pascal),
+            _ => None,
+        }
+    }
+
+    /**
+    Convert a Rust identifier in either `snake_case` or `PascalCase` into this case.
+    */
+    pub(crate) fn apply(self, name: &str) -> String {
+        let name = name.strip_prefix("r#").unwrap_or(name);
+
+        let mut renamed = String::new();
+
+        for (i, word) in words(name).into_iter().enumerate() {
+            match self {
+                RenameAll::Camel if i == 0 => renamed.push_str(&word),
+                RenameAll::Camel | RenameAll::Pascal => {
+                    let mut chars = word.chars();
+
+                    if let Some(first) = chars.next() {
+                        renamed.extend(first.to_uppercase());
+                        renamed.push_str(chars.as_str());
+                    }
+                }
+                RenameAll::Snake | RenameAll::Kebab | RenameAll::ScreamingSnake => {
+                    if i > 0 {
+                        renamed.push(if let RenameAll::Kebab = self {
+                            '-'
+                        } else {
+                            '_'
+                        });
+                    }
+
+                    if let RenameAll::ScreamingSnake = self {
+                        renamed.push_str(&word.to_uppercase());
+                    } else {
+                        renamed.push_str(&word);
+                    }
+                }
+            }
+        }
+
+        renamed
+    }
+}
+
+/**
+Split an identifier into lowercase words.
+
+Words are separated by underscores, or by the start of an uppercase run, so
+`HttpServer`, `HTTPServer`, and `http_server` all split into `http` and `server`.
+*/
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    let chars = name.chars().collect::<Vec<_>>();
+
+    for (i, c) in chars.iter().copied().enumerate() {
+        if c == '_' {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+
+            continue;
+        }
+
+        if c.is_uppercase() && !word.is_empty() {
+            let prev = chars[i - 1];
+            let next = chars.get(i + 1).copied();
+
+            if !prev.is_uppercase() || next.map(|next| next.is_lowercase()).unwrap_or(false) {
+                words.push(std::mem::take(&mut word));
+            }
+        }
+
+        word.extend(c.to_lowercase());
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+pub(crate) fn container_rename_all(container: &DeriveInput) -> Option<RenameAll> {
+    rename_all(&container.attrs)
+}
+
+pub(crate) fn variant_rename_all(variant: &Variant) -> Option<RenameAll> {
+    rename_all(&variant.attrs)
+}
+
+fn rename_all(attrs: &[Attribute]) -> Option<RenameAll> {
+    for list in attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let Some(case) = name_value("rename_all", &meta) {
+                return RenameAll::parse(&case);
+            }
+        }
+    }
+
+    None
+}
+
+pub(crate) fn variant_name(variant: &Variant, rename_all: Option<RenameAll>) -> String {
+    let name = variant.ident.to_string();
+
+    match rename_all {
+        Some(rename_all) => rename_all.apply(&name),
+        None => name,
+    }
+}
+
+pub(crate) fn field_name(field: &Field, rename_all: Option<RenameAll>) -> String {
     let mut rename = None;
 
     for list in field.attrs.iter().filter_map(sval_attr) {
@@ -16,7 +150,14 @@ pub(crate) fn field_name(field: &Field) -> String {
         }
     }
 
-    rename.unwrap_or_else(|| field.ident.as_ref().unwrap().to_string())
+    rename.unwrap_or_else(|| {
+        let name = field.ident.as_ref().unwrap().to_string();
+
+        match rename_all {
+            Some(rename_all) => rename_all.apply(&name),
+            None => name,
+        }
+    })
 }
 
 pub(crate) fn container_tag(container: &DeriveInput) -> Option<syn::Path> {
@@ -55,6 +196,272 @@ pub(crate) fn field_data_tag(field: &Field) -> Option<syn::Path> {
     None
 }
 
+pub(crate) fn container_transparent(container: &DeriveInput) -> bool {
+    flag("transparent", &container.attrs)
+}
+
+pub(crate) fn container_stream_with(container: &DeriveInput) -> Option<syn::Path> {
+    for list in container.attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let Some(path) = name_value("stream_with", &meta) {
+                return Some(syn::parse_str(&path).unwrap());
+            }
+        }
+    }
+
+    None
+}
+
+pub(crate) fn field_stream_with(field: &Field) -> Option<syn::Path> {
+    for list in field.attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let Some(path) = name_value("stream_with", &meta) {
+                return Some(syn::parse_str(&path).unwrap());
+            }
+        }
+    }
+
+    None
+}
+
+pub(crate) fn field_skip(field: &Field) -> bool {
+    flag("skip", &field.attrs)
+}
+
+pub(crate) fn field_flatten(field: &Field) -> bool {
+    flag("flatten", &field.attrs)
+}
+
+fn flag(name: &str, attrs: &[Attribute]) -> bool {
+    for list in attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let NestedMeta::Meta(Meta::Path(path)) = meta {
+                if path.is_ident(name) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+pub(crate) fn field_skip_if(field: &Field) -> Option<syn::Path> {
+    for list in field.attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let Some(path) = name_value("skip_if", &meta) {
+                return Some(syn::parse_str(&path).unwrap());
+            }
+        }
+    }
+
+    None
+}
+
+/**
+Find the `#[sval]` item with the given name, so errors can point at it.
+*/
+pub(crate) fn find(name: &str, attrs: &[Attribute]) -> Option<NestedMeta> {
+    for list in attrs.iter().filter_map(sval_attr) {
+        for meta in list.nested {
+            let path = match meta {
+                NestedMeta::Meta(Meta::Path(ref path)) => path,
+                NestedMeta::Meta(Meta::NameValue(ref value)) => &value.path,
+                _ => continue,
+            };
+
+            if path.is_ident(name) {
+                return Some(meta);
+            }
+        }
+    }
+
+    None
+}
+
+/**
+Fail if any of the given attributes are used on the container, its variants, or its fields.
+
+This is used by derives that don't support some attributes.
+*/
+pub(crate) fn unsupported(
+    container: &DeriveInput,
+    names: &[&str],
+    derive: &str,
+) -> syn::Result<()> {
+    let mut attrs = vec![&container.attrs];
+
+    match container.data {
+        Data::Struct(ref data) => attrs.extend(data.fields.iter().map(|field| &field.attrs)),
+        Data::Enum(ref data) => {
+            for variant in &data.variants {
+                attrs.push(&variant.attrs);
+                attrs.extend(variant.fields.iter().map(|field| &field.attrs));
+            }
+        }
+        Data::Union(_) => (),
+    }
+
+    for attrs in attrs {
+        for name in names {
+            if let Some(meta) = find(name, attrs) {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    format!("`{}` is not supported when deriving `{}`", name, derive),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Fail if any of the given attributes are used on unnamed fields.
+
+This is used for attributes that only make sense on fields with a label.
+*/
+pub(crate) fn named_only(container: &DeriveInput, names: &[&str]) -> syn::Result<()> {
+    let mut fields = Vec::new();
+
+    match container.data {
+        Data::Struct(ref data) => fields.extend(data.fields.iter()),
+        Data::Enum(ref data) => {
+            for variant in &data.variants {
+                fields.extend(variant.fields.iter());
+            }
+        }
+        Data::Union(_) => (),
+    }
+
+    for field in fields.into_iter().filter(|field| field.ident.is_none()) {
+        for name in names {
+            if let Some(meta) = find(name, &field.attrs) {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    format!("`{}` is only supported on named fields", name),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Fail if any of the given attributes are used on the field of a newtype.
+
+A newtype always streams its field, so it can't be skipped.
+*/
+pub(crate) fn not_newtype(container: &DeriveInput, names: &[&str]) -> syn::Result<()> {
+    let mut fields = Vec::new();
+
+    match container.data {
+        Data::Struct(ref data) => fields.push(&data.fields),
+        Data::Enum(ref data) => fields.extend(data.variants.iter().map(|variant| &variant.fields)),
+        Data::Union(_) => (),
+    }
+
+    for field in fields.into_iter().filter_map(|fields| match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Some(&fields.unnamed[0]),
+        _ => None,
+    }) {
+        for name in names {
+            if let Some(meta) = find(name, &field.attrs) {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    format!("`{}` is not supported on the field of a newtype", name),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Fail if any of the given attributes are used on a `flatten` field.
+
+A flattened field streams its entries directly into its container, so
+attributes that change how the field itself is streamed don't apply.
+*/
+pub(crate) fn not_flatten(container: &DeriveInput, names: &[&str]) -> syn::Result<()> {
+    let mut fields = Vec::new();
+
+    match container.data {
+        Data::Struct(ref data) => fields.extend(data.fields.iter()),
+        Data::Enum(ref data) => {
+            for variant in &data.variants {
+                fields.extend(variant.fields.iter());
+            }
+        }
+        Data::Union(_) => (),
+    }
+
+    for field in fields.into_iter().filter(|field| field_flatten(field)) {
+        for name in names {
+            if let Some(meta) = find(name, &field.attrs) {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    format!("`{}` is not supported on `flatten` fields", name),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Fail if any `rename_all` on the container or its variants uses an unsupported case.
+*/
+pub(crate) fn rename_all_cases(container: &DeriveInput) -> syn::Result<()> {
+    let mut attrs = vec![&container.attrs];
+
+    if let Data::Enum(ref data) = container.data {
+        attrs.extend(data.variants.iter().map(|variant| &variant.attrs));
+    }
+
+    for list in attrs.into_iter().flatten().filter_map(sval_attr) {
+        for meta in list.nested {
+            if let NestedMeta::Meta(Meta::NameValue(value)) = meta {
+                if let Lit::Str(ref case) = value.lit {
+                    if value.path.is_ident("rename_all")
+                        && RenameAll::parse(&case.value()).is_none()
+                    {
+                        return Err(syn::Error::new_spanned(
+                            case,
+                            format!("unsupported `rename_all` case `{}`", case.value()),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/**
+Fail if the container is `transparent` but isn't a struct with a single field.
+*/
+pub(crate) fn transparent(container: &DeriveInput) -> syn::Result<()> {
+    let meta = match find("transparent", &container.attrs) {
+        Some(meta) => meta,
+        None => return Ok(()),
+    };
+
+    let msg = "`transparent` is only supported on structs with a single field";
+
+    match container.data {
+        Data::Struct(ref data) if data.fields.len() == 1 => Ok(()),
+        Data::Struct(ref data) if !data.fields.is_empty() => {
+            Err(syn::Error::new_spanned(&data.fields, msg))
+        }
+        _ => Err(syn::Error::new_spanned(meta, msg)),
+    }
+}
+
 fn name_value(name: &str, meta: &NestedMeta) -> Option<String> {
     if let NestedMeta::Meta(Meta::NameValue(value)) = meta {
         if value.path.is_ident(name) {
@@ -0,0 +1,252 @@
+use crate::{
+    attr::{self, RenameAll},
+    bound,
+};
+use proc_macro::TokenStream;
+use syn::{
+    Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed, FieldsUnnamed, Path,
+    Variant,
+};
+
+pub(crate) fn derive(input: DeriveInput) -> TokenStream {
+    if let Err(err) = attr::unsupported(&input, &["stream_with", "flatten"], "Describe")
+        .and_then(|_| attr::named_only(&input, &["skip_if"]))
+        .and_then(|_| attr::not_newtype(&input, &["skip"]))
+        .and_then(|_| attr::transparent(&input))
+        .and_then(|_| attr::rename_all_cases(&input))
+    {
+        return TokenStream::from(err.to_compile_error());
+    }
+
+    let tag = attr::container_tag(&input);
+    let rename_all = attr::container_rename_all(&input);
+    let ident = &input.ident;
+    let label = ident.to_string();
+
+    let schema = match &input.data {
+        // Transparent types share the schema of their single field
+        Data::Struct(DataStruct { ref fields, .. }) if attr::container_transparent(&input) => {
+            let ty = &fields.iter().next().unwrap().ty;
+
+            quote!(*<#ty as sval::schema::Describe>::SCHEMA)
+        }
+        Data::Struct(DataStruct { ref fields, .. }) if fields.is_empty() => {
+            describe_tag(tag.as_ref(), &label, None)
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => describe_record(tag.as_ref(), &label, None, rename_all, fields),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) if fields.unnamed.len() == 1 => describe_newtype(tag.as_ref(), &label, None, fields),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) => describe_tuple(tag.as_ref(), &label, None, fields),
+        Data::Enum(DataEnum { variants, .. }) => {
+            describe_enum(tag.as_ref(), &label, rename_all, variants.iter())
+        }
+        _ => panic!("unimplemented"),
+    };
+
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    let bound = parse_quote!(sval::schema::Describe);
+    let bounded_where_clause = bound::where_clause_with_bound(&input.generics, bound);
+
+    TokenStream::from(quote! {
+        const _: () = {
+            extern crate sval;
+
+            impl #impl_generics sval::schema::Describe for #ident #ty_generics #bounded_where_clause {
+                const SCHEMA: &'static sval::schema::Schema = &#schema;
+            }
+        };
+    })
+}
+
+fn describe_enum<'a>(
+    tag: Option<&Path>,
+    label: &str,
+    rename_all: Option<RenameAll>,
+    variants: impl Iterator<Item = &'a Variant> + 'a,
+) -> proc_macro2::TokenStream {
+    let (enum_tag, enum_label, enum_index) = quote_tag_label_index(tag, label, None);
+
+    let mut variant_schemas = Vec::new();
+
+    for variant in variants {
+        let index = Some(variant_schemas.len());
+        let label = attr::variant_name(variant, rename_all);
+
+        variant_schemas.push(match variant.fields {
+            Fields::Named(ref fields) => describe_record(
+                tag,
+                &label,
+                index,
+                attr::variant_rename_all(variant),
+                fields,
+            ),
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                describe_newtype(tag, &label, index, fields)
+            }
+            Fields::Unnamed(ref fields) => describe_tuple(tag, &label, index, fields),
+            Fields::Unit => describe_tag(tag, &label, index),
+        });
+    }
+
+    quote!(sval::schema::Schema::Enum {
+        tag: #enum_tag,
+        label: #enum_label,
+        index: #enum_index,
+        variants: &[#(#variant_schemas,)*],
+    })
+}
+
+fn describe_record(
+    tag: Option<&Path>,
+    label: &str,
+    index: Option<usize>,
+    rename_all: Option<RenameAll>,
+    fields: &FieldsNamed,
+) -> proc_macro2::TokenStream {
+    let (tag, label, index) = quote_tag_label_index(tag, label, index);
+
+    let mut describe_field = Vec::new();
+
+    for field in &fields.named {
+        if attr::field_skip(field) {
+            continue;
+        }
+
+        let label = attr::field_name(field, rename_all);
+
+        let field_tag = quote_tag(attr::field_tag(field).as_ref());
+        let value = describe_field_value(field);
+        let optional = attr::field_skip_if(field).is_some();
+
+        describe_field.push(quote!(sval::schema::Field {
+            tag: #field_tag,
+            label: Some(sval::Label::new(#label)),
+            index: None,
+            value: #value,
+            optional: #optional,
+        }));
+    }
+
+    quote!(sval::schema::Schema::Record {
+        tag: #tag,
+        label: #label,
+        index: #index,
+        fields: &[#(#describe_field,)*],
+    })
+}
+
+fn describe_newtype(
+    tag: Option<&Path>,
+    label: &str,
+    index: Option<usize>,
+    fields: &FieldsUnnamed,
+) -> proc_macro2::TokenStream {
+    let (tag, label, index) = quote_tag_label_index(tag, label, index);
+
+    let ty = &fields.unnamed[0].ty;
+
+    quote!(sval::schema::Schema::Tagged {
+        tag: #tag,
+        label: #label,
+        index: #index,
+        value: <#ty as sval::schema::Describe>::SCHEMA,
+    })
+}
+
+fn describe_tuple(
+    tag: Option<&Path>,
+    label: &str,
+    index: Option<usize>,
+    fields: &FieldsUnnamed,
+) -> proc_macro2::TokenStream {
+    let (tag, label, index) = quote_tag_label_index(tag, label, index);
+
+    let mut describe_field = Vec::new();
+
+    for (index, field) in fields
+        .unnamed
+        .iter()
+        .filter(|field| !attr::field_skip(field))
+        .enumerate()
+    {
+        let field_tag = quote_tag(attr::field_tag(field).as_ref());
+        let value = describe_field_value(field);
+
+        describe_field.push(quote!(sval::schema::Field {
+            tag: #field_tag,
+            label: None,
+            index: Some(sval::Index::new(#index)),
+            value: #value,
+            optional: false,
+        }));
+    }
+
+    quote!(sval::schema::Schema::Tuple {
+        tag: #tag,
+        label: #label,
+        index: #index,
+        fields: &[#(#describe_field,)*],
+    })
+}
+
+fn describe_tag(tag: Option<&Path>, label: &str, index: Option<usize>) -> proc_macro2::TokenStream {
+    let (tag, label, index) = quote_tag_label_index(tag, label, index);
+
+    quote!(sval::schema::Schema::Tag {
+        tag: #tag,
+        label: #label,
+        index: #index,
+    })
+}
+
+fn describe_field_value(field: &Field) -> proc_macro2::TokenStream {
+    let ty = &field.ty;
+
+    if let Some(tag) = attr::field_data_tag(field) {
+        quote!(&sval::schema::Schema::Tagged {
+            tag: Some(#tag),
+            label: None,
+            index: None,
+            value: <#ty as sval::schema::Describe>::SCHEMA,
+        })
+    } else {
+        quote!(<#ty as sval::schema::Describe>::SCHEMA)
+    }
+}
+
+fn quote_tag_label_index(
+    tag: Option<&Path>,
+    label: &str,
+    index: Option<usize>,
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
+    let tag = quote_tag(tag);
+
+    let label = quote!(Some(sval::Label::new(#label)));
+
+    let index = match index {
+        Some(index) => quote!(Some(sval::Index::new(#index))),
+        None => quote!(None),
+    };
+
+    (tag, label, index)
+}
+
+fn quote_tag(tag: Option<&Path>) -> proc_macro2::TokenStream {
+    match tag {
+        Some(tag) => quote!(Some(#tag)),
+        None => quote!(None),
+    }
+}
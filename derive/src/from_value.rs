@@ -0,0 +1,929 @@
+use crate::{
+    attr::{self, RenameAll},
+    bound,
+};
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use syn::{
+    Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed, Generics, Ident,
+    Path, Type, Visibility, WhereClause,
+};
+
+pub(crate) fn derive(input: DeriveInput) -> TokenStream {
+    if let Err(err) = attr::unsupported(&input, &["stream_with", "flatten"], "FromValue")
+        .and_then(|_| attr::named_only(&input, &["skip_if"]))
+        .and_then(|_| attr::not_newtype(&input, &["skip"]))
+        .and_then(|_| attr::transparent(&input))
+        .and_then(|_| attr::rename_all_cases(&input))
+    {
+        return TokenStream::from(err.to_compile_error());
+    }
+
+    let target = Target::new(&input.vis, &input.ident, &input.generics);
+
+    let compound = Ident::new("__Compound", Span::call_site());
+
+    let ident = &input.ident;
+    let label = ident.to_string();
+    let rename_all = attr::container_rename_all(&input);
+
+    let builder = match &input.data {
+        // Transparent types are built directly from their single field
+        Data::Struct(DataStruct { ref fields, .. }) if attr::container_transparent(&input) => {
+            let field = fields.iter().next().unwrap();
+
+            let ctor = match field.ident {
+                Some(ref field_ident) => quote!((|field0| #ident { #field_ident: field0 })),
+                None => quote!(#ident),
+            };
+
+            build_newtype(&target, &compound, ctor, &label, &field.ty)
+        }
+        Data::Struct(DataStruct { ref fields, .. }) if fields.is_empty() => {
+            build_unit(&target, &compound, quote!(#ident), &label)
+        }
+        Data::Struct(DataStruct {
+            fields: Fields::Named(ref fields),
+            ..
+        }) => build_record(&target, &compound, quote!(#ident), rename_all, fields),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) if fields.unnamed.len() == 1 => build_newtype(
+            &target,
+            &compound,
+            quote!(#ident),
+            &label,
+            &fields.unnamed[0].ty,
+        ),
+        Data::Struct(DataStruct {
+            fields: Fields::Unnamed(ref fields),
+            ..
+        }) => build_tuple(&target, &compound, quote!(#ident), fields),
+        Data::Enum(DataEnum { variants, .. }) => {
+            build_enum(&target, &compound, rename_all, variants.iter())
+        }
+        _ => panic!("unimplemented"),
+    };
+
+    let Target {
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = &target;
+
+    TokenStream::from(quote! {
+        const _: () = {
+            extern crate sval;
+
+            #builder
+
+            impl #impl_generics sval::FromValue for #ident #ty_generics #where_clause {
+                type Builder = sval::build::CompoundBuilder<#compound #ty_generics>;
+
+                fn builder() -> Self::Builder {
+                    sval::build::CompoundBuilder::new(Default::default())
+                }
+            }
+        };
+    })
+}
+
+struct Target {
+    vis: Visibility,
+    output: proc_macro2::TokenStream,
+    params: proc_macro2::TokenStream,
+    impl_generics: proc_macro2::TokenStream,
+    ty_generics: proc_macro2::TokenStream,
+    where_clause: WhereClause,
+}
+
+impl Target {
+    fn new(vis: &Visibility, ident: &Ident, generics: &Generics) -> Self {
+        let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+        let bound = parse_quote!(sval::FromValue);
+        let where_clause = bound::where_clause_with_bound(generics, bound);
+
+        let params = &generics.params;
+
+        Target {
+            vis: vis.clone(),
+            output: quote!(#ident #ty_generics),
+            params: quote!(<#params>),
+            impl_generics: quote!(#impl_generics),
+            ty_generics: quote!(#ty_generics),
+            where_clause,
+        }
+    }
+
+    /**
+    Define a compound with the given fields and implement `Default` for it.
+
+    The compound has the same visibility as the type it builds, and carries a
+    marker for it so that all generic parameters are used.
+    */
+    fn compound(
+        &self,
+        compound: &Ident,
+        fields: proc_macro2::TokenStream,
+        default: proc_macro2::TokenStream,
+    ) -> proc_macro2::TokenStream {
+        let Target {
+            vis,
+            output,
+            params,
+            impl_generics,
+            ty_generics,
+            where_clause,
+        } = self;
+
+        quote! {
+            #vis struct #compound #params #where_clause {
+                #fields
+                __marker: core::marker::PhantomData<fn() -> #output>,
+            }
+
+            impl #impl_generics Default for #compound #ty_generics #where_clause {
+                fn default() -> Self {
+                    #compound {
+                        #default
+                        __marker: core::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn build_record(
+    target: &Target,
+    compound: &Ident,
+    ctor: proc_macro2::TokenStream,
+    rename_all: Option<RenameAll>,
+    fields: &FieldsNamed,
+) -> proc_macro2::TokenStream {
+    let Target {
+        output,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = target;
+
+    let mut field_ident = Vec::new();
+    let mut field_ty = Vec::new();
+    let mut field_label = Vec::new();
+    let mut field_index = Vec::new();
+    let mut field_token = Vec::new();
+    let mut field_finish = Vec::new();
+
+    for field in &fields.named {
+        let ident = field.ident.as_ref().unwrap();
+
+        // Skipped fields are never streamed, so they're always defaulted
+        if attr::field_skip(field) {
+            field_finish.push(quote!(#ident: Default::default()));
+            continue;
+        }
+
+        let index = field_index.len();
+        let label = attr::field_name(field, rename_all);
+
+        field_token.push(build_field_token(
+            ident,
+            attr::field_data_tag(field).as_ref(),
+            quote!(|err: sval::Error| err.at_label(&sval::Label::new(#label))),
+        ));
+
+        // Conditionally skipped fields are defaulted if they're missing
+        field_finish.push(if attr::field_skip_if(field).is_some() {
+            quote!(#ident: self.#ident.take().unwrap_or_default())
+        } else {
+            let missing = format!("missing field `{}`", label);
+
+            quote!(#ident: self.#ident.take().ok_or_else(|| {
+                sval::Error::invalid_value().with_message(#missing)
+            })?)
+        });
+
+        field_ident.push(ident.clone());
+        field_ty.push(field.ty.clone());
+        field_label.push(label);
+        field_index.push(index);
+    }
+
+    let field_count = field_index.len();
+
+    let definition = target.compound(
+        compound,
+        quote!(
+            #(#field_ident: sval::build::Slot<#field_ty>,)*
+            __active: Option<usize>,
+            __key: Option<sval::build::LabelMatcher<#field_count>>,
+            __tagged: bool,
+        ),
+        quote!(
+            #(#field_ident: sval::build::Slot::new(),)*
+            __active: None,
+            __key: None,
+            __tagged: false,
+        ),
+    );
+
+    quote! {
+        #definition
+
+        impl #impl_generics sval::build::Compound for #compound #ty_generics #where_clause {
+            type Output = #output;
+
+            fn nested(&mut self) -> Option<&mut dyn sval::build::NestedBuilder> {
+                match self.__active {
+                    #(Some(#field_index) => self.#field_ident.nested(),)*
+                    _ => None,
+                }
+            }
+
+            fn token(&mut self, token: sval::build::Token) -> sval::Result<sval::build::Handled> {
+                match (self.__active, token) {
+                    (None, sval::build::Token::RecordBegin(_, _, _, _))
+                    | (None, sval::build::Token::RecordEnd(_, _, _)) => {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::RecordValueBegin(_, label)) => {
+                        match label.as_str() {
+                            #(
+                                #field_label => {
+                                    self.__active = Some(#field_index);
+
+                                    Ok(sval::build::Handled::Consumed)
+                                }
+                            )*
+                            _ => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown field `{}`",
+                                label.as_str()
+                            ))),
+                        }
+                    }
+                    // The record may also be a map with text keys, like a JSON object
+                    (None, sval::build::Token::MapBegin(_))
+                    | (None, sval::build::Token::MapEnd)
+                    | (Some(_), sval::build::Token::MapValueBegin) => {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::MapKeyBegin) if self.__key.is_none() => {
+                        self.__key = Some(sval::build::LabelMatcher::new());
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextBegin(_))
+                    | (None, sval::build::Token::TextEnd)
+                        if self.__key.is_some() =>
+                    {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextFragment(fragment)) if self.__key.is_some() => {
+                        if let Some(ref mut key) = self.__key {
+                            key.fragment(&[#(#field_label,)*], fragment);
+                        }
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::MapKeyEnd) if self.__key.is_some() => {
+                        let labels: [&str; #field_count] = [#(#field_label,)*];
+                        let key = self.__key.take().unwrap_or_default();
+
+                        match key.finish(&labels) {
+                            Some(index) => {
+                                self.__active = Some(index);
+
+                                Ok(sval::build::Handled::Consumed)
+                            }
+                            None => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown field `{}`",
+                                key.text(&labels)
+                            ))),
+                        }
+                    }
+                    (Some(active), sval::build::Token::RecordValueEnd(_, _))
+                    | (Some(active), sval::build::Token::MapValueEnd) => {
+                        match active {
+                            #(
+                                #field_index => if self.#field_ident.is_building() {
+                                    self.#field_ident.end().map_err(|err| err.at_label(&sval::Label::new(#field_label)))?;
+                                },
+                            )*
+                            _ => (),
+                        }
+
+                        self.__active = None;
+                        self.__tagged = false;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    #(
+                        (Some(#field_index), token) => #field_token,
+                    )*
+                    _ => Err(sval::Error::unsupported().with_message("expected a record")),
+                }
+            }
+
+            fn nested_error(&self, err: sval::Error) -> sval::Error {
+                match self.__active {
+                    #(Some(#field_index) => err.at_label(&sval::Label::new(#field_label)),)*
+                    _ => err,
+                }
+            }
+
+            fn finish(mut self) -> sval::Result<Self::Output> {
+                Ok(#ctor {
+                    #(#field_finish,)*
+                })
+            }
+        }
+    }
+}
+
+fn build_tuple(
+    target: &Target,
+    compound: &Ident,
+    ctor: proc_macro2::TokenStream,
+    fields: &FieldsUnnamed,
+) -> proc_macro2::TokenStream {
+    let Target {
+        output,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = target;
+
+    let field_count = fields
+        .unnamed
+        .iter()
+        .filter(|field| !attr::field_skip(field))
+        .count();
+    let field_missing = format!("expected a tuple with {} values", field_count);
+
+    let mut field_ident = Vec::new();
+    let mut field_ty = Vec::new();
+    let mut field_index = Vec::new();
+    let mut field_token = Vec::new();
+    let mut field_finish = Vec::new();
+
+    for field in &fields.unnamed {
+        if attr::field_skip(field) {
+            field_finish.push(quote!(Default::default()));
+            continue;
+        }
+
+        let index = field_index.len();
+        let ident = Ident::new(&format!("field{}", index), Span::call_site());
+
+        field_finish.push(quote!(self.#ident.take().ok_or_else(|| {
+            sval::Error::invalid_value().with_message(#field_missing)
+        })?));
+
+        field_token.push(build_field_token(
+            &ident,
+            attr::field_data_tag(field).as_ref(),
+            quote!(|err: sval::Error| err.at_index(&sval::Index::new(#index))),
+        ));
+
+        field_ident.push(ident);
+        field_ty.push(field.ty.clone());
+        field_index.push(index);
+    }
+
+    let definition = target.compound(
+        compound,
+        quote!(
+            #(#field_ident: sval::build::Slot<#field_ty>,)*
+            __active: Option<usize>,
+            __index: usize,
+            __tagged: bool,
+        ),
+        quote!(
+            #(#field_ident: sval::build::Slot::new(),)*
+            __active: None,
+            __index: 0,
+            __tagged: false,
+        ),
+    );
+
+    quote! {
+        #definition
+
+        impl #impl_generics sval::build::Compound for #compound #ty_generics #where_clause {
+            type Output = #output;
+
+            fn nested(&mut self) -> Option<&mut dyn sval::build::NestedBuilder> {
+                match self.__active {
+                    #(Some(#field_index) => self.#field_ident.nested(),)*
+                    _ => None,
+                }
+            }
+
+            fn token(&mut self, token: sval::build::Token) -> sval::Result<sval::build::Handled> {
+                match (self.__active, token) {
+                    (None, sval::build::Token::TupleBegin(_, _, _, _))
+                    | (None, sval::build::Token::TupleEnd(_, _, _))
+                    | (None, sval::build::Token::SeqBegin(_))
+                    | (None, sval::build::Token::SeqEnd) => Ok(sval::build::Handled::Consumed),
+                    (None, sval::build::Token::TupleValueBegin(_, _))
+                    | (None, sval::build::Token::SeqValueBegin) => {
+                        if self.__index >= #field_count {
+                            return Err(sval::Error::invalid_value().with_message(#field_missing));
+                        }
+
+                        self.__active = Some(self.__index);
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (Some(active), sval::build::Token::TupleValueEnd(_, _))
+                    | (Some(active), sval::build::Token::SeqValueEnd) => {
+                        match active {
+                            #(
+                                #field_index => if self.#field_ident.is_building() {
+                                    self.#field_ident.end().map_err(|err| err.at_index(&sval::Index::new(#field_index)))?;
+                                },
+                            )*
+                            _ => (),
+                        }
+
+                        self.__active = None;
+                        self.__index += 1;
+                        self.__tagged = false;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    #(
+                        (Some(#field_index), token) => #field_token,
+                    )*
+                    _ => Err(sval::Error::unsupported().with_message("expected a tuple")),
+                }
+            }
+
+            fn nested_error(&self, err: sval::Error) -> sval::Error {
+                match self.__active {
+                    Some(index) => err.at_index(&sval::Index::new(index)),
+                    None => err,
+                }
+            }
+
+            fn finish(mut self) -> sval::Result<Self::Output> {
+                Ok(#ctor(
+                    #(#field_finish,)*
+                ))
+            }
+        }
+    }
+}
+
+fn build_newtype(
+    target: &Target,
+    compound: &Ident,
+    ctor: proc_macro2::TokenStream,
+    label: &str,
+    field_ty: &Type,
+) -> proc_macro2::TokenStream {
+    let Target {
+        output,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = target;
+
+    let definition = target.compound(
+        compound,
+        quote!(
+            field0: sval::build::Slot<#field_ty>,
+            __tagged: bool,
+        ),
+        quote!(
+            field0: sval::build::Slot::new(),
+            __tagged: false,
+        ),
+    );
+
+    quote! {
+        #definition
+
+        impl #impl_generics sval::build::Compound for #compound #ty_generics #where_clause {
+            type Output = #output;
+
+            fn nested(&mut self) -> Option<&mut dyn sval::build::NestedBuilder> {
+                self.field0.nested()
+            }
+
+            fn token(&mut self, token: sval::build::Token) -> sval::Result<sval::build::Handled> {
+                match token {
+                    // The newtype is wrapped in a tag with its label
+                    // If there's no tag then the value is built directly
+                    sval::build::Token::TaggedBegin(_, Some(label), _)
+                        if !self.__tagged && !self.field0.is_building() && label.as_str() == #label =>
+                    {
+                        self.__tagged = true;
+                        self.field0.begin()?;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    sval::build::Token::TaggedEnd(_, _, _) if self.__tagged && self.field0.is_building() => {
+                        self.field0.end()?;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    _ if !self.__tagged && !self.field0.is_building() => {
+                        self.field0.begin()?;
+
+                        Ok(sval::build::Handled::Forward)
+                    }
+                    _ => Err(sval::Error::unsupported().with_message(concat!("expected ", #label))),
+                }
+            }
+
+            fn finish(mut self) -> sval::Result<Self::Output> {
+                Ok(#ctor(self.field0.finish()?))
+            }
+        }
+    }
+}
+
+fn build_unit(
+    target: &Target,
+    compound: &Ident,
+    ctor: proc_macro2::TokenStream,
+    label: &str,
+) -> proc_macro2::TokenStream {
+    let Target {
+        output,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = target;
+
+    let definition = target.compound(compound, quote!(__built: bool,), quote!(__built: false,));
+
+    quote! {
+        #definition
+
+        impl #impl_generics sval::build::Compound for #compound #ty_generics #where_clause {
+            type Output = #output;
+
+            fn nested(&mut self) -> Option<&mut dyn sval::build::NestedBuilder> {
+                None
+            }
+
+            fn token(&mut self, token: sval::build::Token) -> sval::Result<sval::build::Handled> {
+                match token {
+                    sval::build::Token::Tag(_, _, _) | sval::build::Token::Null if !self.__built => {
+                        self.__built = true;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    _ => Err(sval::Error::unsupported().with_message(concat!("expected ", #label))),
+                }
+            }
+
+            fn finish(self) -> sval::Result<Self::Output> {
+                if self.__built {
+                    Ok(#ctor)
+                } else {
+                    Err(sval::Error::invalid_value().with_message("missing value"))
+                }
+            }
+        }
+    }
+}
+
+fn build_enum<'a>(
+    target: &Target,
+    compound: &Ident,
+    rename_all: Option<RenameAll>,
+    variants: impl Iterator<Item = &'a syn::Variant> + 'a,
+) -> proc_macro2::TokenStream {
+    let Target {
+        output,
+        impl_generics,
+        ty_generics,
+        where_clause,
+        ..
+    } = target;
+
+    let mut unit_label = Vec::new();
+    let mut unit_ident = Vec::new();
+
+    let mut variant_definition = Vec::new();
+    let mut variant_field = Vec::new();
+    let mut variant_compound = Vec::new();
+    let mut variant_label = Vec::new();
+    let mut variant_index = Vec::new();
+
+    for variant in variants {
+        let ident = &variant.ident;
+        let label = attr::variant_name(variant, rename_all);
+
+        let ctor = quote!(Self::Output::#ident);
+
+        let nested_compound = Ident::new(&format!("__Compound{}", ident), Span::call_site());
+
+        variant_definition.push(match variant.fields {
+            Fields::Named(ref fields) => build_record(
+                target,
+                &nested_compound,
+                ctor,
+                attr::variant_rename_all(variant),
+                fields,
+            ),
+            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => build_newtype(
+                target,
+                &nested_compound,
+                ctor,
+                &label,
+                &fields.unnamed[0].ty,
+            ),
+            Fields::Unnamed(ref fields) => build_tuple(target, &nested_compound, ctor, fields),
+            Fields::Unit => {
+                unit_label.push(label);
+                unit_ident.push(ident.clone());
+
+                continue;
+            }
+        });
+
+        variant_field.push(Ident::new(
+            &format!("variant{}", variant_index.len()),
+            Span::call_site(),
+        ));
+        variant_compound.push(nested_compound);
+        variant_label.push(label);
+        variant_index.push(variant_index.len());
+    }
+
+    // Unit variants are matched by index before other variants
+    let unit_count = unit_label.len();
+    let unit_index = (0..unit_count).collect::<Vec<_>>();
+    let label_count = unit_count + variant_label.len();
+
+    let unit_value = quote!(match index {
+        #(#unit_index => Some(Self::Output::#unit_ident),)*
+        _ => None,
+    });
+
+    let definition = target.compound(
+        compound,
+        quote!(
+            #(#variant_field: Option<sval::build::CompoundBuilder<#variant_compound #ty_generics>>,)*
+            __active: Option<usize>,
+            __value: Option<#output>,
+            __key: Option<sval::build::LabelMatcher<#label_count>>,
+            __pending: Option<usize>,
+            __map: bool,
+        ),
+        quote!(
+            #(#variant_field: None,)*
+            __active: None,
+            __value: None,
+            __key: None,
+            __pending: None,
+            __map: false,
+        ),
+    );
+
+    quote! {
+        #(#variant_definition)*
+
+        #definition
+
+        impl #impl_generics #compound #ty_generics #where_clause {
+            fn begin_variant(&mut self, active: usize) {
+                match active {
+                    #(
+                        #variant_index => self.#variant_field = Some(sval::build::CompoundBuilder::new(Default::default())),
+                    )*
+                    _ => (),
+                }
+
+                self.__active = Some(active);
+            }
+
+            fn finish_variant(&mut self, active: usize) -> sval::Result<#output> {
+                match active {
+                    #(
+                        #variant_index => match self.#variant_field.take() {
+                            Some(builder) => sval::build::Builder::finish(builder),
+                            None => Err(sval::Error::invalid_value().with_message("missing value")),
+                        },
+                    )*
+                    _ => Err(sval::Error::invalid_value().with_message("missing value")),
+                }
+            }
+        }
+
+        impl #impl_generics sval::build::Compound for #compound #ty_generics #where_clause {
+            type Output = #output;
+
+            fn nested(&mut self) -> Option<&mut dyn sval::build::NestedBuilder> {
+                match self.__active {
+                    #(
+                        Some(#variant_index) => self
+                            .#variant_field
+                            .as_mut()
+                            .map(|builder| builder as &mut dyn sval::build::NestedBuilder),
+                    )*
+                    _ => None,
+                }
+            }
+
+            fn token(&mut self, token: sval::build::Token) -> sval::Result<sval::build::Handled> {
+                match (self.__active, token) {
+                    (None, sval::build::Token::EnumBegin(_, _, _))
+                    | (None, sval::build::Token::EnumEnd(_, _, _)) => {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (Some(active), sval::build::Token::EnumEnd(_, _, _)) => {
+                        self.__value = Some(self.finish_variant(active)?);
+                        self.__active = None;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::Tag(_, Some(label), _)) if self.__value.is_none() => {
+                        match label.as_str() {
+                            #(
+                                #unit_label => {
+                                    self.__value = Some(Self::Output::#unit_ident);
+
+                                    Ok(sval::build::Handled::Consumed)
+                                }
+                            )*
+                            _ => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown variant `{}`",
+                                label.as_str()
+                            ))),
+                        }
+                    }
+                    (None, sval::build::Token::TaggedBegin(_, Some(label), _))
+                    | (None, sval::build::Token::RecordBegin(_, Some(label), _, _))
+                    | (None, sval::build::Token::TupleBegin(_, Some(label), _, _))
+                        if self.__value.is_none() =>
+                    {
+                        match label.as_str() {
+                            #(
+                                #variant_label => {
+                                    self.begin_variant(#variant_index);
+
+                                    Ok(sval::build::Handled::Forward)
+                                }
+                            )*
+                            _ => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown variant `{}`",
+                                label.as_str()
+                            ))),
+                        }
+                    }
+                    // The enum may also be externally tagged, like in JSON, where unit variants
+                    // are text and other variants are a map with a single entry
+                    (None, sval::build::Token::MapBegin(_)) if self.__value.is_none() && !self.__map => {
+                        self.__map = true;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::MapKeyBegin)
+                        if self.__map && self.__value.is_none() && self.__pending.is_none() && self.__key.is_none() =>
+                    {
+                        self.__key = Some(sval::build::LabelMatcher::new());
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextBegin(_)) if self.__key.is_some() => {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextBegin(_)) if self.__value.is_none() && !self.__map => {
+                        self.__key = Some(sval::build::LabelMatcher::new());
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextFragment(fragment)) if self.__key.is_some() => {
+                        if let Some(ref mut key) = self.__key {
+                            key.fragment(&[#(#unit_label,)* #(#variant_label,)*], fragment);
+                        }
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextEnd) if self.__key.is_some() && self.__map => {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (None, sval::build::Token::TextEnd) if self.__key.is_some() => {
+                        let labels: [&str; #label_count] = [#(#unit_label,)* #(#variant_label,)*];
+                        let key = self.__key.take().unwrap_or_default();
+
+                        match key.finish(&labels).and_then(|index| #unit_value) {
+                            Some(value) => {
+                                self.__value = Some(value);
+
+                                Ok(sval::build::Handled::Consumed)
+                            }
+                            None => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown unit variant `{}`",
+                                key.text(&labels)
+                            ))),
+                        }
+                    }
+                    (None, sval::build::Token::MapKeyEnd) if self.__key.is_some() => {
+                        let labels: [&str; #label_count] = [#(#unit_label,)* #(#variant_label,)*];
+                        let key = self.__key.take().unwrap_or_default();
+
+                        match key.finish(&labels) {
+                            Some(index) if index < #unit_count => {
+                                self.__value = #unit_value;
+
+                                Ok(sval::build::Handled::Consumed)
+                            }
+                            Some(index) => {
+                                self.__pending = Some(index - #unit_count);
+
+                                Ok(sval::build::Handled::Consumed)
+                            }
+                            None => Err(sval::Error::invalid_value().with_message(format_args!(
+                                "unknown variant `{}`",
+                                key.text(&labels)
+                            ))),
+                        }
+                    }
+                    (None, sval::build::Token::MapValueBegin) if self.__map => {
+                        if let Some(pending) = self.__pending.take() {
+                            self.begin_variant(pending);
+                        }
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    (Some(active), sval::build::Token::MapValueEnd) if self.__map => {
+                        self.__value = Some(self.finish_variant(active)?);
+                        self.__active = None;
+
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    // The value of a unit variant in a map is null
+                    (None, sval::build::Token::Null)
+                    | (None, sval::build::Token::MapValueEnd)
+                    | (None, sval::build::Token::MapEnd)
+                        if self.__map && self.__value.is_some() =>
+                    {
+                        Ok(sval::build::Handled::Consumed)
+                    }
+                    _ => Err(sval::Error::unsupported().with_message("expected an enum")),
+                }
+            }
+
+            fn finish(mut self) -> sval::Result<Self::Output> {
+                // If the enum wasn't wrapped then the variant may still be active
+                if let Some(active) = self.__active.take() {
+                    return self.finish_variant(active);
+                }
+
+                self.__value
+                    .ok_or_else(|| sval::Error::invalid_value().with_message("missing value"))
+            }
+        }
+    }
+}
+
+/**
+Handle a token for a field that isn't currently being built.
+
+The field's value may be wrapped in a data tag, which is consumed by the compound
+rather than its builder. Any other token begins the field's value.
+*/
+fn build_field_token(
+    ident: &Ident,
+    data_tag: Option<&Path>,
+    at: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let tagged = data_tag.map(|tag| {
+        quote!(
+            sval::build::Token::TaggedBegin(Some(tag), _, _) if !self.__tagged && *tag == #tag => {
+                self.#ident.begin().map_err(#at)?;
+                self.__tagged = true;
+
+                Ok(sval::build::Handled::Consumed)
+            }
+            sval::build::Token::TaggedEnd(Some(tag), _, _) if self.__tagged && *tag == #tag => {
+                self.#ident.end().map_err(#at)?;
+
+                Ok(sval::build::Handled::Consumed)
+            }
+        )
+    });
+
+    quote!(match token {
+        #tagged
+        _ => {
+            self.#ident.begin().map_err(#at)?;
+
+            Ok(sval::build::Handled::Forward)
+        }
+    })
+}
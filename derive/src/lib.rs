@@ -9,6 +9,8 @@ extern crate syn;
 
 mod attr;
 mod bound;
+mod describe;
+mod from_value;
 mod value;
 
 use proc_macro::TokenStream;
@@ -18,3 +20,13 @@ use syn::DeriveInput;
 pub fn derive_value(input: TokenStream) -> TokenStream {
     value::derive(parse_macro_input!(input as DeriveInput))
 }
+
+#[proc_macro_derive(FromValue, attributes(sval))]
+pub fn derive_from_value(input: TokenStream) -> TokenStream {
+    from_value::derive(parse_macro_input!(input as DeriveInput))
+}
+
+#[proc_macro_derive(Describe, attributes(sval))]
+pub fn derive_describe(input: TokenStream) -> TokenStream {
+    describe::derive(parse_macro_input!(input as DeriveInput))
+}
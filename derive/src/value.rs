@@ -1,40 +1,86 @@
-use crate::{attr, bound};
+use crate::{
+    attr::{self, RenameAll},
+    bound,
+};
 use proc_macro::TokenStream;
 use syn::{
-    spanned::Spanned, Data, DataEnum, DataStruct, DeriveInput, Fields, FieldsNamed, FieldsUnnamed,
-    Generics, Ident, Path, Variant,
+    spanned::Spanned, Data, DataEnum, DataStruct, DeriveInput, Field, Fields, FieldsNamed,
+    FieldsUnnamed, Generics, Ident, Member, Path, Variant,
 };
 
 pub(crate) fn derive(input: DeriveInput) -> TokenStream {
+    if let Err(err) = attr::named_only(&input, &["flatten", "skip_if"])
+        .and_then(|_| attr::not_newtype(&input, &["skip"]))
+        .and_then(|_| attr::not_flatten(&input, &["stream_with", "tag", "field_tag", "rename"]))
+        .and_then(|_| attr::transparent(&input))
+        .and_then(|_| attr::rename_all_cases(&input))
+    {
+        return TokenStream::from(err.to_compile_error());
+    }
+
     let tag = attr::container_tag(&input);
+    let rename_all = attr::container_rename_all(&input);
+
+    if let Some(stream_with) = attr::container_stream_with(&input) {
+        return derive_stream_with(&stream_with, &input.ident, &input.generics);
+    }
 
     match &input.data {
+        Data::Struct(DataStruct { ref fields, .. }) if attr::container_transparent(&input) => {
+            let field = fields.iter().next().unwrap();
+
+            // Transparent types stream their field as-is, so there's nowhere to put a tag
+            if let Some(tag) =
+                attr::find("tag", &input.attrs).or_else(|| attr::find("tag", &field.attrs))
+            {
+                return TokenStream::from(
+                    syn::Error::new_spanned(tag, "`tag` is not supported on `transparent` types")
+                        .to_compile_error(),
+                );
+            }
+
+            derive_transparent(&input.ident, &input.generics, field)
+        }
         Data::Struct(DataStruct { ref fields, .. }) if fields.len() == 0 => {
             derive_unit_struct(tag.as_ref(), &input.ident, &input.generics)
         }
         Data::Struct(DataStruct {
             fields: Fields::Named(ref fields),
             ..
-        }) => derive_struct(tag.as_ref(), &input.ident, &input.generics, fields),
+        }) => derive_struct(
+            tag.as_ref(),
+            rename_all,
+            &input.ident,
+            &input.generics,
+            fields,
+        ),
         Data::Struct(DataStruct {
             fields: Fields::Unnamed(ref fields),
             ..
-        }) if fields.unnamed.len() == 1 => {
-            derive_newtype(tag.as_ref(), &input.ident, &input.generics)
-        }
+        }) if fields.unnamed.len() == 1 => derive_newtype(
+            tag.as_ref(),
+            &input.ident,
+            &input.generics,
+            &fields.unnamed[0],
+        ),
         Data::Struct(DataStruct {
             fields: Fields::Unnamed(ref fields),
             ..
         }) => derive_tuple(tag.as_ref(), &input.ident, &input.generics, fields),
-        Data::Enum(DataEnum { variants, .. }) => {
-            derive_enum(tag.as_ref(), &input.ident, &input.generics, variants.iter())
-        }
+        Data::Enum(DataEnum { variants, .. }) => derive_enum(
+            tag.as_ref(),
+            rename_all,
+            &input.ident,
+            &input.generics,
+            variants.iter(),
+        ),
         _ => panic!("unimplemented"),
     }
 }
 
 fn derive_struct<'a>(
     tag: Option<&Path>,
+    rename_all: Option<RenameAll>,
     ident: &Ident,
     generics: &Generics,
     fields: &FieldsNamed,
@@ -44,7 +90,14 @@ fn derive_struct<'a>(
     let bound = parse_quote!(sval::Value);
     let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
 
-    let match_arm = stream_record(quote!(#ident), tag, &ident, None, fields);
+    let match_arm = stream_record(
+        quote!(#ident),
+        tag,
+        &ident.to_string(),
+        None,
+        rename_all,
+        fields,
+    );
 
     TokenStream::from(quote! {
         const _: () = {
@@ -69,7 +122,7 @@ fn derive_unit_struct<'a>(tag: Option<&Path>, ident: &Ident, generics: &Generics
     let bound = parse_quote!(sval::Value);
     let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
 
-    let match_arm = stream_tag(quote!(#ident), tag, &ident, None);
+    let match_arm = stream_tag(quote!(#ident), tag, &ident.to_string(), None);
 
     TokenStream::from(quote! {
         const _: () = {
@@ -88,13 +141,67 @@ fn derive_unit_struct<'a>(tag: Option<&Path>, ident: &Ident, generics: &Generics
     })
 }
 
-fn derive_newtype<'a>(tag: Option<&Path>, ident: &Ident, generics: &Generics) -> TokenStream {
+fn derive_stream_with(stream_with: &Path, ident: &Ident, generics: &Generics) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let bound = parse_quote!(sval::Value);
+    let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
+
+    TokenStream::from(quote! {
+        const _: () = {
+            extern crate sval;
+
+            impl #impl_generics sval::Value for #ident #ty_generics #bounded_where_clause {
+                fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+                    #stream_with(self, stream)
+                }
+            }
+        };
+    })
+}
+
+fn derive_transparent(ident: &Ident, generics: &Generics, field: &Field) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+
+    let bound = parse_quote!(sval::Value);
+    let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
+
+    let member = match field.ident {
+        Some(ref ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(0.into()),
+    };
+
+    let stream_field = stream_value(field, quote!(field0));
+
+    TokenStream::from(quote! {
+        const _: () = {
+            extern crate sval;
+
+            impl #impl_generics sval::Value for #ident #ty_generics #bounded_where_clause {
+                fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+                    let field0 = &self.#member;
+
+                    #stream_field
+
+                    Ok(())
+                }
+            }
+        };
+    })
+}
+
+fn derive_newtype<'a>(
+    tag: Option<&Path>,
+    ident: &Ident,
+    generics: &Generics,
+    field: &Field,
+) -> TokenStream {
     let (impl_generics, ty_generics, _) = generics.split_for_impl();
 
     let bound = parse_quote!(sval::Value);
     let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
 
-    let match_arm = stream_newtype(quote!(#ident), tag, &ident, None);
+    let match_arm = stream_newtype(quote!(#ident), tag, &ident.to_string(), None, field);
 
     TokenStream::from(quote! {
         const _: () = {
@@ -124,7 +231,7 @@ fn derive_tuple<'a>(
     let bound = parse_quote!(sval::Value);
     let bounded_where_clause = bound::where_clause_with_bound(&generics, bound);
 
-    let match_arm = stream_tuple(quote!(#ident), tag, &ident, None, fields);
+    let match_arm = stream_tuple(quote!(#ident), tag, &ident.to_string(), None, fields);
 
     TokenStream::from(quote! {
         const _: () = {
@@ -145,11 +252,12 @@ fn derive_tuple<'a>(
 
 fn derive_enum<'a>(
     tag: Option<&Path>,
+    rename_all: Option<RenameAll>,
     ident: &Ident,
     generics: &Generics,
     variants: impl Iterator<Item = &'a Variant> + 'a,
 ) -> TokenStream {
-    let (enum_tag, enum_label, enum_index) = quote_tag_label_index(tag, ident, None);
+    let (enum_tag, enum_label, enum_index) = quote_tag_label_index(tag, &ident.to_string(), None);
 
     let (impl_generics, ty_generics, _) = generics.split_for_impl();
 
@@ -160,32 +268,35 @@ fn derive_enum<'a>(
 
     for variant in variants {
         let variant_ident = &variant.ident;
+        let variant_label = attr::variant_name(variant, rename_all);
 
         variant_match_arms.push(match variant.fields {
             Fields::Named(ref fields) => stream_record(
                 quote!(#ident :: #variant_ident),
                 tag,
-                &variant.ident,
+                &variant_label,
                 Some(variant_match_arms.len()),
+                attr::variant_rename_all(variant),
                 fields,
             ),
             Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => stream_newtype(
                 quote!(#ident :: #variant_ident),
                 tag,
-                &variant.ident,
+                &variant_label,
                 Some(variant_match_arms.len()),
+                &fields.unnamed[0],
             ),
             Fields::Unnamed(ref fields) => stream_tuple(
                 quote!(#ident :: #variant_ident),
                 tag,
-                &variant.ident,
+                &variant_label,
                 Some(variant_match_arms.len()),
                 fields,
             ),
             Fields::Unit => stream_tag(
                 quote!(#ident :: #variant_ident),
                 tag,
-                &variant.ident,
+                &variant_label,
                 Some(variant_match_arms.len()),
             ),
         });
@@ -213,8 +324,9 @@ fn derive_enum<'a>(
 fn stream_record(
     path: proc_macro2::TokenStream,
     tag: Option<&Path>,
-    label: &Ident,
+    label: &str,
     index: Option<usize>,
+    rename_all: Option<RenameAll>,
     fields: &FieldsNamed,
 ) -> proc_macro2::TokenStream {
     let (tag, label, index) = quote_tag_label_index(tag, label, index);
@@ -222,37 +334,65 @@ fn stream_record(
     let mut field_count = 0usize;
     let mut field_ident = Vec::new();
     let mut stream_field = Vec::new();
+    let mut is_count_static = true;
 
     for field in &fields.named {
-        let label = attr::field_name(field);
+        if attr::field_skip(field) {
+            continue;
+        }
+
+        let label = attr::field_name(field, rename_all);
         let label = quote!(&sval::Label::new(#label));
 
         let ident = &field.ident;
 
         let field_tag = quote_tag(attr::field_tag(field).as_ref());
+        let stream_field_value = stream_value(field, quote!(#ident));
+
+        // Flattened fields stream their own entries, so the number of entries is unknown
+        let stream = if attr::field_flatten(field) {
+            is_count_static = false;
 
-        stream_field.push(if let Some(tag) = attr::field_data_tag(field) {
+            quote!({
+                sval::flatten(stream, #ident)?;
+            })
+        } else if let Some(tag) = attr::field_data_tag(field) {
             quote!({
                 stream.record_value_begin(#field_tag, #label)?;
                 stream.tagged_begin(Some(&#tag), None, None)?;
-                stream.value(#ident)?;
+                #stream_field_value
                 stream.tagged_end(Some(&#tag), None, None)?;
                 stream.record_value_end(#field_tag, #label)?;
             })
         } else {
             quote!({
                 stream.record_value_begin(#field_tag, #label)?;
-                stream.value(#ident)?;
+                #stream_field_value
                 stream.record_value_end(#field_tag, #label)?;
             })
+        };
+
+        // Fields that are conditionally skipped also make the number of entries unknown
+        stream_field.push(if let Some(skip_if) = attr::field_skip_if(field) {
+            is_count_static = false;
+
+            quote!(if !#skip_if(#ident) #stream)
+        } else {
+            stream
         });
 
         field_ident.push(ident.clone());
         field_count += 1;
     }
 
-    quote!(#path { #(ref #field_ident,)* } => {
-        stream.record_begin(#tag, #label, #index, Some(#field_count))?;
+    let num_entries = if is_count_static {
+        quote!(Some(#field_count))
+    } else {
+        quote!(None)
+    };
+
+    quote!(#path { #(ref #field_ident,)* .. } => {
+        stream.record_begin(#tag, #label, #index, #num_entries)?;
 
         #(
             #stream_field
@@ -265,14 +405,17 @@ fn stream_record(
 fn stream_newtype(
     path: proc_macro2::TokenStream,
     tag: Option<&Path>,
-    label: &Ident,
+    label: &str,
     index: Option<usize>,
+    field: &Field,
 ) -> proc_macro2::TokenStream {
     let (tag, label, index) = quote_tag_label_index(tag, label, index);
 
+    let stream_field = stream_value(field, quote!(field0));
+
     quote!(#path(ref field0) => {
         stream.tagged_begin(#tag, #label, #index)?;
-        stream.value(field0)?;
+        #stream_field
         stream.tagged_end(#tag, #label, #index)?;
     })
 }
@@ -280,44 +423,50 @@ fn stream_newtype(
 fn stream_tuple(
     path: proc_macro2::TokenStream,
     tag: Option<&Path>,
-    label: &Ident,
+    label: &str,
     index: Option<usize>,
     fields: &FieldsUnnamed,
 ) -> proc_macro2::TokenStream {
     let (tag, label, index) = quote_tag_label_index(tag, label, index);
 
-    let mut field_ident = Vec::new();
+    let mut field_pat = Vec::new();
     let mut stream_field = Vec::new();
     let mut field_count = 0usize;
 
     for field in &fields.unnamed {
+        if attr::field_skip(field) {
+            field_pat.push(quote!(_));
+            continue;
+        }
+
         let index = field_count;
 
         let ident = Ident::new(&format!("field{}", field_count), field.span());
 
         let field_tag = quote_tag(attr::field_tag(field).as_ref());
+        let stream_field_value = stream_value(field, quote!(#ident));
 
         stream_field.push(if let Some(tag) = attr::field_data_tag(field) {
             quote!({
                 stream.tuple_value_begin(#field_tag, &sval::Index::new(#index))?;
                 stream.tagged_begin(Some(&#tag), None, None)?;
-                stream.value(#ident)?;
+                #stream_field_value
                 stream.tagged_end(Some(&#tag), None, None)?;
                 stream.tuple_value_end(#field_tag, &sval::Index::new(#index))?;
             })
         } else {
             quote!({
                 stream.tuple_value_begin(#field_tag, &sval::Index::new(#index))?;
-                stream.value(#ident)?;
+                #stream_field_value
                 stream.tuple_value_end(#field_tag, &sval::Index::new(#index))?;
             })
         });
 
-        field_ident.push(ident);
+        field_pat.push(quote!(ref #ident));
         field_count += 1;
     }
 
-    quote!(#path(#(ref #field_ident,)*) => {
+    quote!(#path(#(#field_pat,)*) => {
         stream.tuple_begin(#tag, #label, #index, Some(#field_count))?;
 
         #(
@@ -331,7 +480,7 @@ fn stream_tuple(
 fn stream_tag(
     path: proc_macro2::TokenStream,
     tag: Option<&Path>,
-    label: &Ident,
+    label: &str,
     index: Option<usize>,
 ) -> proc_macro2::TokenStream {
     let (tag, label, index) = quote_tag_label_index(tag, label, index);
@@ -341,17 +490,25 @@ fn stream_tag(
     })
 }
 
+/**
+Stream the value of a field, using its `stream_with` function if it has one.
+*/
+fn stream_value(field: &Field, ident: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    match attr::field_stream_with(field) {
+        Some(stream_with) => quote!(#stream_with(#ident, stream)?;),
+        None => quote!(stream.value(#ident)?;),
+    }
+}
+
 fn quote_tag_label_index(
     tag: Option<&Path>,
-    label: &Ident,
+    label: &str,
     index: Option<usize>,
 ) -> (
     proc_macro2::TokenStream,
     proc_macro2::TokenStream,
     proc_macro2::TokenStream,
 ) {
-    let label = label.to_string();
-
     let tag = quote_tag(tag);
 
     let label = quote!(Some(&sval::Label::new(#label)));
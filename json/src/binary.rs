@@ -0,0 +1,615 @@
+use core::{
+    fmt::{self, Write},
+    str,
+};
+
+use crate::{tags, Binary};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/**
+Get the binary encoding implied by a tag, if there is one.
+*/
+pub(crate) fn from_tag(tag: Option<&sval::Tag>) -> Option<Binary> {
+    match tag {
+        Some(&tags::BASE64) => Some(Binary::Base64),
+        Some(&tags::BASE64_URL) => Some(Binary::Base64Url),
+        Some(&tags::HEX) => Some(Binary::Hex),
+        _ => None,
+    }
+}
+
+/**
+An incremental encoder for binary values written as strings.
+
+Bytes that don't fill a complete base64 group are carried over
+to the next fragment, so the whole value never needs to be buffered.
+*/
+pub(crate) struct Encoder {
+    binary: Binary,
+    partial: [u8; 3],
+    partial_len: usize,
+}
+
+impl Encoder {
+    pub(crate) fn new(binary: Binary) -> Self {
+        Encoder {
+            binary,
+            partial: [0; 3],
+            partial_len: 0,
+        }
+    }
+
+    pub(crate) fn fragment(&mut self, mut bytes: &[u8], out: impl Write) -> fmt::Result {
+        let mut out = Chunk::new(out);
+
+        match self.binary {
+            Binary::Hex => {
+                for b in bytes {
+                    out.push(HEX[(b >> 4) as usize])?;
+                    out.push(HEX[(b & 0x0f) as usize])?;
+                }
+            }
+            Binary::Base64 | Binary::Base64Url => {
+                // Complete any group left over from the last fragment
+                while self.partial_len > 0 && self.partial_len < 3 && !bytes.is_empty() {
+                    self.partial[self.partial_len] = bytes[0];
+                    self.partial_len += 1;
+                    bytes = &bytes[1..];
+                }
+
+                if self.partial_len == 3 {
+                    let group = self.partial;
+                    self.base64_group(&group, &mut out)?;
+                    self.partial_len = 0;
+                }
+
+                let mut groups = bytes.chunks_exact(3);
+                for group in &mut groups {
+                    self.base64_group(group, &mut out)?;
+                }
+
+                let rest = groups.remainder();
+                self.partial[self.partial_len..self.partial_len + rest.len()].copy_from_slice(rest);
+                self.partial_len += rest.len();
+            }
+            Binary::Array => unreachable!("binary arrays aren't encoded"),
+        }
+
+        out.flush()
+    }
+
+    pub(crate) fn end(self, out: impl Write) -> fmt::Result {
+        let mut out = Chunk::new(out);
+
+        if let Binary::Base64 | Binary::Base64Url = self.binary {
+            let alphabet = self.alphabet();
+
+            match self.partial_len {
+                1 => {
+                    let n = (self.partial[0] as u32) << 16;
+
+                    out.push(alphabet[(n >> 18) as usize & 0x3f])?;
+                    out.push(alphabet[(n >> 12) as usize & 0x3f])?;
+
+                    if let Binary::Base64 = self.binary {
+                        out.push(b'=')?;
+                        out.push(b'=')?;
+                    }
+                }
+                2 => {
+                    let n = ((self.partial[0] as u32) << 16) | ((self.partial[1] as u32) << 8);
+
+                    out.push(alphabet[(n >> 18) as usize & 0x3f])?;
+                    out.push(alphabet[(n >> 12) as usize & 0x3f])?;
+                    out.push(alphabet[(n >> 6) as usize & 0x3f])?;
+
+                    if let Binary::Base64 = self.binary {
+                        out.push(b'=')?;
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        out.flush()
+    }
+
+    fn alphabet(&self) -> &'static [u8; 64] {
+        match self.binary {
+            Binary::Base64Url => BASE64_URL,
+            _ => BASE64,
+        }
+    }
+
+    fn base64_group(&self, group: &[u8], out: &mut Chunk<impl Write>) -> fmt::Result {
+        let alphabet = self.alphabet();
+        let n = ((group[0] as u32) << 16) | ((group[1] as u32) << 8) | (group[2] as u32);
+
+        out.push(alphabet[(n >> 18) as usize & 0x3f])?;
+        out.push(alphabet[(n >> 12) as usize & 0x3f])?;
+        out.push(alphabet[(n >> 6) as usize & 0x3f])?;
+        out.push(alphabet[n as usize & 0x3f])
+    }
+}
+
+/**
+A small buffer of ASCII characters that's written in chunks.
+*/
+struct Chunk<W> {
+    buf: [u8; 64],
+    len: usize,
+    out: W,
+}
+
+impl<W: Write> Chunk<W> {
+    fn new(out: W) -> Self {
+        Chunk {
+            buf: [0; 64],
+            len: 0,
+            out,
+        }
+    }
+
+    fn push(&mut self, b: u8) -> fmt::Result {
+        if self.len == self.buf.len() {
+            self.flush()?;
+        }
+
+        self.buf[self.len] = b;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> fmt::Result {
+        // The buffer only ever contains ASCII
+        self.out
+            .write_str(str::from_utf8(&self.buf[..self.len]).map_err(|_| fmt::Error)?)?;
+        self.len = 0;
+
+        Ok(())
+    }
+}
+
+/**
+An incremental decoder for binary values read from strings.
+*/
+struct Decoder {
+    binary: Binary,
+    acc: u32,
+    acc_len: usize,
+    padding: usize,
+    buf: [u8; 48],
+    len: usize,
+}
+
+impl Decoder {
+    fn new(binary: Binary) -> Self {
+        Decoder {
+            binary,
+            acc: 0,
+            acc_len: 0,
+            padding: 0,
+            buf: [0; 48],
+            len: 0,
+        }
+    }
+
+    fn fragment<'sval>(
+        &mut self,
+        text: &str,
+        stream: &mut (impl sval::Stream<'sval> + ?Sized),
+    ) -> sval::Result {
+        for c in text.bytes() {
+            match self.binary {
+                Binary::Hex => {
+                    let v = match c {
+                        b'0'..=b'9' => c - b'0',
+                        b'a'..=b'f' => c - b'a' + 10,
+                        b'A'..=b'F' => c - b'A' + 10,
+                        _ => return Err(self.invalid()),
+                    };
+
+                    self.acc = (self.acc << 4) | v as u32;
+                    self.acc_len += 1;
+
+                    if self.acc_len == 2 {
+                        self.push(self.acc as u8, stream)?;
+
+                        self.acc = 0;
+                        self.acc_len = 0;
+                    }
+                }
+                Binary::Base64 | Binary::Base64Url => {
+                    if c == b'=' {
+                        if self.acc_len < 2 || self.acc_len + self.padding >= 4 {
+                            return Err(self.invalid());
+                        }
+
+                        self.padding += 1;
+                        continue;
+                    }
+
+                    if self.padding > 0 {
+                        return Err(self.invalid());
+                    }
+
+                    let alphabet = match self.binary {
+                        Binary::Base64Url => BASE64_URL,
+                        _ => BASE64,
+                    };
+
+                    let v = alphabet
+                        .iter()
+                        .position(|a| *a == c)
+                        .ok_or_else(|| self.invalid())?;
+
+                    self.acc = (self.acc << 6) | v as u32;
+                    self.acc_len += 1;
+
+                    if self.acc_len == 4 {
+                        self.push((self.acc >> 16) as u8, stream)?;
+                        self.push((self.acc >> 8) as u8, stream)?;
+                        self.push(self.acc as u8, stream)?;
+
+                        self.acc = 0;
+                        self.acc_len = 0;
+                    }
+                }
+                Binary::Array => unreachable!("binary arrays aren't decoded"),
+            }
+        }
+
+        self.flush(stream)
+    }
+
+    fn end<'sval>(mut self, stream: &mut (impl sval::Stream<'sval> + ?Sized)) -> sval::Result {
+        match (self.binary, self.acc_len) {
+            (_, 0) => (),
+            (Binary::Base64 | Binary::Base64Url, 2) => {
+                self.push((self.acc >> 4) as u8, stream)?;
+            }
+            (Binary::Base64 | Binary::Base64Url, 3) => {
+                self.push((self.acc >> 10) as u8, stream)?;
+                self.push((self.acc >> 2) as u8, stream)?;
+            }
+            _ => return Err(self.invalid()),
+        }
+
+        // Padding is optional, but if it's present it needs to be complete
+        if self.padding > 0 && self.acc_len + self.padding != 4 {
+            return Err(self.invalid());
+        }
+
+        self.flush(stream)
+    }
+
+    fn push<'sval>(
+        &mut self,
+        b: u8,
+        stream: &mut (impl sval::Stream<'sval> + ?Sized),
+    ) -> sval::Result {
+        if self.len == self.buf.len() {
+            self.flush(stream)?;
+        }
+
+        self.buf[self.len] = b;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn flush<'sval>(&mut self, stream: &mut (impl sval::Stream<'sval> + ?Sized)) -> sval::Result {
+        if self.len > 0 {
+            stream.binary_fragment_computed(&self.buf[..self.len])?;
+            self.len = 0;
+        }
+
+        Ok(())
+    }
+
+    fn invalid(&self) -> sval::Error {
+        sval::Error::invalid_value().with_message(match self.binary {
+            Binary::Hex => "invalid hex",
+            _ => "invalid base64",
+        })
+    }
+}
+
+/**
+A stream adapter that reverses the binary encodings written by the JSON formatter.
+
+Text inside a value tagged with [`tags::BASE64`](crate::tags::BASE64),
+[`tags::BASE64_URL`](crate::tags::BASE64_URL), or [`tags::HEX`](crate::tags::HEX)
+is decoded incrementally and forwarded to the inner stream as binary.
+Everything else is forwarded unchanged.
+
+JSON itself can't carry tags, so they need to come from the value being streamed,
+such as a type that wraps a JSON string read by [`from_str`](crate::from_str).
+*/
+pub struct DecodeBinary<S> {
+    binary: Option<Binary>,
+    decoder: Option<Decoder>,
+    stream: S,
+}
+
+impl<S> DecodeBinary<S> {
+    /**
+    Wrap a stream, decoding tagged text into binary.
+    */
+    pub fn new(stream: S) -> Self {
+        DecodeBinary {
+            binary: None,
+            decoder: None,
+            stream,
+        }
+    }
+
+    /**
+    Get the inner stream.
+    */
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<'sval, S: sval::Stream<'sval>> sval::Stream<'sval> for DecodeBinary<S> {
+    fn null(&mut self) -> sval::Result {
+        self.stream.null()
+    }
+
+    fn bool(&mut self, value: bool) -> sval::Result {
+        self.stream.bool(value)
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        if let Some(binary) = self.binary {
+            self.decoder = Some(Decoder::new(binary));
+
+            self.stream.binary_begin(None)
+        } else {
+            self.stream.text_begin(num_bytes_hint)
+        }
+    }
+
+    fn text_fragment(&mut self, fragment: &'sval str) -> sval::Result {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.fragment(fragment, &mut self.stream)
+        } else {
+            self.stream.text_fragment(fragment)
+        }
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.fragment(fragment, &mut self.stream)
+        } else {
+            self.stream.text_fragment_computed(fragment)
+        }
+    }
+
+    fn text_end(&mut self) -> sval::Result {
+        if let Some(decoder) = self.decoder.take() {
+            decoder.end(&mut self.stream)?;
+
+            self.stream.binary_end()
+        } else {
+            self.stream.text_end()
+        }
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        self.stream.binary_begin(num_bytes_hint)
+    }
+
+    fn binary_fragment(&mut self, fragment: &'sval [u8]) -> sval::Result {
+        self.stream.binary_fragment(fragment)
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> sval::Result {
+        self.stream.binary_fragment_computed(fragment)
+    }
+
+    fn binary_end(&mut self) -> sval::Result {
+        self.stream.binary_end()
+    }
+
+    fn u8(&mut self, value: u8) -> sval::Result {
+        self.stream.u8(value)
+    }
+
+    fn u16(&mut self, value: u16) -> sval::Result {
+        self.stream.u16(value)
+    }
+
+    fn u32(&mut self, value: u32) -> sval::Result {
+        self.stream.u32(value)
+    }
+
+    fn u64(&mut self, value: u64) -> sval::Result {
+        self.stream.u64(value)
+    }
+
+    fn u128(&mut self, value: u128) -> sval::Result {
+        self.stream.u128(value)
+    }
+
+    fn i8(&mut self, value: i8) -> sval::Result {
+        self.stream.i8(value)
+    }
+
+    fn i16(&mut self, value: i16) -> sval::Result {
+        self.stream.i16(value)
+    }
+
+    fn i32(&mut self, value: i32) -> sval::Result {
+        self.stream.i32(value)
+    }
+
+    fn i64(&mut self, value: i64) -> sval::Result {
+        self.stream.i64(value)
+    }
+
+    fn i128(&mut self, value: i128) -> sval::Result {
+        self.stream.i128(value)
+    }
+
+    fn f32(&mut self, value: f32) -> sval::Result {
+        self.stream.f32(value)
+    }
+
+    fn f64(&mut self, value: f64) -> sval::Result {
+        self.stream.f64(value)
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        self.stream.map_begin(num_entries_hint)
+    }
+
+    fn map_key_begin(&mut self) -> sval::Result {
+        self.stream.map_key_begin()
+    }
+
+    fn map_key_end(&mut self) -> sval::Result {
+        self.stream.map_key_end()
+    }
+
+    fn map_value_begin(&mut self) -> sval::Result {
+        self.stream.map_value_begin()
+    }
+
+    fn map_value_end(&mut self) -> sval::Result {
+        self.stream.map_value_end()
+    }
+
+    fn map_end(&mut self) -> sval::Result {
+        self.stream.map_end()
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        self.stream.seq_begin(num_entries_hint)
+    }
+
+    fn seq_value_begin(&mut self) -> sval::Result {
+        self.stream.seq_value_begin()
+    }
+
+    fn seq_value_end(&mut self) -> sval::Result {
+        self.stream.seq_value_end()
+    }
+
+    fn seq_end(&mut self) -> sval::Result {
+        self.stream.seq_end()
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.enum_begin(tag, label, index)
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.enum_end(tag, label, index)
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(binary) = from_tag(tag) {
+            self.binary = Some(binary);
+        }
+
+        self.stream.tagged_begin(tag, label, index)
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if from_tag(tag).is_some() {
+            self.binary = None;
+        }
+
+        self.stream.tagged_end(tag, label, index)
+    }
+
+    fn tag(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.tag(tag, label, index)
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        self.stream
+            .record_begin(tag, label, index, num_entries_hint)
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        self.stream.record_value_begin(tag, label)
+    }
+
+    fn record_value_end(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        self.stream.record_value_end(tag, label)
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.record_end(tag, label, index)
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        self.stream.tuple_begin(tag, label, index, num_entries_hint)
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        self.stream.tuple_value_begin(tag, index)
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        self.stream.tuple_value_end(tag, index)
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.tuple_end(tag, label, index)
+    }
+}
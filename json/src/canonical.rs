@@ -0,0 +1,245 @@
+/*!
+Support for canonical JSON, as described in RFC 8785.
+*/
+
+use core::{
+    fmt::{self, Write},
+    str,
+};
+
+/**
+Write a finite floating point number using ECMAScript's `Number.prototype.toString`.
+*/
+pub(crate) fn write_f64(mut out: impl Write, v: f64) -> fmt::Result {
+    debug_assert!(v.is_finite());
+
+    // Both `0` and `-0` are written as `0`
+    if v == 0.0 {
+        return out.write_char('0');
+    }
+
+    // `ryu` produces the shortest digits that round-trip, which is also what ECMAScript uses
+    // We just need to lay them out differently
+    let mut buf = ryu::Buffer::new();
+    let formatted = buf.format_finite(v);
+
+    let (negative, formatted) = match formatted.strip_prefix('-') {
+        Some(formatted) => (true, formatted),
+        None => (false, formatted),
+    };
+
+    let (mantissa, exponent) = match formatted.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().map_err(|_| fmt::Error)?),
+        None => (formatted, 0),
+    };
+
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    let mut digits = [0u8; 32];
+    let mut len = 0;
+    for b in int.bytes().chain(frac.bytes()) {
+        digits[len] = b;
+        len += 1;
+    }
+
+    // `n` is the position of the decimal point relative to the first significant digit
+    let leading_zeroes = digits[..len].iter().take_while(|b| **b == b'0').count();
+    let trailing_zeroes = digits[leading_zeroes..len]
+        .iter()
+        .rev()
+        .take_while(|b| **b == b'0')
+        .count();
+
+    let digits =
+        str::from_utf8(&digits[leading_zeroes..len - trailing_zeroes]).map_err(|_| fmt::Error)?;
+    let k = digits.len() as i32;
+    let n = int.len() as i32 + exponent - leading_zeroes as i32;
+
+    if negative {
+        out.write_char('-')?;
+    }
+
+    if k <= n && n <= 21 {
+        out.write_str(digits)?;
+        write_zeroes(&mut out, n - k)
+    } else if 0 < n && n <= 21 {
+        out.write_str(&digits[..n as usize])?;
+        out.write_char('.')?;
+        out.write_str(&digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        out.write_str("0.")?;
+        write_zeroes(&mut out, -n)?;
+        out.write_str(digits)
+    } else {
+        let exponent = n - 1;
+
+        out.write_str(&digits[..1])?;
+        if k > 1 {
+            out.write_char('.')?;
+            out.write_str(&digits[1..])?;
+        }
+
+        out.write_char('e')?;
+        out.write_char(if exponent < 0 { '-' } else { '+' })?;
+        out.write_str(itoa::Buffer::new().format(exponent.unsigned_abs()))
+    }
+}
+
+fn write_zeroes(mut out: impl Write, n: i32) -> fmt::Result {
+    for _ in 0..n {
+        out.write_char('0')?;
+    }
+
+    Ok(())
+}
+
+/**
+The output of a formatter.
+
+In canonical mode, the entries of objects are written into intermediate
+buffers so they can be sorted before being written to the real output.
+*/
+pub(crate) struct Output<W> {
+    out: W,
+    #[cfg(feature = "alloc")]
+    buffers: alloc::vec::Vec<alloc::string::String>,
+}
+
+impl<W> Output<W> {
+    pub(crate) fn new(out: W) -> Self {
+        Output {
+            out,
+            #[cfg(feature = "alloc")]
+            buffers: alloc::vec::Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(buffer) = self.buffers.last_mut() {
+                buffer.push_str(s);
+                return Ok(());
+            }
+        }
+
+        self.out.write_str(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    use crate::Error;
+
+    use alloc::{string::String, vec::Vec};
+
+    /**
+    The buffered entries of an object in canonical mode.
+    */
+    #[derive(Default)]
+    pub(crate) struct Object {
+        entries: Vec<(String, String)>,
+    }
+
+    impl<W: Write> Output<W> {
+        /**
+        Start buffering an entry of an object.
+        */
+        pub(crate) fn entry_begin(&mut self) {
+            self.buffers.push(String::new());
+        }
+
+        /**
+        Finish buffering an entry of an object.
+
+        The entry is written as `"key":value`.
+        */
+        pub(crate) fn entry_end(&mut self, object: &mut Object) -> sval::Result {
+            let entry = self.buffers.pop().expect("missing entry buffer");
+
+            object.entries.push((unescape_key(&entry)?, entry));
+
+            Ok(())
+        }
+
+        /**
+        Write the entries of an object, sorted by their keys.
+        */
+        pub(crate) fn entries(&mut self, mut object: Object) -> fmt::Result {
+            // Keys are compared by their UTF-16 code units
+            object
+                .entries
+                .sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+            let mut first = true;
+            for (_, entry) in object.entries {
+                if !first {
+                    self.write_char(',')?;
+                }
+                first = false;
+
+                self.write_str(&entry)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /**
+    Get the unescaped key from a buffered `"key":value` entry.
+    */
+    fn unescape_key(entry: &str) -> sval::Result<String> {
+        let mut key = String::new();
+        let mut chars = entry.chars().skip(1);
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('b') => key.push('\x08'),
+                    Some('t') => key.push('\t'),
+                    Some('n') => key.push('\n'),
+                    Some('f') => key.push('\x0c'),
+                    Some('r') => key.push('\r'),
+                    Some('u') => {
+                        let c = match unescape_hex(&mut chars) {
+                            // A high surrogate must be followed by an escaped low surrogate
+                            Some(high @ 0xD800..=0xDBFF) => {
+                                match (chars.next(), chars.next(), unescape_hex(&mut chars)) {
+                                    (Some('\\'), Some('u'), Some(low @ 0xDC00..=0xDFFF)) => {
+                                        char::from_u32(
+                                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                                        )
+                                    }
+                                    _ => None,
+                                }
+                            }
+                            Some(c) => char::from_u32(c),
+                            None => None,
+                        };
+
+                        key.push(c.ok_or_else(Error::invalid_key_escape)?);
+                    }
+                    Some(c) => key.push(c),
+                    None => break,
+                },
+                c => key.push(c),
+            }
+        }
+
+        Ok(key)
+    }
+
+    fn unescape_hex(chars: &mut impl Iterator<Item = char>) -> Option<u32> {
+        let hex = chars.take(4).collect::<String>();
+
+        u32::from_str_radix(&hex, 16).ok()
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub(crate) use self::alloc_support::*;
@@ -0,0 +1,327 @@
+use crate::PrettyConfig;
+
+/**
+Configuration for writing JSON.
+
+The default configuration writes compact JSON in a `serde`-compatible way.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub(crate) pretty: Option<PrettyConfig>,
+    pub(crate) map_keys: MapKeys,
+    pub(crate) binary: Binary,
+    pub(crate) non_finite: NonFinite,
+    pub(crate) large_integers: LargeIntegers,
+    pub(crate) ascii_only: bool,
+    pub(crate) html_safe: bool,
+    pub(crate) canonical: bool,
+    pub(crate) enums: Enums,
+    pub(crate) enum_tag_field: &'static str,
+    pub(crate) enum_content_field: &'static str,
+}
+
+/**
+How to write map keys that aren't text.
+
+JSON objects can only have string keys, so maps with other kinds of keys
+either need their keys converted into strings, or need to be written as
+something other than a JSON object.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapKeys {
+    /**
+    Only allow text keys.
+
+    Any other key fails with an `Unsupported` error.
+    */
+    Text,
+    /**
+    Allow text keys and unit enum variants, which are written as their label.
+    */
+    Labels,
+    /**
+    Allow text keys and unit enum variants, and write numbers and booleans as strings.
+
+    Binary keys are allowed when they're encoded as strings.
+
+    This is the default, and matches the way `serde_json` writes map keys.
+    */
+    #[default]
+    Stringify,
+    /**
+    Write maps as arrays of `[key, value]` pairs.
+
+    Keys can be any value, including maps and sequences.
+    Records are still written as JSON objects.
+    */
+    Pairs,
+}
+
+/**
+How to write binary values.
+
+Values tagged with [`tags::BASE64`](crate::tags::BASE64),
+[`tags::BASE64_URL`](crate::tags::BASE64_URL), or [`tags::HEX`](crate::tags::HEX)
+always use that encoding, regardless of the configured one.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Binary {
+    /**
+    Write binary values as an array of numbers.
+
+    This is the default, and matches the way `serde_json` writes bytes.
+    */
+    #[default]
+    Array,
+    /**
+    Write binary values as a string using the standard base64 alphabet, with padding.
+    */
+    Base64,
+    /**
+    Write binary values as a string using the URL-safe base64 alphabet, without padding.
+    */
+    Base64Url,
+    /**
+    Write binary values as a string of lowercase hex digits.
+    */
+    Hex,
+}
+
+/**
+How to write floating point numbers that aren't finite.
+
+JSON numbers can't represent `NaN` or infinities.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /**
+    Write non-finite numbers as `null`.
+
+    This is the default, and matches the way `serde_json` writes them.
+    */
+    #[default]
+    Null,
+    /**
+    Fail with an `InvalidValue` error.
+    */
+    Error,
+    /**
+    Write non-finite numbers as the strings `"NaN"`, `"Infinity"`, or `"-Infinity"`.
+
+    These strings can be read back as numbers using [`DecodeNumbers`](crate::DecodeNumbers).
+    */
+    String,
+}
+
+/**
+How to write integers that are outside the range JavaScript can represent exactly.
+
+The safe range is `-(2^53 - 1)` to `2^53 - 1`.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LargeIntegers {
+    /**
+    Write large integers as numbers.
+
+    This is the default.
+    */
+    #[default]
+    Number,
+    /**
+    Write large integers as strings, like `"9007199254740993"`.
+
+    These strings can be read back as numbers using [`DecodeNumbers`](crate::DecodeNumbers).
+    */
+    String,
+}
+
+/**
+How to write enum variants.
+
+Enums can also choose their own representation by tagging themselves with
+one of [`tags::EXTERNALLY_TAGGED`](crate::tags::EXTERNALLY_TAGGED),
+[`tags::INTERNALLY_TAGGED`](crate::tags::INTERNALLY_TAGGED),
+[`tags::ADJACENTLY_TAGGED`](crate::tags::ADJACENTLY_TAGGED), or
+[`tags::UNTAGGED`](crate::tags::UNTAGGED), regardless of the configured one.
+
+The examples below use the default field names, which can be changed with
+[`Config::with_enum_tag_field`] and [`Config::with_enum_content_field`].
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Enums {
+    /**
+    Write variants as an object with the variant label as its only key, like `{"Variant":{"a":1}}`.
+
+    Unit variants are written as just their label, like `"Variant"`.
+    This is the default, and matches the way `serde_json` writes enums.
+    */
+    #[default]
+    External,
+    /**
+    Write variants as an object with the variant label in a field, alongside the variant's own fields, like `{"type":"Variant","a":1}`.
+
+    Unit variants are written as `{"type":"Variant"}`. Variants that contain values
+    other than records or maps can't be written internally tagged, and fail with an
+    `Unsupported` error. This matches `#[serde(tag = "type")]`.
+    */
+    Internal,
+    /**
+    Write variants as an object with the variant label in one field and its value in another, like `{"type":"Variant","content":{"a":1}}`.
+
+    Unit variants are written as `{"type":"Variant"}`.
+    This matches `#[serde(tag = "type", content = "content")]`.
+    */
+    Adjacent,
+    /**
+    Write variants as just their value, like `{"a":1}`.
+
+    Unit variants are written as `null`.
+    This matches `#[serde(untagged)]`.
+    */
+    Untagged,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+impl Config {
+    /**
+    Create a default configuration.
+    */
+    pub const fn new() -> Self {
+        Config {
+            pretty: None,
+            map_keys: MapKeys::Stringify,
+            binary: Binary::Array,
+            non_finite: NonFinite::Null,
+            large_integers: LargeIntegers::Number,
+            ascii_only: false,
+            html_safe: false,
+            canonical: false,
+            enums: Enums::External,
+            enum_tag_field: "type",
+            enum_content_field: "content",
+        }
+    }
+
+    /**
+    Write pretty-printed JSON using the given configuration.
+    */
+    pub const fn with_pretty(mut self, pretty: PrettyConfig) -> Self {
+        self.pretty = Some(pretty);
+        self
+    }
+
+    /**
+    Set how to write map keys that aren't text.
+    */
+    pub const fn with_map_keys(mut self, map_keys: MapKeys) -> Self {
+        self.map_keys = map_keys;
+        self
+    }
+
+    /**
+    Set how to write binary values.
+    */
+    pub const fn with_binary(mut self, binary: Binary) -> Self {
+        self.binary = binary;
+        self
+    }
+
+    /**
+    Set how to write floating point numbers that aren't finite.
+
+    This applies to `f32` and `f64` values, and to text tagged with
+    [`sval::tags::NUMBER`] like `NaN` or `-inf`.
+    */
+    pub const fn with_non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+
+    /**
+    Set how to write integers outside of the range JavaScript can represent exactly.
+
+    This applies to integer values, but not to text tagged with [`sval::tags::NUMBER`],
+    which is always written as-is.
+    */
+    pub const fn with_large_integers(mut self, large_integers: LargeIntegers) -> Self {
+        self.large_integers = large_integers;
+        self
+    }
+
+    /**
+    Set whether to escape all non-ASCII characters in strings.
+
+    Non-ASCII characters are written as `\uXXXX` escapes, using a surrogate
+    pair for characters outside the Basic Multilingual Plane. The output is
+    then safe to send over transports that only support ASCII.
+    */
+    pub const fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /**
+    Set whether to escape characters in strings that are significant in HTML.
+
+    The characters `<`, `>`, and `&` are written as `\uXXXX` escapes, so the output
+    can be embedded in an HTML `<script>` block. The line and paragraph separators
+    U+2028 and U+2029 are also escaped, because older JavaScript engines don't
+    allow them in string literals.
+    */
+    pub const fn with_html_safe(mut self, html_safe: bool) -> Self {
+        self.html_safe = html_safe;
+        self
+    }
+
+    /**
+    Set whether to write canonical JSON, as described in RFC 8785.
+
+    Canonical JSON is deterministic, byte-for-byte. The members of records and maps
+    are sorted by the UTF-16 code units of their keys, floating point numbers are
+    formatted the way ECMAScript formats them, strings use minimal escaping, and
+    non-finite numbers always fail with an `InvalidValue` error. Integers are written
+    exactly, so use [`LargeIntegers::String`] to write values outside of the safe
+    range as strings.
+
+    Canonical JSON is always compact and minimally escaped, so any pretty-printing
+    or escaping configuration is ignored.
+    Sorting members requires buffering, so this needs the `alloc` feature.
+    */
+    pub const fn with_canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /**
+    Set how to write enum variants.
+    */
+    pub const fn with_enums(mut self, enums: Enums) -> Self {
+        self.enums = enums;
+        self
+    }
+
+    /**
+    Set the name of the field holding the variant label in internally and adjacently tagged enums.
+
+    The default is `"type"`.
+    */
+    pub const fn with_enum_tag_field(mut self, field: &'static str) -> Self {
+        self.enum_tag_field = field;
+        self
+    }
+
+    /**
+    Set the name of the field holding the variant value in adjacently tagged enums.
+
+    The default is `"content"`.
+    */
+    pub const fn with_enum_content_field(mut self, field: &'static str) -> Self {
+        self.enum_content_field = field;
+        self
+    }
+}
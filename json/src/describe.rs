@@ -0,0 +1,539 @@
+use alloc::{string::String, vec, vec::Vec};
+
+use sval::schema::{Describe, Field, Schema};
+
+use crate::{
+    binary, to_fmt::enums_from_tag, Binary, Config, Enums, Error, LargeIntegers, MapKeys, NonFinite,
+};
+
+/**
+Describe the JSON written for values of type `T` as a JSON Schema document.
+
+See [`describe_to_string_with`] for details.
+*/
+pub fn describe_to_string<T: Describe + ?Sized>() -> Result<String, Error> {
+    describe_to_string_with::<T>(Config::new())
+}
+
+/**
+Describe the JSON written for values of type `T` using the given configuration as a JSON Schema document.
+
+The document uses draft 2020-12, and describes the JSON that [`stream_to_string_with`](crate::stream_to_string_with)
+writes for the same configuration, so any configured enum representation, map key policy, binary
+encoding, or number policy is reflected in the schema. The document itself is also written using
+the given configuration.
+
+Records are described as objects with all of their non-optional fields required, tuples as fixed-length
+arrays, and `Option`s as nullable values. Enums are described with `oneOf`, containing a schema
+for each variant in the configured representation. Untagged variants may overlap, so untagged
+enums are described with `anyOf` instead. Values tagged with [`sval::tags::NUMBER`] are described
+as numbers.
+
+This method fails if values of type `T` could never be written, like maps with keys that
+can't be written as JSON object keys, or internally tagged variants that don't contain a record or map.
+*/
+pub fn describe_to_string_with<T: Describe + ?Sized>(config: Config) -> Result<String, Error> {
+    let describer = Describer { config };
+
+    let mut root = describer.value(T::SCHEMA, None)?;
+
+    if let Json::Object(ref mut members) = root {
+        let mut header = vec![(
+            "$schema",
+            Json::Str("https://json-schema.org/draft/2020-12/schema"),
+        )];
+
+        if let Some(label) = T::SCHEMA.label() {
+            header.push(("title", Json::Str(label_str(label))));
+        }
+
+        members.splice(0..0, header);
+    }
+
+    crate::stream_to_string_with(&root, config)
+}
+
+struct Describer {
+    config: Config,
+}
+
+impl Describer {
+    fn value(&self, schema: &'static Schema, binary_tag: Option<Binary>) -> Result<Json, Error> {
+        Ok(match schema {
+            Schema::Any => Json::Bool(true),
+            Schema::Null => ty("null"),
+            Schema::Bool => ty("boolean"),
+            Schema::Text => ty("string"),
+            Schema::Binary => match binary_tag.unwrap_or(self.config.binary) {
+                Binary::Array => array(vec![("items", integer(0, u8::MAX as i64))]),
+                Binary::Base64 => encoded("base64"),
+                Binary::Base64Url => encoded("base64url"),
+                Binary::Hex => encoded("base16"),
+            },
+            Schema::U8 => integer(0, u8::MAX as i64),
+            Schema::U16 => integer(0, u16::MAX as i64),
+            Schema::U32 => integer(0, u32::MAX as i64),
+            Schema::I8 => integer(i8::MIN as i64, i8::MAX as i64),
+            Schema::I16 => integer(i16::MIN as i64, i16::MAX as i64),
+            Schema::I32 => integer(i32::MIN as i64, i32::MAX as i64),
+            Schema::U64 | Schema::U128 => self.large_integer(true),
+            Schema::I64 | Schema::I128 => self.large_integer(false),
+            Schema::F32 | Schema::F64 => self.number(),
+            Schema::Map { key, value } => self.map(key, value, binary_tag)?,
+            Schema::Seq(value) => array(vec![("items", self.value(value, binary_tag)?)]),
+            Schema::Option(value) => nullable(self.value(value, binary_tag)?),
+            Schema::Record { fields, .. } => self.record(None, fields, binary_tag)?,
+            Schema::Tuple { fields, .. } => self.tuple(fields, binary_tag)?,
+            Schema::Enum { tag, variants, .. } => {
+                self.enumeration(tag.as_ref(), variants, binary_tag)?
+            }
+            Schema::Tagged { tag, value, .. } => match tag {
+                Some(sval::tags::NUMBER) => self.number(),
+                tag => self.value(value, binary::from_tag(tag.as_ref()).or(binary_tag))?,
+            },
+            Schema::Tag { tag, label, .. } => match (tag, label) {
+                (Some(sval::tags::RUST_OPTION_NONE), _) | (_, None) => ty("null"),
+                (_, Some(label)) => constant(label_str(label)),
+            },
+        })
+    }
+
+    fn large_integer(&self, unsigned: bool) -> Json {
+        let mut members = Vec::new();
+
+        if let LargeIntegers::String = self.config.large_integers {
+            members.push((
+                "type",
+                Json::Array(vec![Json::Str("integer"), Json::Str("string")]),
+            ));
+            members.push((
+                "pattern",
+                Json::Str(if unsigned { "^[0-9]+$" } else { "^-?[0-9]+$" }),
+            ));
+        } else {
+            members.push(("type", Json::Str("integer")));
+        }
+
+        if unsigned {
+            members.push(("minimum", Json::Int(0)));
+        }
+
+        Json::Object(members)
+    }
+
+    fn number(&self) -> Json {
+        // Canonical JSON always fails on non-finite numbers
+        let non_finite = if self.config.canonical {
+            NonFinite::Error
+        } else {
+            self.config.non_finite
+        };
+
+        match non_finite {
+            NonFinite::Null => Json::Object(vec![(
+                "type",
+                Json::Array(vec![Json::Str("number"), Json::Str("null")]),
+            )]),
+            NonFinite::Error => ty("number"),
+            NonFinite::String => Json::Object(vec![(
+                "anyOf",
+                Json::Array(vec![
+                    ty("number"),
+                    Json::Object(vec![(
+                        "enum",
+                        Json::Array(vec![
+                            Json::Str("NaN"),
+                            Json::Str("Infinity"),
+                            Json::Str("-Infinity"),
+                        ]),
+                    )]),
+                ]),
+            )]),
+        }
+    }
+
+    fn map(
+        &self,
+        key: &'static Schema,
+        value: &'static Schema,
+        binary_tag: Option<Binary>,
+    ) -> Result<Json, Error> {
+        if let MapKeys::Pairs = self.config.map_keys {
+            let entry = tuple(vec![
+                self.value(key, binary_tag)?,
+                self.value(value, binary_tag)?,
+            ]);
+
+            return Ok(array(vec![("items", entry)]));
+        }
+
+        if !self.is_key(key) {
+            return Err(Error::new(Error::invalid_key()));
+        }
+
+        Ok(Json::Object(vec![
+            ("type", Json::Str("object")),
+            ("additionalProperties", self.value(value, binary_tag)?),
+        ]))
+    }
+
+    /**
+    Whether a value with the given schema can be written as a JSON object key.
+    */
+    fn is_key(&self, schema: &'static Schema) -> bool {
+        let map_keys = self.config.map_keys;
+
+        let is_label = |schema: &Schema| match schema {
+            Schema::Tag { tag, label, .. } => {
+                label.is_some() && tag.as_ref() != Some(&sval::tags::RUST_OPTION_NONE)
+            }
+            _ => false,
+        };
+
+        match schema {
+            Schema::Any | Schema::Text => true,
+            Schema::Tagged {
+                tag: Some(sval::tags::NUMBER),
+                ..
+            } => map_keys == MapKeys::Stringify,
+            Schema::Tagged { value, .. } => self.is_key(value),
+            Schema::Tag { .. } => {
+                matches!(map_keys, MapKeys::Labels | MapKeys::Stringify) && is_label(schema)
+            }
+            Schema::Enum { variants, .. } => {
+                matches!(map_keys, MapKeys::Labels | MapKeys::Stringify)
+                    && variants.iter().all(is_label)
+            }
+            Schema::Bool
+            | Schema::U8
+            | Schema::U16
+            | Schema::U32
+            | Schema::U64
+            | Schema::U128
+            | Schema::I8
+            | Schema::I16
+            | Schema::I32
+            | Schema::I64
+            | Schema::I128
+            | Schema::F32
+            | Schema::F64 => map_keys == MapKeys::Stringify,
+            _ => false,
+        }
+    }
+
+    /**
+    Describe a record as an object, optionally with the label field of an internally tagged variant.
+    */
+    fn record(
+        &self,
+        variant_tag: Option<(&'static str, &'static str)>,
+        fields: &'static [Field],
+        binary_tag: Option<Binary>,
+    ) -> Result<Json, Error> {
+        let mut properties = Vec::new();
+        let mut required = Vec::new();
+
+        if let Some((tag_field, label)) = variant_tag {
+            properties.push((tag_field, constant(label)));
+            required.push(Json::Str(tag_field));
+        }
+
+        for field in fields {
+            let label = field.label.as_ref().map(label_str).ok_or_else(|| {
+                Error::new(
+                    sval::Error::invalid_value().with_message("record fields must have a label"),
+                )
+            })?;
+
+            properties.push((label, self.value(field.value, binary_tag)?));
+
+            if !field.optional {
+                required.push(Json::Str(label));
+            }
+        }
+
+        Ok(object(properties, required))
+    }
+
+    fn tuple(&self, fields: &'static [Field], binary_tag: Option<Binary>) -> Result<Json, Error> {
+        let mut items = Vec::new();
+
+        for field in fields {
+            items.push(self.value(field.value, binary_tag)?);
+        }
+
+        Ok(tuple(items))
+    }
+
+    fn enumeration(
+        &self,
+        tag: Option<&sval::Tag>,
+        variants: &'static [Schema],
+        binary_tag: Option<Binary>,
+    ) -> Result<Json, Error> {
+        let enums = enums_from_tag(tag).unwrap_or(self.config.enums);
+
+        let mut schemas = Vec::new();
+
+        for variant in variants {
+            schemas.push(self.variant(enums, variant, binary_tag)?);
+        }
+
+        Ok(Json::Object(vec![(
+            if let Enums::Untagged = enums {
+                "anyOf"
+            } else {
+                "oneOf"
+            },
+            Json::Array(schemas),
+        )]))
+    }
+
+    fn variant(
+        &self,
+        enums: Enums,
+        variant: &'static Schema,
+        binary_tag: Option<Binary>,
+    ) -> Result<Json, Error> {
+        let label = match variant {
+            Schema::Record { label, .. }
+            | Schema::Tuple { label, .. }
+            | Schema::Tagged { label, .. }
+            | Schema::Tag { label, .. } => label.as_ref().map(label_str),
+            _ => None,
+        };
+
+        // Variants without labels are written as their value
+        let label = match label {
+            Some(label) => label,
+            None => return self.value(variant, binary_tag),
+        };
+
+        let tag_field = self.config.enum_tag_field;
+
+        if let Schema::Tag { tag, .. } = variant {
+            if tag.as_ref() == Some(&sval::tags::RUST_OPTION_NONE) {
+                return Ok(ty("null"));
+            }
+
+            return Ok(match enums {
+                Enums::External => constant(label),
+                Enums::Internal | Enums::Adjacent => object(
+                    vec![(tag_field, constant(label))],
+                    vec![Json::Str(tag_field)],
+                ),
+                Enums::Untagged => ty("null"),
+            });
+        }
+
+        Ok(match enums {
+            Enums::External => object(
+                vec![(label, self.value(variant, binary_tag)?)],
+                vec![Json::Str(label)],
+            ),
+            Enums::Internal => self.internally_tagged(label, variant, binary_tag)?,
+            Enums::Adjacent => {
+                let content_field = self.config.enum_content_field;
+
+                object(
+                    vec![
+                        (tag_field, constant(label)),
+                        (content_field, self.value(variant, binary_tag)?),
+                    ],
+                    vec![Json::Str(tag_field), Json::Str(content_field)],
+                )
+            }
+            Enums::Untagged => self.value(variant, binary_tag)?,
+        })
+    }
+
+    fn internally_tagged(
+        &self,
+        label: &'static str,
+        mut content: &'static Schema,
+        binary_tag: Option<Binary>,
+    ) -> Result<Json, Error> {
+        let tag_field = self.config.enum_tag_field;
+
+        // Find the record or map the variant's label is written into
+        while let Schema::Tagged { tag, value, .. } = content {
+            if tag.as_ref() == Some(&sval::tags::NUMBER) {
+                break;
+            }
+
+            content = value;
+        }
+
+        match content {
+            Schema::Record { fields, .. } => {
+                self.record(Some((tag_field, label)), fields, binary_tag)
+            }
+            // The map's entries are written alongside the label
+            Schema::Map { key, value } if self.config.map_keys != MapKeys::Pairs => {
+                if !self.is_key(key) {
+                    return Err(Error::new(Error::invalid_key()));
+                }
+
+                Ok(Json::Object(vec![
+                    ("type", Json::Str("object")),
+                    (
+                        "properties",
+                        Json::Object(vec![(tag_field, constant(label))]),
+                    ),
+                    ("required", Json::Array(vec![Json::Str(tag_field)])),
+                    ("additionalProperties", self.value(value, binary_tag)?),
+                ]))
+            }
+            Schema::Any => Ok(Json::Object(vec![
+                ("type", Json::Str("object")),
+                (
+                    "properties",
+                    Json::Object(vec![(tag_field, constant(label))]),
+                ),
+                ("required", Json::Array(vec![Json::Str(tag_field)])),
+            ])),
+            _ => Err(Error::new(sval::Error::unsupported().with_message(
+                "internally tagged variants must contain a record or map",
+            ))),
+        }
+    }
+}
+
+/**
+A JSON document.
+
+Keys and strings in a schema all come from static data, so they're borrowed.
+*/
+enum Json {
+    Bool(bool),
+    Int(i64),
+    Str(&'static str),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl sval::Value for Json {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        match self {
+            Json::Bool(v) => stream.bool(*v),
+            Json::Int(v) => stream.i64(*v),
+            Json::Str(v) => stream.value(*v),
+            Json::Array(values) => {
+                stream.seq_begin(Some(values.len()))?;
+
+                for value in values {
+                    stream.seq_value_begin()?;
+                    stream.value(value)?;
+                    stream.seq_value_end()?;
+                }
+
+                stream.seq_end()
+            }
+            // Members are written as a record so their keys are always text,
+            // regardless of the configured map key policy
+            Json::Object(members) => {
+                stream.record_begin(None, None, None, Some(members.len()))?;
+
+                for (key, value) in members {
+                    let label = sval::Label::new(key);
+
+                    stream.record_value_begin(None, &label)?;
+                    stream.value(value)?;
+                    stream.record_value_end(None, &label)?;
+                }
+
+                stream.record_end(None, None, None)
+            }
+        }
+    }
+}
+
+fn label_str(label: &'static sval::Label<'static>) -> &'static str {
+    label.as_str()
+}
+
+fn ty(name: &'static str) -> Json {
+    Json::Object(vec![("type", Json::Str(name))])
+}
+
+fn constant(value: &'static str) -> Json {
+    Json::Object(vec![("const", Json::Str(value))])
+}
+
+fn integer(minimum: i64, maximum: i64) -> Json {
+    Json::Object(vec![
+        ("type", Json::Str("integer")),
+        ("minimum", Json::Int(minimum)),
+        ("maximum", Json::Int(maximum)),
+    ])
+}
+
+fn encoded(encoding: &'static str) -> Json {
+    Json::Object(vec![
+        ("type", Json::Str("string")),
+        ("contentEncoding", Json::Str(encoding)),
+    ])
+}
+
+fn array(mut members: Vec<(&'static str, Json)>) -> Json {
+    members.insert(0, ("type", Json::Str("array")));
+
+    Json::Object(members)
+}
+
+fn tuple(items: Vec<Json>) -> Json {
+    let len = items.len() as i64;
+
+    array(vec![
+        ("prefixItems", Json::Array(items)),
+        ("items", Json::Bool(false)),
+        ("minItems", Json::Int(len)),
+        ("maxItems", Json::Int(len)),
+    ])
+}
+
+fn object(properties: Vec<(&'static str, Json)>, required: Vec<Json>) -> Json {
+    let mut members = vec![
+        ("type", Json::Str("object")),
+        ("properties", Json::Object(properties)),
+    ];
+
+    if !required.is_empty() {
+        members.push(("required", Json::Array(required)));
+    }
+
+    members.push(("additionalProperties", Json::Bool(false)));
+
+    Json::Object(members)
+}
+
+/**
+Allow `null` in addition to the values a schema accepts.
+*/
+fn nullable(schema: Json) -> Json {
+    let mut members = match schema {
+        Json::Object(members) => members,
+        schema => return schema,
+    };
+
+    if let Some((_, ty)) = members.iter_mut().find(|(key, _)| *key == "type") {
+        match ty {
+            Json::Str("null") => (),
+            Json::Str(name) => {
+                let name = *name;
+                *ty = Json::Array(vec![Json::Str(name), Json::Str("null")]);
+            }
+            Json::Array(names) if !names.iter().any(|name| matches!(name, Json::Str("null"))) => {
+                names.push(Json::Str("null"));
+            }
+            _ => (),
+        }
+
+        return Json::Object(members);
+    }
+
+    Json::Object(vec![(
+        "anyOf",
+        Json::Array(vec![Json::Object(members), ty("null")]),
+    )])
+}
@@ -1,64 +1,296 @@
 use core::fmt;
 
+/**
+An error encountered while streaming a value as JSON, or reading JSON as a value.
+*/
 #[derive(Debug)]
 pub struct Error {
-    pub(crate) kind: ErrorKind,
+    inner: sval::Error,
+    position: Option<Position>,
+    expected: Option<&'static str>,
+    unresolved_token: Option<usize>,
 }
 
-#[derive(Debug)]
-pub(crate) enum ErrorKind {
-    Generic,
-    Fmt(fmt::Error),
-    #[cfg(feature = "std")]
-    IO(std::io::Error),
-    InvalidKey,
+/**
+A position in a JSON document.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    line: usize,
+    column: usize,
+    offset: usize,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.kind {
-            ErrorKind::Generic => write!(f, "an error occurred serializing a value to JSON"),
-            ErrorKind::Fmt(_) => write!(f, "failed to write JSON into a formatter"),
-            #[cfg(feature = "std")]
-            ErrorKind::IO(_) => write!(f, "failed to write JSON"),
-            ErrorKind::InvalidKey => write!(f, "attempt to serialize a non-string key"),
-        }
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
     }
 }
 
 impl Error {
-    pub(crate) fn generic() -> Self {
+    pub(crate) fn new(err: sval::Error) -> Self {
+        Error {
+            unresolved_token: unresolved_token(&err),
+            inner: err,
+            position: None,
+            expected: None,
+        }
+    }
+
+    pub(crate) fn invalid_key() -> sval::Error {
+        sval::Error::unsupported().with_message("attempt to serialize a non-string key")
+    }
+
+    pub(crate) fn non_finite() -> sval::Error {
+        sval::Error::invalid_value().with_message("attempt to serialize a non-finite number")
+    }
+
+    #[cfg(feature = "alloc")]
+    pub(crate) fn invalid_key_escape() -> sval::Error {
+        sval::Error::invalid_value().with_message(
+            "attempt to serialize a key with an invalid escape, like an unpaired surrogate",
+        )
+    }
+
+    /**
+    An error for malformed JSON, where the input didn't contain what was expected.
+    */
+    pub(crate) fn unexpected(position: Position, expected: &'static str) -> Self {
+        Error {
+            inner: sval::Error::invalid_value()
+                .with_message(format_args!("expected {} at {}", expected, position)),
+            position: Some(position),
+            expected: Some(expected),
+            unresolved_token: None,
+        }
+    }
+
+    /**
+    An error produced by a stream while reading JSON.
+    */
+    pub(crate) fn at(err: sval::Error, position: Position) -> Self {
         Error {
-            kind: ErrorKind::Generic,
+            unresolved_token: unresolved_token(&err),
+            inner: err,
+            position: Some(position),
+            expected: None,
         }
     }
 
-    pub(crate) fn from_fmt(e: fmt::Error) -> Self {
+    /**
+    An error for a JSON pointer that didn't resolve, because its reference token at `index` didn't match.
+    */
+    pub(crate) fn unresolved(pointer: crate::Pointer, index: usize, token: &str) -> Self {
         Error {
-            kind: ErrorKind::Fmt(e),
+            inner: sval::Error::invalid_value().with_message(format_args!(
+                "the JSON pointer `{}` doesn't resolve at `{}`",
+                pointer, token
+            )),
+            position: None,
+            expected: None,
+            unresolved_token: Some(index),
         }
     }
 
-    pub(crate) fn invalid_key() -> Self {
+    /**
+    Attach the line of an NDJSON document that failed to an error.
+    */
+    pub(crate) fn at_line(self, line: usize, offset: usize) -> Self {
         Error {
-            kind: ErrorKind::InvalidKey,
+            position: Some(Position {
+                line,
+                column: 1,
+                offset,
+            }),
+            ..self
         }
     }
+
+    /**
+    Get the kind of failure this error represents.
+    */
+    pub fn kind(&self) -> sval::ErrorKind {
+        self.inner.kind()
+    }
+
+    /**
+    Get the path from the root of the value down to the value that failed.
+
+    The path is only available when the `alloc` feature is enabled.
+    */
+    pub fn path(&self) -> sval::ErrorPath<'_> {
+        self.inner.path()
+    }
+
+    /**
+    Get the position in the input where reading JSON failed.
+
+    The position is available for errors produced while reading JSON, and
+    for errors writing NDJSON, where it points to the start of the line
+    that failed.
+    */
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    /**
+    Get a description of what was expected when the input was malformed.
+
+    This will be something like `` `:` `` or `a value`.
+    */
+    pub fn expected(&self) -> Option<&'static str> {
+        self.expected
+    }
+
+    /**
+    Get the index of the first reference token in a JSON pointer that couldn't be resolved.
+
+    This is only set for errors produced by [`Select::finish`](crate::Select::finish)
+    when the pointer didn't point to a value. When a [`Selected`](crate::Selected) value
+    fails to resolve, the index is carried through the `sval::Error` it returns
+    as its source, so it's also set for errors from functions like
+    [`stream_to_string`](crate::stream_to_string). That requires the `std` feature,
+    and is skipped if the error already has a source; otherwise, only the error's
+    message describes the token.
+    */
+    pub fn unresolved_token(&self) -> Option<usize> {
+        self.unresolved_token
+    }
+}
+
+impl Position {
+    /**
+    The position at the start of a JSON document.
+    */
+    pub(crate) fn start() -> Self {
+        Position {
+            line: 1,
+            column: 1,
+            offset: 0,
+        }
+    }
+
+    /**
+    Compute the position of a byte offset into a JSON document.
+    */
+    pub(crate) fn from_offset(src: &[u8], offset: usize) -> Self {
+        let mut position = Position::start();
+        position.advance(&src[..offset.min(src.len())]);
+
+        position
+    }
+
+    /**
+    Move the position past the given bytes.
+    */
+    pub(crate) fn advance(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.offset += 1;
+
+            if *b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            }
+            // Columns are counted in characters rather than bytes
+            else if (*b & 0b1100_0000) != 0b1000_0000 {
+                self.column += 1;
+            }
+        }
+    }
+
+    /**
+    The line, starting from `1`.
+    */
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /**
+    The column within the line, starting from `1`.
+
+    Columns are counted in characters.
+    */
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /**
+    The byte offset from the start of the input, starting from `0`.
+    */
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl From<Error> for sval::Error {
+    fn from(err: Error) -> sval::Error {
+        #[cfg(feature = "std")]
+        {
+            // Don't replace a source that's already attached to the error
+            if let (Some(index), None) =
+                (err.unresolved_token, std::error::Error::source(&err.inner))
+            {
+                return err.inner.with_source(std_support::UnresolvedToken(index));
+            }
+        }
+
+        err.inner
+    }
+}
+
+#[cfg(not(feature = "std"))]
+fn unresolved_token(_: &sval::Error) -> Option<usize> {
+    None
 }
 
+#[cfg(feature = "std")]
+use self::std_support::unresolved_token;
+
 #[cfg(feature = "std")]
 mod std_support {
     use super::*;
 
-    use std::error;
+    use std::{error, io};
+
+    /**
+    The index of an unresolved JSON pointer reference token, carried as the source of an `sval::Error`.
+    */
+    #[derive(Debug)]
+    pub(super) struct UnresolvedToken(pub(super) usize);
+
+    impl fmt::Display for UnresolvedToken {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(
+                f,
+                "reference token {} of the JSON pointer didn't resolve",
+                self.0
+            )
+        }
+    }
+
+    impl error::Error for UnresolvedToken {}
+
+    pub(super) fn unresolved_token(err: &sval::Error) -> Option<usize> {
+        error::Error::source(err)?
+            .downcast_ref::<UnresolvedToken>()
+            .map(|token| token.0)
+    }
+
+    impl From<io::Error> for Error {
+        fn from(err: io::Error) -> Error {
+            Error::new(sval::Error::from(err))
+        }
+    }
 
     impl error::Error for Error {
         fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-            match self.kind {
-                ErrorKind::Fmt(ref err) => Some(err),
-                ErrorKind::IO(ref err) => Some(err),
-                _ => None,
-            }
+            error::Error::source(&self.inner)
         }
     }
 }
@@ -0,0 +1,640 @@
+use crate::{
+    read::{Container, ReadError, Stack, State, TOO_DEEP},
+    tags, Error, Position,
+};
+
+use std::{boxed::Box, io, str, vec};
+
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+// The longest sequence that needs to be buffered at once is an escaped surrogate pair `\uXXXX\uXXXX`
+const MIN_CAPACITY: usize = 12;
+
+/**
+Read JSON from an `io::Read` incrementally.
+
+See [`JsonReader`] for details.
+*/
+pub fn from_reader<R: io::Read>(reader: R) -> JsonReader<R> {
+    JsonReader::new(reader)
+}
+
+/**
+A reader that streams a JSON document from an `io::Read` as `sval` tokens.
+
+The input is pulled through a fixed-size buffer as it's needed, so memory
+use doesn't depend on the size of the document. Text and numbers are streamed
+as computed fragments as the bytes for them arrive, so large strings don't
+need to be buffered either.
+
+The reader can be resumed, streaming a single token at a time.
+*/
+pub struct JsonReader<R> {
+    reader: R,
+    buf: Box<[u8]>,
+    start: usize,
+    end: usize,
+    eof: bool,
+    position: Position,
+    in_str: bool,
+    stack: Stack,
+    state: State,
+}
+
+impl<R: io::Read> JsonReader<R> {
+    /**
+    Create a reader with a default buffer size.
+    */
+    pub fn new(reader: R) -> Self {
+        JsonReader::with_capacity(DEFAULT_CAPACITY, reader)
+    }
+
+    /**
+    Create a reader with the given buffer size.
+
+    Very small capacities are rounded up to the minimum needed to read
+    escape sequences.
+    */
+    pub fn with_capacity(capacity: usize, reader: R) -> Self {
+        JsonReader {
+            reader,
+            buf: vec![0; capacity.max(MIN_CAPACITY)].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            eof: false,
+            position: Position::start(),
+            in_str: false,
+            stack: Stack::new(),
+            state: State::Root,
+        }
+    }
+
+    /**
+    Get the current position of the reader in the input.
+    */
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /**
+    Stream the rest of the document.
+    */
+    pub fn stream_to_end<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<(), Error> {
+        while self.stream_resume(stream)? {}
+
+        Ok(())
+    }
+
+    /**
+    Stream the next token in the document.
+
+    This method returns `false` when the document has been fully streamed.
+    */
+    pub fn stream_resume<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<bool, Error> {
+        self.stream_token(stream).map_err(|err| match err {
+            ReadError::Unexpected(index, expected) => {
+                Error::unexpected(self.position_at(index), expected)
+            }
+            ReadError::Stream(err) => Error::at(err, self.position),
+        })
+    }
+
+    fn stream_token<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<bool, ReadError> {
+        if self.in_str {
+            self.str_fragment(stream)?;
+
+            return Ok(true);
+        }
+
+        self.skip_whitespace()?;
+
+        let b = match self.peek()? {
+            Some(b) => b,
+            None if self.state == State::Done => return Ok(false),
+            None => return Err(self.unexpected()),
+        };
+
+        match (self.state, b) {
+            // Begin a map key
+            (State::MapEmpty | State::MapKey, b'"') => {
+                self.advance(1);
+                self.state = State::MapKeyEnd;
+                self.in_str = true;
+
+                stream.map_key_begin()?;
+                stream.text_begin(None)?;
+            }
+            // End a map key
+            (State::MapKeyEnd, b':') => {
+                self.advance(1);
+                self.state = State::MapValue;
+            }
+            // Separate map entries or seq elems
+            (State::MapValueEnd, b',') => {
+                self.advance(1);
+                self.state = State::MapKey;
+            }
+            (State::SeqElemEnd, b',') => {
+                self.advance(1);
+                self.state = State::SeqElem;
+            }
+            // End a map
+            (State::MapEmpty | State::MapValueEnd, b'}') => {
+                self.advance(1);
+                self.stack.pop();
+
+                stream.map_end()?;
+                self.value_end(stream)?;
+            }
+            // End a seq
+            (State::SeqEmpty | State::SeqElemEnd, b']') => {
+                self.advance(1);
+                self.stack.pop();
+
+                stream.seq_end()?;
+                self.value_end(stream)?;
+            }
+            // Begin a value
+            (State::Root | State::MapValue | State::SeqEmpty | State::SeqElem, b) => {
+                self.value(b, stream)?;
+            }
+            _ => return Err(self.unexpected()),
+        }
+
+        Ok(true)
+    }
+
+    fn value<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        b: u8,
+        stream: &mut S,
+    ) -> Result<(), ReadError> {
+        match b {
+            // Begin a string
+            b'"' => {
+                self.advance(1);
+                self.in_str = true;
+
+                self.value_begin(stream)?;
+                stream.text_begin(None)?;
+            }
+            // Begin a map
+            b'{' => {
+                self.value_begin(stream)?;
+
+                self.stack
+                    .push(Container::Map)
+                    .ok_or(ReadError::Unexpected(self.start, TOO_DEEP))?;
+                self.advance(1);
+                self.state = State::MapEmpty;
+
+                stream.map_begin(None)?;
+            }
+            // Begin a seq
+            b'[' => {
+                self.value_begin(stream)?;
+
+                self.stack
+                    .push(Container::Seq)
+                    .ok_or(ReadError::Unexpected(self.start, TOO_DEEP))?;
+                self.advance(1);
+                self.state = State::SeqEmpty;
+
+                stream.seq_begin(None)?;
+            }
+            // The boolean value `true`
+            b't' => {
+                self.literal(b"true", "`true`")?;
+
+                self.value_begin(stream)?;
+                stream.bool(true)?;
+                self.value_end(stream)?;
+            }
+            // The boolean value `false`
+            b'f' => {
+                self.literal(b"false", "`false`")?;
+
+                self.value_begin(stream)?;
+                stream.bool(false)?;
+                self.value_end(stream)?;
+            }
+            // The value `null`
+            b'n' => {
+                self.literal(b"null", "`null`")?;
+
+                self.value_begin(stream)?;
+                stream.null()?;
+                self.value_end(stream)?;
+            }
+            // Numbers
+            b'-' | b'0'..=b'9' => {
+                self.value_begin(stream)?;
+
+                // The number is validated as it's read, so it's already valid JSON
+                stream.tagged_begin(Some(&tags::JSON_NATIVE), None, None)?;
+                stream.tagged_begin(Some(&sval::tags::NUMBER), None, None)?;
+                stream.text_begin(None)?;
+                self.number(stream)?;
+                stream.text_end()?;
+                stream.tagged_end(Some(&sval::tags::NUMBER), None, None)?;
+                stream.tagged_end(Some(&tags::JSON_NATIVE), None, None)?;
+
+                self.value_end(stream)?;
+            }
+            _ => return Err(self.unexpected()),
+        }
+
+        Ok(())
+    }
+
+    fn value_begin<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> sval::Result {
+        match self.state {
+            State::SeqEmpty | State::SeqElem => stream.seq_value_begin(),
+            State::MapValue => stream.map_value_begin(),
+            _ => Ok(()),
+        }
+    }
+
+    fn value_end<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> sval::Result {
+        match self.stack.top() {
+            Some(Container::Map) => {
+                self.state = State::MapValueEnd;
+
+                stream.map_value_end()
+            }
+            Some(Container::Seq) => {
+                self.state = State::SeqElemEnd;
+
+                stream.seq_value_end()
+            }
+            None => {
+                self.state = State::Done;
+
+                Ok(())
+            }
+        }
+    }
+
+    fn str_fragment<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<(), ReadError> {
+        loop {
+            if self.ensure(1)? == 0 {
+                return Err(ReadError::Unexpected(self.start, "`\"`"));
+            }
+
+            // Scan through the buffer until we reach the end or an escaped character
+            let mut head = self.start;
+            while head < self.end {
+                match self.buf[head] {
+                    b'"' | b'\\' => break,
+                    0x00..=0x1f => {
+                        return Err(ReadError::Unexpected(head, "an escaped control character"))
+                    }
+                    _ => head += 1,
+                }
+            }
+
+            if head > self.start {
+                let run = &self.buf[self.start..head];
+
+                let fragment = match str::from_utf8(run) {
+                    Ok(fragment) => fragment,
+                    // A character may be split across reads
+                    Err(err) if err.error_len().is_none() && head == self.end => {
+                        // SAFETY: The input up to this point is known to be valid UTF-8
+                        unsafe { str::from_utf8_unchecked(&run[..err.valid_up_to()]) }
+                    }
+                    Err(err) => {
+                        return Err(ReadError::Unexpected(
+                            self.start + err.valid_up_to(),
+                            "valid UTF-8",
+                        ))
+                    }
+                };
+
+                if !fragment.is_empty() {
+                    stream.text_fragment_computed(fragment)?;
+
+                    let len = fragment.len();
+                    self.advance(len);
+
+                    return Ok(());
+                }
+
+                // Only part of a character is buffered, so read more
+                if !self.fill()? {
+                    return Err(ReadError::Unexpected(self.start, "valid UTF-8"));
+                }
+
+                continue;
+            }
+
+            if self.buf[self.start] == b'"' {
+                self.advance(1);
+                self.in_str = false;
+
+                stream.text_end()?;
+
+                if self.state == State::MapKeyEnd {
+                    stream.map_key_end()?;
+                } else {
+                    self.value_end(stream)?;
+                }
+            } else {
+                let escaped = self.escape()?;
+
+                stream.text_fragment_computed(escaped.encode_utf8(&mut [0; 4]))?;
+            }
+
+            return Ok(());
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ReadError> {
+        // Filling the buffer may move its contents, so `self.start` is
+        // re-read after any call to `ensure`
+        if self.ensure(2)? < 2 {
+            return Err(ReadError::Unexpected(
+                self.start + 1,
+                "a valid escape sequence",
+            ));
+        }
+
+        let escaped = match self.buf[self.start + 1] {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                self.ensure(6)?;
+                let high = self.hex(self.start + 2)?;
+
+                let c = match high {
+                    // A high surrogate must be followed by an escaped low surrogate
+                    0xd800..=0xdbff => {
+                        self.ensure(12)?;
+
+                        let low_start = self.start + 6;
+
+                        if self.buf[..self.end].get(low_start..low_start + 2) != Some(b"\\u") {
+                            return Err(ReadError::Unexpected(low_start, "a low surrogate"));
+                        }
+
+                        let low = self.hex(low_start + 2)?;
+
+                        if !(0xdc00..=0xdfff).contains(&low) {
+                            return Err(ReadError::Unexpected(low_start, "a low surrogate"));
+                        }
+
+                        self.advance(12);
+
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    }
+                    0xdc00..=0xdfff => {
+                        return Err(ReadError::Unexpected(self.start, "a high surrogate"));
+                    }
+                    c => {
+                        self.advance(6);
+
+                        c
+                    }
+                };
+
+                return char::from_u32(c)
+                    .ok_or(ReadError::Unexpected(self.start, "a valid character"));
+            }
+            _ => {
+                return Err(ReadError::Unexpected(
+                    self.start + 1,
+                    "a valid escape sequence",
+                ))
+            }
+        };
+
+        self.advance(2);
+
+        Ok(escaped)
+    }
+
+    fn hex(&self, start: usize) -> Result<u32, ReadError> {
+        let mut value = 0;
+
+        for i in start..start + 4 {
+            let digit = self.buf[..self.end]
+                .get(i)
+                .and_then(|b| (*b as char).to_digit(16))
+                .ok_or(ReadError::Unexpected(i, "a hex digit"))?;
+
+            value = (value << 4) | digit;
+        }
+
+        Ok(value)
+    }
+
+    fn number<'sval, S: sval::Stream<'sval> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<(), ReadError> {
+        let mut number = Number::Start;
+
+        while self.ensure(1)? > 0 {
+            let mut head = self.start;
+            while head < self.end {
+                match number.next(self.buf[head]) {
+                    Some(next) => {
+                        number = next;
+                        head += 1;
+                    }
+                    None => break,
+                }
+            }
+
+            if head > self.start {
+                // SAFETY: Numbers are only made up of ASCII characters
+                let fragment = unsafe { str::from_utf8_unchecked(&self.buf[self.start..head]) };
+
+                stream.text_fragment_computed(fragment)?;
+
+                self.advance(head - self.start);
+            }
+
+            // If we stopped before the end of the buffer then the number is finished
+            if head < self.end {
+                break;
+            }
+        }
+
+        if number.is_complete() {
+            Ok(())
+        } else {
+            Err(ReadError::Unexpected(self.start, "a digit"))
+        }
+    }
+
+    fn literal(&mut self, literal: &'static [u8], expected: &'static str) -> Result<(), ReadError> {
+        self.ensure(literal.len())?;
+
+        let matched = literal
+            .iter()
+            .zip(&self.buf[self.start..self.end])
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if matched != literal.len() {
+            return Err(ReadError::Unexpected(self.start + matched, expected));
+        }
+
+        self.advance(literal.len());
+
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), ReadError> {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.peek()? {
+            self.advance(1);
+        }
+
+        Ok(())
+    }
+
+    fn unexpected(&self) -> ReadError {
+        ReadError::Unexpected(self.start, self.state.expected())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, ReadError> {
+        if self.ensure(1)? == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(self.buf[self.start]))
+        }
+    }
+
+    /**
+    Consume bytes from the buffer.
+    */
+    fn advance(&mut self, len: usize) {
+        self.position
+            .advance(&self.buf[self.start..self.start + len]);
+        self.start += len;
+    }
+
+    /**
+    Try make at least `len` bytes available in the buffer.
+
+    This method returns the number of bytes available, which may be less than
+    `len` if the end of the input was reached.
+    */
+    fn ensure(&mut self, len: usize) -> Result<usize, ReadError> {
+        while self.end - self.start < len && self.fill()? {}
+
+        Ok(self.end - self.start)
+    }
+
+    /**
+    Read more bytes into the buffer.
+
+    Any unconsumed bytes are moved to the front of the buffer first.
+    This method returns `false` if the end of the input was reached.
+    */
+    fn fill(&mut self) -> Result<bool, ReadError> {
+        if self.eof {
+            return Ok(false);
+        }
+
+        if self.start > 0 {
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+
+        loop {
+            match self.reader.read(&mut self.buf[self.end..]) {
+                Ok(0) => {
+                    self.eof = true;
+
+                    return Ok(false);
+                }
+                Ok(read) => {
+                    self.end += read;
+
+                    return Ok(true);
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(ReadError::Stream(sval::Error::from(err))),
+            }
+        }
+    }
+
+    /**
+    Get the position of a byte in the buffer.
+    */
+    fn position_at(&self, index: usize) -> Position {
+        let mut position = self.position;
+        position.advance(&self.buf[self.start..index.min(self.end)]);
+
+        position
+    }
+}
+
+/**
+The part of a number that's been read.
+*/
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Start,
+    Sign,
+    Zero,
+    Int,
+    Point,
+    Frac,
+    Exp,
+    ExpSign,
+    ExpInt,
+}
+
+impl Number {
+    fn next(self, b: u8) -> Option<Number> {
+        match (self, b) {
+            (Number::Start, b'-') => Some(Number::Sign),
+            // The integer part can't have leading zeroes
+            (Number::Start | Number::Sign, b'0') => Some(Number::Zero),
+            (Number::Start | Number::Sign, b'1'..=b'9') | (Number::Int, b'0'..=b'9') => {
+                Some(Number::Int)
+            }
+            (Number::Zero | Number::Int, b'.') => Some(Number::Point),
+            (Number::Point | Number::Frac, b'0'..=b'9') => Some(Number::Frac),
+            (Number::Zero | Number::Int | Number::Frac, b'e' | b'E') => Some(Number::Exp),
+            (Number::Exp, b'+' | b'-') => Some(Number::ExpSign),
+            (Number::Exp | Number::ExpSign | Number::ExpInt, b'0'..=b'9') => Some(Number::ExpInt),
+            _ => None,
+        }
+    }
+
+    fn is_complete(self) -> bool {
+        matches!(
+            self,
+            Number::Zero | Number::Int | Number::Frac | Number::ExpInt
+        )
+    }
+}
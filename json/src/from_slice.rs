@@ -0,0 +1,523 @@
+use crate::{
+    read::{Container, ReadError, Stack, State, TOO_DEEP},
+    tags, Error, Position,
+};
+
+use core::{mem, str};
+
+/**
+Read a JSON string as an `sval::Value`.
+
+The input is parsed as it's streamed, so malformed JSON is only reported
+when the value is streamed. Text fragments are borrowed from the input
+wherever they don't contain escapes, and numbers are streamed as text
+tagged with [`sval::tags::NUMBER`].
+*/
+pub fn from_str(json: &str) -> &JsonSlice {
+    JsonSlice::new(json.as_bytes())
+}
+
+/**
+Read a UTF-8 encoded JSON byte slice as an `sval::Value`.
+
+See [`from_str`] for details.
+*/
+pub fn from_slice(json: &[u8]) -> &JsonSlice {
+    JsonSlice::new(json)
+}
+
+/**
+A JSON document that can be streamed as an `sval::Value`.
+
+Errors encountered while streaming the document include the position of
+the malformed input in their message. Use a [`JsonSliceReader`] to get
+the [`Error`] with its [`Position`] directly.
+*/
+#[repr(transparent)]
+pub struct JsonSlice([u8]);
+
+impl JsonSlice {
+    /**
+    Treat a UTF-8 encoded byte slice as a JSON document.
+    */
+    pub fn new(src: &[u8]) -> &JsonSlice {
+        // SAFETY: `JsonSlice` is a `repr(transparent)` wrapper over `[u8]`
+        unsafe { mem::transmute::<&[u8], &JsonSlice>(src) }
+    }
+}
+
+impl sval::Value for JsonSlice {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        JsonSliceReader::new(&self.0)
+            .stream_to_end(stream)
+            .map_err(Into::into)
+    }
+}
+
+/**
+A reader that streams a JSON document as `sval` tokens.
+
+The reader can be resumed, streaming a single token at a time.
+*/
+pub struct JsonSliceReader<'a> {
+    src: &'a [u8],
+    head: usize,
+    in_str: bool,
+    stack: Stack,
+    state: State,
+}
+
+impl<'a> JsonSliceReader<'a> {
+    /**
+    Create a reader over a UTF-8 encoded JSON document.
+    */
+    pub fn new(src: &'a [u8]) -> JsonSliceReader<'a> {
+        JsonSliceReader::starting_at(src, 0)
+    }
+
+    /**
+    Create a reader that starts part way through a document.
+
+    Positions are still reported from the start of `src`.
+    */
+    pub(crate) fn starting_at(src: &'a [u8], head: usize) -> JsonSliceReader<'a> {
+        JsonSliceReader {
+            src,
+            head,
+            in_str: false,
+            stack: Stack::new(),
+            state: State::Root,
+        }
+    }
+
+    /**
+    Get the current position of the reader in the input.
+    */
+    pub fn position(&self) -> Position {
+        Position::from_offset(self.src, self.head)
+    }
+
+    /**
+    Stream the rest of the document.
+    */
+    pub fn stream_to_end<'b, S: sval::Stream<'b> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<(), Error>
+    where
+        'a: 'b,
+    {
+        while self.stream_resume(stream)? {}
+
+        Ok(())
+    }
+
+    /**
+    Stream the next token in the document.
+
+    This method returns `false` when the document has been fully streamed.
+    */
+    pub fn stream_resume<'b, S: sval::Stream<'b> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<bool, Error>
+    where
+        'a: 'b,
+    {
+        self.stream_token(stream).map_err(|err| match err {
+            ReadError::Unexpected(offset, expected) => {
+                Error::unexpected(Position::from_offset(self.src, offset), expected)
+            }
+            ReadError::Stream(err) => Error::at(err, self.position()),
+        })
+    }
+
+    fn stream_token<'b, S: sval::Stream<'b> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<bool, ReadError>
+    where
+        'a: 'b,
+    {
+        if self.in_str {
+            self.str_fragment(stream)?;
+
+            return Ok(true);
+        }
+
+        self.skip_whitespace();
+
+        let b = match self.src.get(self.head) {
+            Some(b) => *b,
+            None if self.state == State::Done => return Ok(false),
+            None => return Err(self.unexpected()),
+        };
+
+        match (self.state, b) {
+            // Begin a map key
+            (State::MapEmpty | State::MapKey, b'"') => {
+                self.head += 1;
+                self.state = State::MapKeyEnd;
+                self.in_str = true;
+
+                stream.map_key_begin()?;
+                stream.text_begin(None)?;
+            }
+            // End a map key
+            (State::MapKeyEnd, b':') => {
+                self.head += 1;
+                self.state = State::MapValue;
+            }
+            // Separate map entries or seq elems
+            (State::MapValueEnd, b',') => {
+                self.head += 1;
+                self.state = State::MapKey;
+            }
+            (State::SeqElemEnd, b',') => {
+                self.head += 1;
+                self.state = State::SeqElem;
+            }
+            // End a map
+            (State::MapEmpty | State::MapValueEnd, b'}') => {
+                self.head += 1;
+                self.stack.pop();
+
+                stream.map_end()?;
+                self.value_end(stream)?;
+            }
+            // End a seq
+            (State::SeqEmpty | State::SeqElemEnd, b']') => {
+                self.head += 1;
+                self.stack.pop();
+
+                stream.seq_end()?;
+                self.value_end(stream)?;
+            }
+            // Begin a value
+            (State::Root | State::MapValue | State::SeqEmpty | State::SeqElem, b) => {
+                self.value(b, stream)?;
+            }
+            _ => return Err(self.unexpected()),
+        }
+
+        Ok(true)
+    }
+
+    fn value<'b, S: sval::Stream<'b> + ?Sized>(
+        &mut self,
+        b: u8,
+        stream: &mut S,
+    ) -> Result<(), ReadError>
+    where
+        'a: 'b,
+    {
+        match b {
+            // Begin a string
+            b'"' => {
+                self.head += 1;
+                self.in_str = true;
+
+                self.value_begin(stream)?;
+                stream.text_begin(None)?;
+            }
+            // Begin a map
+            b'{' => {
+                self.value_begin(stream)?;
+
+                self.stack
+                    .push(Container::Map)
+                    .ok_or(ReadError::Unexpected(self.head, TOO_DEEP))?;
+                self.head += 1;
+                self.state = State::MapEmpty;
+
+                stream.map_begin(None)?;
+            }
+            // Begin a seq
+            b'[' => {
+                self.value_begin(stream)?;
+
+                self.stack
+                    .push(Container::Seq)
+                    .ok_or(ReadError::Unexpected(self.head, TOO_DEEP))?;
+                self.head += 1;
+                self.state = State::SeqEmpty;
+
+                stream.seq_begin(None)?;
+            }
+            // The boolean value `true`
+            b't' => {
+                self.literal(b"true", "`true`")?;
+
+                self.value_begin(stream)?;
+                stream.bool(true)?;
+                self.value_end(stream)?;
+            }
+            // The boolean value `false`
+            b'f' => {
+                self.literal(b"false", "`false`")?;
+
+                self.value_begin(stream)?;
+                stream.bool(false)?;
+                self.value_end(stream)?;
+            }
+            // The value `null`
+            b'n' => {
+                self.literal(b"null", "`null`")?;
+
+                self.value_begin(stream)?;
+                stream.null()?;
+                self.value_end(stream)?;
+            }
+            // Numbers
+            b'-' | b'0'..=b'9' => {
+                let n = self.number()?;
+
+                self.value_begin(stream)?;
+
+                // The number has been validated, so it's already valid JSON
+                stream.tagged_begin(Some(&tags::JSON_NATIVE), None, None)?;
+                stream.tagged_begin(Some(&sval::tags::NUMBER), None, None)?;
+                stream.text_begin(Some(n.len()))?;
+                stream.text_fragment(n)?;
+                stream.text_end()?;
+                stream.tagged_end(Some(&sval::tags::NUMBER), None, None)?;
+                stream.tagged_end(Some(&tags::JSON_NATIVE), None, None)?;
+
+                self.value_end(stream)?;
+            }
+            _ => return Err(self.unexpected()),
+        }
+
+        Ok(())
+    }
+
+    fn value_begin<'b, S: sval::Stream<'b> + ?Sized>(&mut self, stream: &mut S) -> sval::Result {
+        match self.state {
+            State::SeqEmpty | State::SeqElem => stream.seq_value_begin(),
+            State::MapValue => stream.map_value_begin(),
+            _ => Ok(()),
+        }
+    }
+
+    fn value_end<'b, S: sval::Stream<'b> + ?Sized>(&mut self, stream: &mut S) -> sval::Result {
+        match self.stack.top() {
+            Some(Container::Map) => {
+                self.state = State::MapValueEnd;
+
+                stream.map_value_end()
+            }
+            Some(Container::Seq) => {
+                self.state = State::SeqElemEnd;
+
+                stream.seq_value_end()
+            }
+            None => {
+                self.state = State::Done;
+
+                Ok(())
+            }
+        }
+    }
+
+    fn str_fragment<'b, S: sval::Stream<'b> + ?Sized>(
+        &mut self,
+        stream: &mut S,
+    ) -> Result<(), ReadError>
+    where
+        'a: 'b,
+    {
+        let start = self.head;
+
+        // Scan through the input until we reach the end or an escaped character
+        while let Some(b) = self.src.get(self.head) {
+            match *b {
+                b'"' | b'\\' => break,
+                0x00..=0x1f => {
+                    return Err(ReadError::Unexpected(
+                        self.head,
+                        "an escaped control character",
+                    ))
+                }
+                _ => self.head += 1,
+            }
+        }
+
+        // Fragments end at an ASCII character, so they're never split within a UTF-8 sequence
+        if self.head > start {
+            let fragment = str::from_utf8(&self.src[start..self.head])
+                .map_err(|err| ReadError::Unexpected(start + err.valid_up_to(), "valid UTF-8"))?;
+
+            stream.text_fragment(fragment)?;
+        }
+
+        match self.src.get(self.head) {
+            Some(b'"') => {
+                self.head += 1;
+                self.in_str = false;
+
+                stream.text_end()?;
+
+                if self.state == State::MapKeyEnd {
+                    stream.map_key_end()?;
+                } else {
+                    self.value_end(stream)?;
+                }
+            }
+            Some(b'\\') => {
+                let escaped = self.escape()?;
+
+                stream.text_fragment_computed(escaped.encode_utf8(&mut [0; 4]))?;
+            }
+            _ => return Err(ReadError::Unexpected(self.head, "`\"`")),
+        }
+
+        Ok(())
+    }
+
+    fn escape(&mut self) -> Result<char, ReadError> {
+        // Skip the leading `\`
+        self.head += 1;
+
+        let escaped = match self.src.get(self.head) {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let start = self.head - 1;
+
+                self.head += 1;
+                let high = self.hex()?;
+
+                let c = match high {
+                    // A high surrogate must be followed by an escaped low surrogate
+                    0xd800..=0xdbff => {
+                        if self.src.get(self.head..self.head + 2) != Some(b"\\u") {
+                            return Err(ReadError::Unexpected(self.head, "a low surrogate"));
+                        }
+
+                        let low_start = self.head;
+
+                        self.head += 2;
+                        let low = self.hex()?;
+
+                        if !(0xdc00..=0xdfff).contains(&low) {
+                            return Err(ReadError::Unexpected(low_start, "a low surrogate"));
+                        }
+
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    }
+                    0xdc00..=0xdfff => {
+                        return Err(ReadError::Unexpected(start, "a high surrogate"));
+                    }
+                    c => c,
+                };
+
+                return char::from_u32(c).ok_or(ReadError::Unexpected(start, "a valid character"));
+            }
+            _ => return Err(ReadError::Unexpected(self.head, "a valid escape sequence")),
+        };
+
+        self.head += 1;
+
+        Ok(escaped)
+    }
+
+    fn hex(&mut self) -> Result<u32, ReadError> {
+        let mut value = 0;
+
+        for _ in 0..4 {
+            let digit = self
+                .src
+                .get(self.head)
+                .and_then(|b| (*b as char).to_digit(16))
+                .ok_or(ReadError::Unexpected(self.head, "a hex digit"))?;
+
+            value = (value << 4) | digit;
+            self.head += 1;
+        }
+
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<&'a str, ReadError> {
+        let start = self.head;
+
+        if let Some(b'-') = self.src.get(self.head) {
+            self.head += 1;
+        }
+
+        // The integer part can't have leading zeroes
+        match self.src.get(self.head) {
+            Some(b'0') => self.head += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(ReadError::Unexpected(self.head, "a digit")),
+        }
+
+        if let Some(b'.') = self.src.get(self.head) {
+            self.head += 1;
+            self.digits1()?;
+        }
+
+        if let Some(b'e' | b'E') = self.src.get(self.head) {
+            self.head += 1;
+
+            if let Some(b'+' | b'-') = self.src.get(self.head) {
+                self.head += 1;
+            }
+
+            self.digits1()?;
+        }
+
+        str::from_utf8(&self.src[start..self.head])
+            .map_err(|_| ReadError::Unexpected(start, "a number"))
+    }
+
+    fn digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.src.get(self.head) {
+            self.head += 1;
+        }
+    }
+
+    fn digits1(&mut self) -> Result<(), ReadError> {
+        let start = self.head;
+
+        self.digits();
+
+        if self.head == start {
+            Err(ReadError::Unexpected(self.head, "a digit"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn literal(&mut self, literal: &'static [u8], expected: &'static str) -> Result<(), ReadError> {
+        let matched = literal
+            .iter()
+            .zip(&self.src[self.head..])
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if matched != literal.len() {
+            return Err(ReadError::Unexpected(self.head + matched, expected));
+        }
+
+        self.head += literal.len();
+
+        Ok(())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\r' | b'\n') = self.src.get(self.head) {
+            self.head += 1;
+        }
+    }
+
+    fn unexpected(&self) -> ReadError {
+        ReadError::Unexpected(self.head, self.state.expected())
+    }
+}
@@ -2,7 +2,23 @@
 JSON support for `sval`.
 
 This crate provides the [`Formatter`] type, an `sval::Stream` that produces JSON.
-Data is serialized in a `serde`-compatible way.
+Data is serialized in a `serde`-compatible way by default. A [`Config`] can be
+passed to the `_with` variants of the streaming functions to change how JSON is written.
+Binary values written as encoded strings can be decoded again with [`DecodeBinary`],
+and numbers written as strings with [`DecodeNumbers`].
+Canonical JSON (RFC 8785) can be written using the `_canonical` variants.
+
+JSON can also be read as an `sval::Value` using [`from_str`] or [`from_slice`].
+When the `std` feature is enabled, JSON can be read incrementally from an
+`io::Read` using `from_reader`.
+
+Newline-delimited JSON is supported by the `_ndjson` variants of the
+streaming functions, and [`from_str_ndjson`] or [`from_slice_ndjson`].
+
+Individual values can be selected from larger ones using a JSON [`Pointer`].
+When the `alloc` feature is enabled, values can be validated against a JSON
+Schema using `Schema`, and JSON Schema documents can be generated for types
+that implement `sval::schema::Describe` using `describe_to_string`.
 */
 
 #![no_std]
@@ -13,10 +29,23 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+mod binary;
+mod canonical;
+mod config;
 mod error;
+mod path;
 
+mod from_slice;
+mod ndjson;
+mod numbers;
+mod pointer;
+mod pretty;
+mod read;
 mod to_fmt;
-pub use self::{error::*, to_fmt::*};
+pub use self::{
+    binary::*, config::*, error::*, from_slice::*, ndjson::*, numbers::*, pointer::*, pretty::*,
+    to_fmt::*,
+};
 
 pub mod tags {
     /**
@@ -26,16 +55,71 @@ pub mod tags {
     For numbers, that means they're already in a JSON compatible format.
     */
     pub const JSON_NATIVE: sval::Tag = sval::Tag::new("svaljsonnat");
+
+    /**
+    A tag for binary values that are written as standard base64 strings.
+
+    See [`Binary::Base64`](crate::Binary::Base64) for details.
+    */
+    pub const BASE64: sval::Tag = sval::Tag::new("svaljsonb64");
+
+    /**
+    A tag for binary values that are written as URL-safe base64 strings.
+
+    See [`Binary::Base64Url`](crate::Binary::Base64Url) for details.
+    */
+    pub const BASE64_URL: sval::Tag = sval::Tag::new("svaljsonb64url");
+
+    /**
+    A tag for binary values that are written as hex strings.
+
+    See [`Binary::Hex`](crate::Binary::Hex) for details.
+    */
+    pub const HEX: sval::Tag = sval::Tag::new("svaljsonhex");
+
+    /**
+    A tag for enums that are written externally tagged.
+
+    See [`Enums::External`](crate::Enums::External) for details.
+    */
+    pub const EXTERNALLY_TAGGED: sval::Tag = sval::Tag::new("svaljsonenumext");
+
+    /**
+    A tag for enums that are written internally tagged.
+
+    See [`Enums::Internal`](crate::Enums::Internal) for details.
+    */
+    pub const INTERNALLY_TAGGED: sval::Tag = sval::Tag::new("svaljsonenumint");
+
+    /**
+    A tag for enums that are written adjacently tagged.
+
+    See [`Enums::Adjacent`](crate::Enums::Adjacent) for details.
+    */
+    pub const ADJACENTLY_TAGGED: sval::Tag = sval::Tag::new("svaljsonenumadj");
+
+    /**
+    A tag for enums that are written untagged.
+
+    See [`Enums::Untagged`](crate::Enums::Untagged) for details.
+    */
+    pub const UNTAGGED: sval::Tag = sval::Tag::new("svaljsonenumuntagged");
 }
 
+#[cfg(feature = "alloc")]
+mod describe;
 #[cfg(feature = "alloc")]
 mod to_string;
+#[cfg(feature = "alloc")]
+mod validate;
 
 #[cfg(feature = "alloc")]
-pub use self::to_string::*;
+pub use self::{describe::*, to_string::*, validate::*};
 
+#[cfg(feature = "std")]
+mod from_reader;
 #[cfg(feature = "std")]
 mod to_writer;
 
 #[cfg(feature = "std")]
-pub use self::to_writer::*;
+pub use self::{from_reader::*, to_writer::*};
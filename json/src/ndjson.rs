@@ -0,0 +1,246 @@
+use core::fmt::{self, Write};
+
+use crate::{Error, JsonSliceReader};
+
+/**
+Stream a sequence of values as newline-delimited JSON.
+
+Each value is written as compact JSON on its own line, followed by a `\n`.
+If a value would produce a raw newline, such as from JSON native text, then
+streaming fails and the error's [`Position`](crate::Position) points to the
+line of the value.
+
+When the `alloc` feature is enabled, each line is buffered and only written
+once its value has been streamed, so a value that fails doesn't leave part of
+its line in `fmt`. Without it, lines are written as they're streamed.
+*/
+pub fn stream_to_fmt_ndjson<V: sval::Value>(
+    fmt: impl Write,
+    values: impl IntoIterator<Item = V>,
+) -> Result<(), Error> {
+    let mut out = Lines {
+        out: fmt,
+        offset: 0,
+        is_newline: false,
+        #[cfg(feature = "alloc")]
+        line: alloc::string::String::new(),
+    };
+
+    for (index, v) in values.into_iter().enumerate() {
+        let line = index + 1;
+        let offset = out.offset;
+
+        crate::stream_to_fmt(&mut out, v).map_err(|err| {
+            if out.is_newline {
+                Error::new(
+                    sval::Error::unsupported().with_message("the value contains a raw newline"),
+                )
+                .at_line(line, offset)
+            } else {
+                err.at_line(line, offset)
+            }
+        })?;
+
+        out.newline()
+            .map_err(|err| Error::new(err.into()).at_line(line, offset))?;
+    }
+
+    Ok(())
+}
+
+/**
+A writer that refuses to write newlines except at the end of each line.
+*/
+struct Lines<W> {
+    out: W,
+    // The number of bytes written to `out`
+    offset: usize,
+    is_newline: bool,
+    #[cfg(feature = "alloc")]
+    line: alloc::string::String,
+}
+
+impl<W: Write> Lines<W> {
+    fn newline(&mut self) -> fmt::Result {
+        #[cfg(feature = "alloc")]
+        {
+            self.out.write_str(&self.line)?;
+            self.offset += self.line.len();
+            self.line.clear();
+        }
+
+        self.out.write_char('\n')?;
+        self.offset += 1;
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Lines<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.contains(['\n', '\r']) {
+            self.is_newline = true;
+
+            return Err(fmt::Error);
+        }
+
+        #[cfg(feature = "alloc")]
+        {
+            self.line.push_str(s);
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            self.out.write_str(s)?;
+            self.offset += s.len();
+        }
+
+        Ok(())
+    }
+}
+
+/**
+Read a newline-delimited JSON string as a sequence of `sval::Value`s.
+
+Each non-empty line is read as its own JSON document. See [`JsonLine`] for details.
+*/
+pub fn from_str_ndjson(json: &str) -> JsonLines<'_> {
+    from_slice_ndjson(json.as_bytes())
+}
+
+/**
+Read a UTF-8 encoded newline-delimited JSON byte slice as a sequence of `sval::Value`s.
+
+See [`from_str_ndjson`] for details.
+*/
+pub fn from_slice_ndjson(json: &[u8]) -> JsonLines<'_> {
+    JsonLines {
+        src: json,
+        head: 0,
+        line: 1,
+    }
+}
+
+/**
+An iterator over the lines of a newline-delimited JSON document.
+
+Lines that only contain whitespace are skipped.
+*/
+#[derive(Debug, Clone)]
+pub struct JsonLines<'a> {
+    src: &'a [u8],
+    head: usize,
+    line: usize,
+}
+
+impl<'a> Iterator for JsonLines<'a> {
+    type Item = JsonLine<'a>;
+
+    fn next(&mut self) -> Option<JsonLine<'a>> {
+        while self.head < self.src.len() {
+            let start = self.head;
+            let end = self.src[start..]
+                .iter()
+                .position(|b| *b == b'\n')
+                .map(|newline| start + newline)
+                .unwrap_or(self.src.len());
+
+            let line = self.line;
+
+            self.head = end + 1;
+            self.line += 1;
+
+            if self.src[start..end]
+                .iter()
+                .all(|b| matches!(b, b' ' | b'\t' | b'\r'))
+            {
+                continue;
+            }
+
+            return Some(JsonLine {
+                src: &self.src[..end],
+                start,
+                line,
+            });
+        }
+
+        None
+    }
+}
+
+/**
+A single line of a newline-delimited JSON document.
+
+The line can be streamed as an `sval::Value`. Errors encountered while
+streaming it report their [`Position`](crate::Position) from the start of the
+whole document, so they point to the line that failed.
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct JsonLine<'a> {
+    src: &'a [u8],
+    start: usize,
+    line: usize,
+}
+
+impl<'a> JsonLine<'a> {
+    /**
+    The line number of this value in the document, starting from `1`.
+    */
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /**
+    Get a reader for this line.
+    */
+    pub fn reader(&self) -> JsonSliceReader<'a> {
+        JsonSliceReader::starting_at(self.src, self.start)
+    }
+}
+
+impl<'a> sval::Value for JsonLine<'a> {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        self.reader().stream_to_end(stream).map_err(Into::into)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    use alloc::string::String;
+
+    /**
+    Stream a sequence of values as newline-delimited JSON into a string.
+    */
+    pub fn stream_to_string_ndjson<V: sval::Value>(
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<String, Error> {
+        let mut out = String::new();
+        stream_to_fmt_ndjson(&mut out, values)?;
+
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use self::alloc_support::*;
+
+#[cfg(feature = "std")]
+mod std_support {
+    use super::*;
+
+    use std::io::Write;
+
+    /**
+    Stream a sequence of values as newline-delimited JSON into a writer.
+    */
+    pub fn stream_to_writer_ndjson<V: sval::Value>(
+        io: impl Write,
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<(), Error> {
+        crate::to_writer::stream_to_io(io, |fmt| stream_to_fmt_ndjson(fmt, values))
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::std_support::*;
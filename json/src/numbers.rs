@@ -0,0 +1,381 @@
+use core::str;
+
+/**
+A stream adapter that reverses the number encodings written by the JSON formatter.
+
+Strings written by [`NonFinite::String`](crate::NonFinite::String) and
+[`LargeIntegers::String`](crate::LargeIntegers::String) are forwarded to the
+inner stream as text tagged with [`sval::tags::NUMBER`], so they can be
+read back into numbers. That means the strings `"NaN"`, `"Infinity"`, and
+`"-Infinity"`, and strings containing integers outside of the range
+`-(2^53 - 1)` to `2^53 - 1`. Map keys and all other text are forwarded unchanged.
+
+Strings are only buffered until they're too long to be a number.
+*/
+pub struct DecodeNumbers<S> {
+    key_depth: usize,
+    number_depth: usize,
+    pending: Option<Pending>,
+    stream: S,
+}
+
+/**
+The start of a string that might be a number.
+*/
+struct Pending {
+    num_bytes_hint: Option<usize>,
+    buf: [u8; 40],
+    len: usize,
+}
+
+impl<S> DecodeNumbers<S> {
+    /**
+    Wrap a stream, decoding strings that contain numbers.
+    */
+    pub fn new(stream: S) -> Self {
+        DecodeNumbers {
+            key_depth: 0,
+            number_depth: 0,
+            pending: None,
+            stream,
+        }
+    }
+
+    /**
+    Get the inner stream.
+    */
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<'sval, S: sval::Stream<'sval>> DecodeNumbers<S> {
+    /**
+    Forward any text buffered so far, because it can't be a number.
+    */
+    fn flush(&mut self) -> sval::Result {
+        if let Some(pending) = self.pending.take() {
+            self.stream.text_begin(pending.num_bytes_hint)?;
+
+            // The buffer only contains complete fragments, so is valid UTF-8
+            let text = str::from_utf8(&pending.buf[..pending.len])
+                .map_err(|_| sval::Error::invalid_value())?;
+
+            if !text.is_empty() {
+                self.stream.text_fragment_computed(text)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fragment(&mut self, fragment: &str) -> sval::Result<bool> {
+        match self.pending {
+            Some(ref mut pending) if pending.len + fragment.len() <= pending.buf.len() => {
+                pending.buf[pending.len..pending.len + fragment.len()]
+                    .copy_from_slice(fragment.as_bytes());
+                pending.len += fragment.len();
+
+                Ok(true)
+            }
+            Some(_) => {
+                self.flush()?;
+
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+fn is_number(text: &str) -> bool {
+    if let "NaN" | "Infinity" | "-Infinity" = text {
+        return true;
+    }
+
+    let digits = text.strip_prefix('-').unwrap_or(text);
+
+    // Only integers that were too large to write as numbers are decoded
+    !digits.is_empty()
+        && digits.bytes().all(|b| b.is_ascii_digit())
+        && !(digits.len() > 1 && digits.starts_with('0'))
+        && (digits.len() > 16 || (digits.len() == 16 && digits > "9007199254740991"))
+}
+
+impl<'sval, S: sval::Stream<'sval>> sval::Stream<'sval> for DecodeNumbers<S> {
+    fn null(&mut self) -> sval::Result {
+        self.stream.null()
+    }
+
+    fn bool(&mut self, value: bool) -> sval::Result {
+        self.stream.bool(value)
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        if self.key_depth == 0 && self.number_depth == 0 {
+            self.pending = Some(Pending {
+                num_bytes_hint,
+                buf: [0; 40],
+                len: 0,
+            });
+
+            Ok(())
+        } else {
+            self.stream.text_begin(num_bytes_hint)
+        }
+    }
+
+    fn text_fragment(&mut self, fragment: &'sval str) -> sval::Result {
+        if self.fragment(fragment)? {
+            Ok(())
+        } else {
+            self.stream.text_fragment(fragment)
+        }
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+        if self.fragment(fragment)? {
+            Ok(())
+        } else {
+            self.stream.text_fragment_computed(fragment)
+        }
+    }
+
+    fn text_end(&mut self) -> sval::Result {
+        let is_number = match self.pending {
+            Some(ref pending) => is_number(
+                str::from_utf8(&pending.buf[..pending.len])
+                    .map_err(|_| sval::Error::invalid_value())?,
+            ),
+            None => false,
+        };
+
+        if is_number {
+            self.stream
+                .tagged_begin(Some(&sval::tags::NUMBER), None, None)?;
+            self.flush()?;
+            self.stream.text_end()?;
+            self.stream
+                .tagged_end(Some(&sval::tags::NUMBER), None, None)
+        } else {
+            self.flush()?;
+            self.stream.text_end()
+        }
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        self.stream.binary_begin(num_bytes_hint)
+    }
+
+    fn binary_fragment(&mut self, fragment: &'sval [u8]) -> sval::Result {
+        self.stream.binary_fragment(fragment)
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> sval::Result {
+        self.stream.binary_fragment_computed(fragment)
+    }
+
+    fn binary_end(&mut self) -> sval::Result {
+        self.stream.binary_end()
+    }
+
+    fn u8(&mut self, value: u8) -> sval::Result {
+        self.stream.u8(value)
+    }
+
+    fn u16(&mut self, value: u16) -> sval::Result {
+        self.stream.u16(value)
+    }
+
+    fn u32(&mut self, value: u32) -> sval::Result {
+        self.stream.u32(value)
+    }
+
+    fn u64(&mut self, value: u64) -> sval::Result {
+        self.stream.u64(value)
+    }
+
+    fn u128(&mut self, value: u128) -> sval::Result {
+        self.stream.u128(value)
+    }
+
+    fn i8(&mut self, value: i8) -> sval::Result {
+        self.stream.i8(value)
+    }
+
+    fn i16(&mut self, value: i16) -> sval::Result {
+        self.stream.i16(value)
+    }
+
+    fn i32(&mut self, value: i32) -> sval::Result {
+        self.stream.i32(value)
+    }
+
+    fn i64(&mut self, value: i64) -> sval::Result {
+        self.stream.i64(value)
+    }
+
+    fn i128(&mut self, value: i128) -> sval::Result {
+        self.stream.i128(value)
+    }
+
+    fn f32(&mut self, value: f32) -> sval::Result {
+        self.stream.f32(value)
+    }
+
+    fn f64(&mut self, value: f64) -> sval::Result {
+        self.stream.f64(value)
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        self.stream.map_begin(num_entries_hint)
+    }
+
+    fn map_key_begin(&mut self) -> sval::Result {
+        self.key_depth += 1;
+
+        self.stream.map_key_begin()
+    }
+
+    fn map_key_end(&mut self) -> sval::Result {
+        self.key_depth -= 1;
+
+        self.stream.map_key_end()
+    }
+
+    fn map_value_begin(&mut self) -> sval::Result {
+        self.stream.map_value_begin()
+    }
+
+    fn map_value_end(&mut self) -> sval::Result {
+        self.stream.map_value_end()
+    }
+
+    fn map_end(&mut self) -> sval::Result {
+        self.stream.map_end()
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        self.stream.seq_begin(num_entries_hint)
+    }
+
+    fn seq_value_begin(&mut self) -> sval::Result {
+        self.stream.seq_value_begin()
+    }
+
+    fn seq_value_end(&mut self) -> sval::Result {
+        self.stream.seq_value_end()
+    }
+
+    fn seq_end(&mut self) -> sval::Result {
+        self.stream.seq_end()
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.enum_begin(tag, label, index)
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.enum_end(tag, label, index)
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.number_depth += 1;
+        }
+
+        self.stream.tagged_begin(tag, label, index)
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.number_depth -= 1;
+        }
+
+        self.stream.tagged_end(tag, label, index)
+    }
+
+    fn tag(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.tag(tag, label, index)
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        self.stream
+            .record_begin(tag, label, index, num_entries_hint)
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        self.stream.record_value_begin(tag, label)
+    }
+
+    fn record_value_end(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        self.stream.record_value_end(tag, label)
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.record_end(tag, label, index)
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        self.stream.tuple_begin(tag, label, index, num_entries_hint)
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        self.stream.tuple_value_begin(tag, index)
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        self.stream.tuple_value_end(tag, index)
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.stream.tuple_end(tag, label, index)
+    }
+}
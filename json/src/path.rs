@@ -0,0 +1,158 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/**
+The path from the root of a value down to the value currently being streamed.
+
+When the `alloc` feature isn't enabled, this type doesn't track anything.
+*/
+pub(crate) struct Path {
+    #[cfg(feature = "alloc")]
+    frames: Vec<Frame>,
+    // The text of all computed labels and map keys in the current path
+    // Frames point into this buffer so they don't need their own allocations
+    #[cfg(feature = "alloc")]
+    text: String,
+}
+
+#[cfg(feature = "alloc")]
+enum Frame {
+    Seq(usize),
+    Index(usize),
+    Label(&'static str),
+    ComputedLabel(usize, usize),
+    KeyBegin(usize),
+    Key(usize, usize),
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path {
+            #[cfg(feature = "alloc")]
+            frames: Vec::new(),
+            #[cfg(feature = "alloc")]
+            text: String::new(),
+        }
+    }
+
+    pub fn seq_begin(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            self.frames.push(Frame::Seq(0));
+        }
+    }
+
+    pub fn seq_value_begin(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            let index = match self.frames.last_mut() {
+                Some(Frame::Seq(next)) => {
+                    let index = *next;
+                    *next += 1;
+                    index
+                }
+                _ => 0,
+            };
+
+            self.frames.push(Frame::Index(index));
+        }
+    }
+
+    pub fn seq_end(&mut self) {
+        self.pop();
+    }
+
+    pub fn map_key_begin(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            self.frames.push(Frame::KeyBegin(self.text.len()));
+        }
+    }
+
+    pub fn map_key_fragment(&mut self, fragment: &str) {
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(Frame::KeyBegin(_)) = self.frames.last() {
+                self.text.push_str(fragment);
+            }
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = fragment;
+        }
+    }
+
+    pub fn map_key_end(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            let end = self.text.len();
+
+            if let Some(frame) = self.frames.last_mut() {
+                if let Frame::KeyBegin(start) = *frame {
+                    *frame = Frame::Key(start, end);
+                }
+            }
+        }
+    }
+
+    pub fn record_value_begin(&mut self, label: &sval::Label) {
+        #[cfg(feature = "alloc")]
+        {
+            match label.as_static_str() {
+                Some(label) => self.frames.push(Frame::Label(label)),
+                None => {
+                    let start = self.text.len();
+                    self.text.push_str(label.as_str());
+
+                    self.frames
+                        .push(Frame::ComputedLabel(start, self.text.len()));
+                }
+            }
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = label;
+        }
+    }
+
+    pub fn pop(&mut self) {
+        #[cfg(feature = "alloc")]
+        {
+            match self.frames.pop() {
+                Some(Frame::KeyBegin(start))
+                | Some(Frame::Key(start, _))
+                | Some(Frame::ComputedLabel(start, _)) => self.text.truncate(start),
+                _ => (),
+            }
+        }
+    }
+
+    pub fn attach(&self, err: sval::Error) -> sval::Error {
+        #[cfg(feature = "alloc")]
+        {
+            // Errors that already carry a path were attached closer to the source
+            if !err.path().is_empty() {
+                return err;
+            }
+
+            // The path is only built when something fails, attaching segments
+            // from the innermost value outwards like the error unwinding
+            self.frames
+                .iter()
+                .rev()
+                .fold(err, |err, frame| match *frame {
+                    Frame::Index(index) => err.at_index(&sval::Index::new(index)),
+                    Frame::Label(label) => err.at_label(&sval::Label::new(label)),
+                    Frame::ComputedLabel(start, end) => {
+                        err.at_label(&sval::Label::from_computed(&self.text[start..end]))
+                    }
+                    Frame::Key(start, end) => err.at_key(&self.text[start..end]),
+                    Frame::Seq(_) | Frame::KeyBegin(_) => err,
+                })
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            err
+        }
+    }
+}
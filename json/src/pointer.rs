@@ -0,0 +1,811 @@
+use core::fmt;
+
+use crate::Error;
+
+/**
+A JSON Pointer, as described in RFC 6901, like `/items/3/price`.
+
+Pointers are resolved against the `sval` data model, so they work over any
+`sval::Value`, including JSON read with [`from_str`](crate::from_str). Map
+values are selected by their text or integer keys, record values by their
+labels, and sequence and tuple values by their index. Enums and tagged values
+are transparent, so a pointer selects through them to the values they contain.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pointer<'a> {
+    pointer: &'a str,
+}
+
+impl<'a> Pointer<'a> {
+    /**
+    Parse a JSON Pointer.
+
+    The pointer must either be empty, which selects the whole value, or start with a `/`.
+    The only escapes allowed in reference tokens are `~0` for `~` and `~1` for `/`.
+    */
+    pub fn parse(pointer: &'a str) -> Result<Self, Error> {
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(Error::new(
+                sval::Error::invalid_value().with_message("a JSON pointer must start with `/`"),
+            ));
+        }
+
+        let mut escapes = pointer.split('~').skip(1);
+        if escapes.any(|escape| !escape.starts_with(['0', '1'])) {
+            return Err(Error::new(
+                sval::Error::invalid_value()
+                    .with_message("a JSON pointer can only escape `~0` or `~1`"),
+            ));
+        }
+
+        Ok(Pointer { pointer })
+    }
+
+    /**
+    Get the pointer as a string.
+    */
+    pub fn as_str(&self) -> &'a str {
+        self.pointer
+    }
+
+    /**
+    Select the value this pointer points to from the given value.
+
+    The returned value streams only the selected value, and fails with an
+    `InvalidValue` error if the pointer doesn't resolve.
+    */
+    pub fn select<V: sval::Value>(self, value: V) -> Selected<'a, V> {
+        Selected {
+            pointer: self,
+            value,
+        }
+    }
+
+    fn tokens(&self) -> impl Iterator<Item = &'a str> {
+        self.pointer.split('/').skip(1)
+    }
+}
+
+impl<'a> fmt::Display for Pointer<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.pointer)
+    }
+}
+
+/**
+A value selected from another by a [`Pointer`].
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct Selected<'a, V> {
+    pointer: Pointer<'a>,
+    value: V,
+}
+
+impl<'a, V: sval::Value> sval::Value for Selected<'a, V> {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        let mut select = Select::new(self.pointer, stream);
+
+        self.value.stream(&mut select)?;
+        select.finish()?;
+
+        Ok(())
+    }
+}
+
+/**
+A stream adapter that only forwards the value a [`Pointer`] points to.
+
+Everything outside of the selected value is skipped without being buffered,
+and text is forwarded as it's received, so borrowed fragments stay borrowed.
+Once the value has been streamed, call [`Select::finish`] to check whether
+the pointer resolved.
+*/
+pub struct Select<'a, S> {
+    pointer: Pointer<'a>,
+    // The reference token to match next, or `None` if they've all matched
+    token: Option<&'a str>,
+    tokens: core::iter::Skip<core::str::Split<'a, char>>,
+    matched: usize,
+    // Whether the next value to begin is the one the matched tokens point to
+    is_candidate: bool,
+    // The depth of the container whose values are being matched against `token`
+    candidate_depth: usize,
+    next_index: usize,
+    key: Option<KeyMatcher<'a>>,
+    is_key_matched: bool,
+    depth: usize,
+    state: State,
+    stream: S,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Searching,
+    // Forwarding the selected value, which began at the given depth
+    Selecting(usize),
+    Found,
+    Unresolved,
+}
+
+/**
+The kind of a value that's beginning.
+*/
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    // A map, sequence, record, or tuple, which reference tokens can select into
+    Container,
+    // An enum or tagged value, which reference tokens select through
+    Wrapper,
+    // Text or binary, which reference tokens can't select into
+    Leaf,
+}
+
+impl<'a, S> Select<'a, S> {
+    /**
+    Wrap a stream, forwarding only the value the pointer points to.
+    */
+    pub fn new(pointer: Pointer<'a>, stream: S) -> Self {
+        let mut tokens = pointer.pointer.split('/').skip(1);
+
+        Select {
+            pointer,
+            token: tokens.next(),
+            tokens,
+            matched: 0,
+            is_candidate: true,
+            candidate_depth: 0,
+            next_index: 0,
+            key: None,
+            is_key_matched: false,
+            depth: 0,
+            state: State::Searching,
+            stream,
+        }
+    }
+
+    /**
+    Check whether the pointer resolved, and get the inner stream.
+
+    If the pointer didn't resolve then this method fails with an `InvalidValue` error.
+    The error's [`Error::unresolved_token`] is the index of the first reference
+    token that couldn't be matched.
+    */
+    pub fn finish(self) -> Result<S, Error> {
+        match self.state {
+            State::Found => Ok(self.stream),
+            _ => Err(Error::unresolved(
+                self.pointer,
+                self.matched,
+                self.pointer.tokens().nth(self.matched).unwrap_or(""),
+            )),
+        }
+    }
+
+    /**
+    Check whether a value that's beginning should be forwarded.
+    */
+    fn select_begin(&mut self) -> bool {
+        match self.state {
+            State::Selecting(_) => true,
+            State::Searching if self.is_candidate && self.token.is_none() => {
+                self.state = State::Selecting(self.depth);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /**
+    Check whether a value that's ending should be forwarded.
+    */
+    fn select_end(&mut self) -> bool {
+        match self.state {
+            State::Selecting(depth) => {
+                if self.depth == depth {
+                    self.state = State::Found;
+                }
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn begin(&mut self, kind: Kind) -> bool {
+        let forward = self.select_begin();
+        self.depth += 1;
+
+        if !forward && self.state == State::Searching {
+            if self.key.is_some() && kind == Kind::Container {
+                self.key = Some(KeyMatcher::mismatch());
+            }
+
+            if self.is_candidate {
+                match kind {
+                    Kind::Container => {
+                        self.is_candidate = false;
+                        self.candidate_depth = self.depth;
+                        self.next_index = 0;
+                    }
+                    Kind::Wrapper => (),
+                    Kind::Leaf => self.state = State::Unresolved,
+                }
+            }
+        }
+
+        forward
+    }
+
+    fn end(&mut self) -> bool {
+        // If the container being matched ends then none of its values matched
+        if self.state == State::Searching && self.depth == self.candidate_depth {
+            self.state = State::Unresolved;
+        }
+
+        self.depth -= 1;
+        self.select_end()
+    }
+
+    fn scalar(&mut self) -> bool {
+        if self.select_begin() {
+            self.select_end()
+        } else {
+            if self.state == State::Searching && self.is_candidate {
+                self.state = State::Unresolved;
+            }
+
+            false
+        }
+    }
+
+    /**
+    Check whether a value of the container being matched is beginning.
+    */
+    fn is_candidate_value(&self) -> bool {
+        self.state == State::Searching && !self.is_candidate && self.depth == self.candidate_depth
+    }
+
+    fn index_value_begin(&mut self) {
+        if self.is_candidate_value() {
+            let index = self.next_index;
+            self.next_index += 1;
+
+            if self.token.and_then(parse_index) == Some(index) {
+                self.advance();
+            }
+        }
+    }
+
+    fn key_fragment(&mut self, fragment: &str) {
+        if let Some(ref mut key) = self.key {
+            key.fragment(fragment);
+        }
+    }
+
+    /**
+    Move on to the next reference token.
+    */
+    fn advance(&mut self) {
+        self.token = self.tokens.next();
+        self.matched += 1;
+        self.is_candidate = true;
+    }
+}
+
+/**
+Parse a reference token as an array index.
+
+Indexes can't have leading zeroes, and the `-` index is never matched.
+*/
+fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+
+    token.parse().ok()
+}
+
+/**
+Match a key, fragment by fragment, against an escaped reference token.
+*/
+struct KeyMatcher<'a> {
+    token: Option<&'a str>,
+}
+
+impl<'a> KeyMatcher<'a> {
+    fn new(token: &'a str) -> Self {
+        KeyMatcher { token: Some(token) }
+    }
+
+    fn mismatch() -> Self {
+        KeyMatcher { token: None }
+    }
+
+    fn fragment(&mut self, fragment: &str) {
+        for c in fragment.chars() {
+            let token = match self.token {
+                Some(token) => token,
+                None => return,
+            };
+
+            self.token = match token.strip_prefix('~') {
+                Some(escaped) if c == '~' => escaped.strip_prefix('0'),
+                Some(escaped) if c == '/' => escaped.strip_prefix('1'),
+                Some(_) => None,
+                None => token.strip_prefix(c),
+            };
+        }
+    }
+
+    fn is_match(&self) -> bool {
+        self.token == Some("")
+    }
+}
+
+impl<'a, 'sval, S: sval::Stream<'sval>> sval::Stream<'sval> for Select<'a, S> {
+    fn null(&mut self) -> sval::Result {
+        if self.scalar() {
+            self.stream.null()?;
+        }
+
+        Ok(())
+    }
+
+    fn bool(&mut self, value: bool) -> sval::Result {
+        if self.scalar() {
+            self.stream.bool(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        if self.begin(Kind::Leaf) {
+            self.stream.text_begin(num_bytes_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn text_fragment(&mut self, fragment: &'sval str) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.text_fragment(fragment)?;
+        } else {
+            self.key_fragment(fragment);
+        }
+
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.text_fragment_computed(fragment)?;
+        } else {
+            self.key_fragment(fragment);
+        }
+
+        Ok(())
+    }
+
+    fn text_end(&mut self) -> sval::Result {
+        if self.end() {
+            self.stream.text_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        if self.begin(Kind::Leaf) {
+            self.stream.binary_begin(num_bytes_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn binary_fragment(&mut self, fragment: &'sval [u8]) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.binary_fragment(fragment)?;
+        }
+
+        Ok(())
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.binary_fragment_computed(fragment)?;
+        }
+
+        Ok(())
+    }
+
+    fn binary_end(&mut self) -> sval::Result {
+        if self.end() {
+            self.stream.binary_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn u8(&mut self, value: u8) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.u8(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn u16(&mut self, value: u16) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.u16(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn u32(&mut self, value: u32) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.u32(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn u64(&mut self, value: u64) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.u64(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn u128(&mut self, value: u128) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.u128(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn i8(&mut self, value: i8) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.i8(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn i16(&mut self, value: i16) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.i16(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn i32(&mut self, value: i32) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.i32(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn i64(&mut self, value: i64) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.i64(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn i128(&mut self, value: i128) -> sval::Result {
+        self.key_fragment(itoa::Buffer::new().format(value));
+
+        if self.scalar() {
+            self.stream.i128(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn f32(&mut self, value: f32) -> sval::Result {
+        if self.scalar() {
+            self.stream.f32(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn f64(&mut self, value: f64) -> sval::Result {
+        if self.scalar() {
+            self.stream.f64(value)?;
+        }
+
+        Ok(())
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        if self.begin(Kind::Container) {
+            self.stream.map_begin(num_entries_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn map_key_begin(&mut self) -> sval::Result {
+        if self.is_candidate_value() {
+            if let Some(token) = self.token {
+                self.key = Some(KeyMatcher::new(token));
+            }
+        }
+
+        if let State::Selecting(_) = self.state {
+            self.stream.map_key_begin()?;
+        }
+
+        Ok(())
+    }
+
+    fn map_key_end(&mut self) -> sval::Result {
+        if self.is_candidate_value() {
+            self.is_key_matched = self.key.take().map(|key| key.is_match()).unwrap_or(false);
+        }
+
+        if let State::Selecting(_) = self.state {
+            self.stream.map_key_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn map_value_begin(&mut self) -> sval::Result {
+        if self.is_candidate_value() && self.is_key_matched {
+            self.is_key_matched = false;
+            self.advance();
+        }
+
+        if let State::Selecting(_) = self.state {
+            self.stream.map_value_begin()?;
+        }
+
+        Ok(())
+    }
+
+    fn map_value_end(&mut self) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.map_value_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn map_end(&mut self) -> sval::Result {
+        if self.end() {
+            self.stream.map_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        if self.begin(Kind::Container) {
+            self.stream.seq_begin(num_entries_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn seq_value_begin(&mut self) -> sval::Result {
+        self.index_value_begin();
+
+        if let State::Selecting(_) = self.state {
+            self.stream.seq_value_begin()?;
+        }
+
+        Ok(())
+    }
+
+    fn seq_value_end(&mut self) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.seq_value_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn seq_end(&mut self) -> sval::Result {
+        if self.end() {
+            self.stream.seq_end()?;
+        }
+
+        Ok(())
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.begin(Kind::Wrapper) {
+            self.stream.enum_begin(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.end() {
+            self.stream.enum_end(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.begin(Kind::Wrapper) {
+            self.stream.tagged_begin(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.end() {
+            self.stream.tagged_end(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tag(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.scalar() {
+            self.stream.tag(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        if self.begin(Kind::Container) {
+            self.stream
+                .record_begin(tag, label, index, num_entries_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        if self.is_candidate_value() {
+            if let Some(token) = self.token {
+                let mut key = KeyMatcher::new(token);
+                key.fragment(label.as_str());
+
+                if key.is_match() {
+                    self.advance();
+                }
+            }
+        }
+
+        if let State::Selecting(_) = self.state {
+            self.stream.record_value_begin(tag, label)?;
+        }
+
+        Ok(())
+    }
+
+    fn record_value_end(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.record_value_end(tag, label)?;
+        }
+
+        Ok(())
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.end() {
+            self.stream.record_end(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+        num_entries_hint: Option<usize>,
+    ) -> sval::Result {
+        if self.begin(Kind::Container) {
+            self.stream
+                .tuple_begin(tag, label, index, num_entries_hint)?;
+        }
+
+        Ok(())
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        self.index_value_begin();
+
+        if let State::Selecting(_) = self.state {
+            self.stream.tuple_value_begin(tag, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&sval::Tag>, index: &sval::Index) -> sval::Result {
+        if let State::Selecting(_) = self.state {
+            self.stream.tuple_value_end(tag, index)?;
+        }
+
+        Ok(())
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        index: Option<&sval::Index>,
+    ) -> sval::Result {
+        if self.end() {
+            self.stream.tuple_end(tag, label, index)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use core::fmt::{self, Write};
+
+/**
+Configuration for pretty-printed JSON.
+
+The default configuration indents with two spaces, uses `\n` for newlines,
+and writes a space after the `:` in map entries.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrettyConfig {
+    indent_width: usize,
+    indent_char: char,
+    newline: Newline,
+    space_after_colon: bool,
+}
+
+/**
+The style of newline to use in pretty-printed JSON.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Newline {
+    /**
+    A line feed `\n`.
+    */
+    Lf,
+    /**
+    A carriage return followed by a line feed `\r\n`.
+    */
+    CrLf,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        PrettyConfig::new()
+    }
+}
+
+impl PrettyConfig {
+    /**
+    Create a default configuration.
+    */
+    pub const fn new() -> Self {
+        PrettyConfig {
+            indent_width: 2,
+            indent_char: ' ',
+            newline: Newline::Lf,
+            space_after_colon: true,
+        }
+    }
+
+    /**
+    Set the number of indent characters to write for each level of nesting.
+    */
+    pub const fn with_indent_width(mut self, indent_width: usize) -> Self {
+        self.indent_width = indent_width;
+        self
+    }
+
+    /**
+    Set the character to indent with.
+
+    JSON only allows whitespace between tokens, so this should be one of
+    `' '`, `'\t'`, `'\n'` or `'\r'`.
+    */
+    pub const fn with_indent_char(mut self, indent_char: char) -> Self {
+        self.indent_char = indent_char;
+        self
+    }
+
+    /**
+    Set the style of newline to write.
+    */
+    pub const fn with_newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /**
+    Set whether to write a space after the `:` in map entries.
+    */
+    pub const fn with_space_after_colon(mut self, space_after_colon: bool) -> Self {
+        self.space_after_colon = space_after_colon;
+        self
+    }
+
+    pub(crate) fn write_newline(&self, mut out: impl Write, depth: usize) -> fmt::Result {
+        out.write_str(match self.newline {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        })?;
+
+        for _ in 0..depth * self.indent_width {
+            out.write_char(self.indent_char)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn write_colon(&self, mut out: impl Write) -> fmt::Result {
+        if self.space_after_colon {
+            out.write_str(": ")
+        } else {
+            out.write_char(':')
+        }
+    }
+}
@@ -0,0 +1,105 @@
+/*!
+State shared by the JSON readers.
+*/
+
+// serde_json uses the same limit
+const MAX_DEPTH: u32 = 128;
+
+pub(crate) const TOO_DEEP: &str = "at most 128 nested maps or sequences";
+
+/**
+What a reader expects to see next in its input.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum State {
+    Root,
+    Done,
+    MapEmpty,
+    MapKey,
+    MapKeyEnd,
+    MapValue,
+    MapValueEnd,
+    SeqEmpty,
+    SeqElem,
+    SeqElemEnd,
+}
+
+impl State {
+    pub(crate) fn expected(self) -> &'static str {
+        match self {
+            State::Root | State::MapValue | State::SeqElem => "a value",
+            State::Done => "the end of input",
+            State::MapEmpty => "a string key or `}`",
+            State::MapKey => "a string key",
+            State::MapKeyEnd => "`:`",
+            State::MapValueEnd => "`,` or `}`",
+            State::SeqEmpty => "a value or `]`",
+            State::SeqElemEnd => "`,` or `]`",
+        }
+    }
+}
+
+/**
+An error while reading.
+
+Unexpected input is reported along with the reader-specific location it was found at.
+*/
+pub(crate) enum ReadError {
+    Unexpected(usize, &'static str),
+    Stream(sval::Error),
+}
+
+impl From<sval::Error> for ReadError {
+    fn from(err: sval::Error) -> Self {
+        ReadError::Stream(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Container {
+    Map,
+    Seq,
+}
+
+// The kind of each container we're inside is tracked as a bit, so the stack
+// doesn't need any allocations. A set bit is a map and an unset bit is a seq.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stack {
+    bits: u128,
+    depth: u32,
+}
+
+impl Stack {
+    pub(crate) fn new() -> Self {
+        Stack { bits: 0, depth: 0 }
+    }
+
+    pub(crate) fn push(&mut self, container: Container) -> Option<()> {
+        if self.depth >= MAX_DEPTH {
+            return None;
+        }
+
+        match container {
+            Container::Map => self.bits |= 1 << self.depth,
+            Container::Seq => self.bits &= !(1 << self.depth),
+        }
+
+        self.depth += 1;
+
+        Some(())
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.depth -= 1;
+    }
+
+    pub(crate) fn top(&self) -> Option<Container> {
+        let depth = self.depth.checked_sub(1)?;
+
+        if self.bits & (1 << depth) != 0 {
+            Some(Container::Map)
+        } else {
+            Some(Container::Seq)
+        }
+    }
+}
@@ -1,45 +1,133 @@
 use core::fmt::{self, Write};
 
-use sval::Stream as _;
-
-use crate::{tags, Error};
+use crate::{
+    binary::{self, Encoder},
+    canonical::{self, Output},
+    path::Path,
+    tags, Binary, Config, Enums, Error, LargeIntegers, MapKeys, NonFinite, PrettyConfig,
+};
 
 pub fn stream_to_fmt(fmt: impl Write, v: impl sval::Value) -> Result<(), Error> {
-    let mut stream = Formatter::new(fmt);
+    stream(Formatter::new(fmt), v)
+}
+
+/**
+Stream a value as pretty-printed JSON.
+*/
+pub fn stream_to_fmt_pretty(
+    fmt: impl Write,
+    v: impl sval::Value,
+    config: PrettyConfig,
+) -> Result<(), Error> {
+    stream(Formatter::pretty(fmt, config), v)
+}
+
+/**
+Stream a value as JSON using the given configuration.
+*/
+pub fn stream_to_fmt_with(
+    fmt: impl Write,
+    v: impl sval::Value,
+    config: Config,
+) -> Result<(), Error> {
+    stream(Formatter::with_config(fmt, config), v)
+}
+
+/**
+Stream a value as canonical JSON, as described in RFC 8785.
+
+See [`Config::with_canonical`] for details.
+*/
+#[cfg(feature = "alloc")]
+pub fn stream_to_fmt_canonical(fmt: impl Write, v: impl sval::Value) -> Result<(), Error> {
+    stream(
+        Formatter::with_config(fmt, Config::new().with_canonical(true)),
+        v,
+    )
+}
+
+fn stream(mut stream: Formatter<impl Write>, v: impl sval::Value) -> Result<(), Error> {
+    // Sorting the members of objects requires buffering them
+    if cfg!(not(feature = "alloc")) && stream.config.canonical {
+        return Err(Error::new(
+            sval::Error::unsupported().with_message("canonical JSON requires the `alloc` feature"),
+        ));
+    }
 
     match v.stream(&mut stream) {
         Ok(()) => Ok(()),
-        Err(_) => Err(stream.err.unwrap_or_else(Error::generic)),
+        Err(e) => Err(Error::new(stream.path.attach(e))),
     }
 }
 
 pub(crate) struct Formatter<W> {
     is_internally_tagged: bool,
+    is_enum_content: bool,
+    enums: Enums,
     is_current_depth_empty: bool,
     is_text_quoted: bool,
     is_json_native: bool,
+    is_key: bool,
     text_handler: Option<TextHandler>,
-    err: Option<Error>,
-    out: W,
+    binary_tag: Option<Binary>,
+    binary_encoder: Option<Encoder>,
+    config: Config,
+    depth: usize,
+    path: Path,
+    #[cfg(feature = "alloc")]
+    objects: alloc::vec::Vec<canonical::Object>,
+    out: Output<W>,
+}
+
+/**
+The kind of a non-text value written as a map key.
+*/
+#[derive(Clone, Copy)]
+enum Key {
+    Null,
+    Bool,
+    Number,
+    Label,
+    Binary,
 }
 
 impl<W> Formatter<W> {
     pub fn new(out: W) -> Self {
+        Formatter::with_config(out, Config::new())
+    }
+
+    pub fn pretty(out: W, config: PrettyConfig) -> Self {
+        Formatter::with_config(out, Config::new().with_pretty(config))
+    }
+
+    pub fn with_config(out: W, mut config: Config) -> Self {
+        // Canonical JSON is always compact, minimally escaped, and never contains non-finite numbers
+        if config.canonical {
+            config.pretty = None;
+            config.non_finite = NonFinite::Error;
+            config.ascii_only = false;
+            config.html_safe = false;
+        }
+
         Formatter {
             is_internally_tagged: false,
+            is_enum_content: false,
+            enums: config.enums,
             is_current_depth_empty: true,
             is_text_quoted: true,
             is_json_native: false,
+            is_key: false,
             text_handler: None,
-            err: None,
-            out,
+            binary_tag: None,
+            binary_encoder: None,
+            config,
+            depth: 0,
+            path: Path::new(),
+            #[cfg(feature = "alloc")]
+            objects: alloc::vec::Vec::new(),
+            out: Output::new(out),
         }
     }
-
-    fn err(&mut self, e: Error) -> sval::Error {
-        self.err = Some(e);
-        sval::Error::new()
-    }
 }
 
 impl<'sval, W> sval::Stream<'sval> for Formatter<W>
@@ -47,42 +135,36 @@ where
     W: Write,
 {
     fn null(&mut self) -> sval::Result {
-        self.out
-            .write_str("null")
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Null)?;
+        self.out.write_str("null")?;
 
         Ok(())
     }
 
     fn bool(&mut self, v: bool) -> sval::Result {
-        self.out
-            .write_str(if v { "true" } else { "false" })
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Bool)?;
+        self.out.write_str(if v { "true" } else { "false" })?;
 
         Ok(())
     }
 
     fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+        self.enum_content()?;
+
         if self.is_text_quoted {
-            self.out
-                .write_char('"')
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+            self.out.write_char('"')?;
         }
 
         Ok(())
     }
 
     fn text_fragment_computed(&mut self, v: &str) -> sval::Result {
+        self.path.map_key_fragment(v);
+
         if let Some(ref mut handler) = self.text_handler {
-            handler
-                .text_fragment(v, &mut self.out)
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        } else if !self.is_json_native {
-            escape_str(v, &mut self.out).map_err(|e| self.err(Error::from_fmt(e)))?;
+            handler.text_fragment(v, &mut self.out)?;
         } else {
-            self.out
-                .write_str(v)
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+            self.escape(v, self.is_json_native)?;
         }
 
         Ok(())
@@ -90,186 +172,215 @@ where
 
     fn text_end(&mut self) -> sval::Result {
         if self.is_text_quoted {
-            self.out
-                .write_char('"')
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+            self.out.write_char('"')?;
         }
 
         Ok(())
     }
 
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> sval::Result {
+        self.key(Key::Binary)?;
+
+        match self.binary_tag.unwrap_or(self.config.binary) {
+            Binary::Array => self.seq_begin(num_bytes_hint),
+            binary => {
+                // Encoded binary is written as a string, just like text
+                if self.is_text_quoted {
+                    self.out.write_char('"')?;
+                }
+
+                self.binary_encoder = Some(Encoder::new(binary));
+
+                Ok(())
+            }
+        }
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> sval::Result {
+        if let Some(ref mut encoder) = self.binary_encoder {
+            encoder.fragment(fragment, &mut self.out)?;
+
+            Ok(())
+        } else {
+            for byte in fragment {
+                self.seq_value_begin()?;
+                self.u8(*byte)?;
+                self.seq_value_end()?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn binary_end(&mut self) -> sval::Result {
+        if let Some(encoder) = self.binary_encoder.take() {
+            encoder.end(&mut self.out)?;
+
+            if self.is_text_quoted {
+                self.out.write_char('"')?;
+            }
+
+            Ok(())
+        } else {
+            self.seq_end()
+        }
+    }
+
     fn u8(&mut self, v: u8) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn u16(&mut self, v: u16) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn u32(&mut self, v: u32) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn u64(&mut self, v: u64) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        Ok(())
+        self.integer(v, v <= MAX_SAFE_INTEGER)
     }
 
     fn u128(&mut self, v: u128) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        Ok(())
+        self.integer(v, v <= MAX_SAFE_INTEGER as u128)
     }
 
     fn i8(&mut self, v: i8) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn i16(&mut self, v: i16) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn i32(&mut self, v: i32) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.key(Key::Number)?;
+        self.out.write_str(itoa::Buffer::new().format(v))?;
 
         Ok(())
     }
 
     fn i64(&mut self, v: i64) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        Ok(())
+        self.integer(v, v.unsigned_abs() <= MAX_SAFE_INTEGER)
     }
 
     fn i128(&mut self, v: i128) -> sval::Result {
-        self.out
-            .write_str(itoa::Buffer::new().format(v))
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        Ok(())
+        self.integer(v, v.unsigned_abs() <= MAX_SAFE_INTEGER as u128)
     }
 
     fn f32(&mut self, v: f32) -> sval::Result {
-        if v.is_nan() || v.is_infinite() {
-            self.null()?;
+        self.key(Key::Number)?;
+
+        if !v.is_finite() {
+            return self.non_finite(v.is_nan(), v.is_sign_negative());
+        }
+
+        if self.config.canonical {
+            canonical::write_f64(&mut self.out, v as f64)?;
         } else {
-            self.out
-                .write_str(ryu::Buffer::new().format_finite(v))
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+            self.out.write_str(ryu::Buffer::new().format_finite(v))?;
         }
 
         Ok(())
     }
 
     fn f64(&mut self, v: f64) -> sval::Result {
-        if v.is_nan() || v.is_infinite() {
-            self.null()?;
+        self.key(Key::Number)?;
+
+        if !v.is_finite() {
+            return self.non_finite(v.is_nan(), v.is_sign_negative());
+        }
+
+        if self.config.canonical {
+            canonical::write_f64(&mut self.out, v)?;
         } else {
-            self.out
-                .write_str(ryu::Buffer::new().format_finite(v))
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+            self.out.write_str(ryu::Buffer::new().format_finite(v))?;
         }
 
         Ok(())
     }
 
-    fn map_begin(&mut self, _: Option<usize>) -> sval::Result {
-        if !self.is_text_quoted {
-            return Err(self.err(Error::invalid_key()));
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> sval::Result {
+        if let MapKeys::Pairs = self.config.map_keys {
+            return self.seq_begin(num_entries_hint);
         }
 
-        self.is_current_depth_empty = true;
-        self.out
-            .write_char('{')
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        Ok(())
+        self.object_begin()
     }
 
     fn map_key_begin(&mut self) -> sval::Result {
-        self.is_text_quoted = false;
-        self.is_internally_tagged = false;
-
-        if !self.is_current_depth_empty {
-            self.out
-                .write_str(",\"")
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        } else {
-            self.out
-                .write_char('"')
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
+        if let MapKeys::Pairs = self.config.map_keys {
+            self.seq_value_begin()?;
+            self.seq_begin(Some(2))?;
+            return self.seq_value_begin();
         }
 
-        Ok(())
+        self.object_key_begin()
     }
 
     fn map_key_end(&mut self) -> sval::Result {
-        self.out
-            .write_str("\":")
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
-
-        self.is_text_quoted = true;
+        if let MapKeys::Pairs = self.config.map_keys {
+            return self.seq_value_end();
+        }
 
-        Ok(())
+        self.object_key_end()
     }
 
     fn map_value_begin(&mut self) -> sval::Result {
+        if let MapKeys::Pairs = self.config.map_keys {
+            return self.seq_value_begin();
+        }
+
         Ok(())
     }
 
     fn map_value_end(&mut self) -> sval::Result {
-        self.is_current_depth_empty = false;
+        if let MapKeys::Pairs = self.config.map_keys {
+            self.seq_value_end()?;
+            self.seq_end()?;
+            return self.seq_value_end();
+        }
 
-        Ok(())
+        self.object_value_end()
     }
 
     fn map_end(&mut self) -> sval::Result {
-        self.out
-            .write_char('}')
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        if let MapKeys::Pairs = self.config.map_keys {
+            return self.seq_end();
+        }
 
-        Ok(())
+        self.object_end()
     }
 
     fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
-        if !self.is_text_quoted {
-            return Err(self.err(Error::invalid_key()));
+        self.enum_content()?;
+
+        if self.is_key {
+            return Err(Error::invalid_key());
         }
 
         self.is_current_depth_empty = true;
+        self.depth += 1;
+
+        self.out.write_char('[')?;
 
-        self.out
-            .write_char('[')
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.path.seq_begin();
 
         Ok(())
     }
@@ -277,11 +388,9 @@ where
     fn seq_value_begin(&mut self) -> sval::Result {
         self.is_internally_tagged = false;
 
-        if !self.is_current_depth_empty {
-            self.out
-                .write_char(',')
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        }
+        self.path.seq_value_begin();
+
+        self.entry_begin()?;
 
         Ok(())
     }
@@ -289,36 +398,45 @@ where
     fn seq_value_end(&mut self) -> sval::Result {
         self.is_current_depth_empty = false;
 
+        self.path.pop();
+
         Ok(())
     }
 
     fn seq_end(&mut self) -> sval::Result {
-        self.out
-            .write_char(']')
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.entries_end()?;
+        self.out.write_char(']')?;
+
+        self.path.seq_end();
 
         Ok(())
     }
 
     fn enum_begin(
         &mut self,
-        _: Option<&sval::Tag>,
+        tag: Option<&sval::Tag>,
         _: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
         self.is_internally_tagged = true;
+        self.enums = enums_from_tag(tag).unwrap_or(self.config.enums);
 
         Ok(())
     }
 
     fn enum_end(
         &mut self,
-        _: Option<&sval::Tag>,
+        tag: Option<&sval::Tag>,
         _: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
         if self.is_internally_tagged {
-            self.internally_tagged_map_end()?;
+            // Nested enums may have changed the representation, so get it again
+            match enums_from_tag(tag).unwrap_or(self.config.enums) {
+                Enums::External | Enums::Adjacent => self.internally_tagged_map_end()?,
+                // Internally tagged variants are closed by their own value
+                Enums::Internal | Enums::Untagged => (),
+            }
 
             self.is_internally_tagged = false;
         }
@@ -332,16 +450,30 @@ where
         label: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
+        if let Some(binary) = binary::from_tag(tag) {
+            self.binary_tag = Some(binary);
+        }
+
         match tag {
             Some(&tags::JSON_NATIVE) => {
                 self.is_json_native = true;
             }
             Some(&sval::tags::NUMBER) => {
+                self.key(Key::Number)?;
+
                 self.is_text_quoted = false;
 
+                // Canonical numbers are reformatted, so they need to be buffered
+                if self.config.canonical {
+                    #[cfg(feature = "alloc")]
+                    {
+                        self.text_handler = Some(TextHandler::Canonical(Default::default()));
+                    }
+                }
                 // If the number isn't guaranteed to be valid JSON then create an adapter
-                if !self.is_json_native {
-                    self.text_handler = Some(TextHandler::number());
+                else if !self.is_json_native {
+                    self.text_handler =
+                        Some(TextHandler::number(self.config.non_finite, !self.is_key));
                 }
             }
             _ => (),
@@ -358,6 +490,10 @@ where
         label: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
+        if binary::from_tag(tag).is_some() {
+            self.binary_tag = None;
+        }
+
         match tag {
             Some(&tags::JSON_NATIVE) => {
                 self.is_json_native = false;
@@ -365,12 +501,21 @@ where
             Some(&sval::tags::NUMBER) => {
                 self.is_text_quoted = true;
 
-                if !self.is_json_native {
-                    if let Some(TextHandler::Number(mut number)) = self.text_handler.take() {
-                        number
-                            .end(&mut self.out)
-                            .map_err(|e| self.err(Error::from_fmt(e)))?;
+                match self.text_handler.take() {
+                    Some(TextHandler::Number(mut number)) => number.end(&mut self.out)?,
+                    #[cfg(feature = "alloc")]
+                    Some(TextHandler::Canonical(number)) => {
+                        let v = number.parse::<f64>().map_err(|_| {
+                            sval::Error::invalid_value().with_message("invalid number")
+                        })?;
+
+                        if !v.is_finite() {
+                            return Err(Error::non_finite());
+                        }
+
+                        canonical::write_f64(&mut self.out, v)?;
                     }
+                    None => (),
                 }
             }
             _ => (),
@@ -385,17 +530,30 @@ where
         label: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
+        let is_variant = self.is_internally_tagged;
         self.is_internally_tagged = false;
 
         match tag {
             Some(&sval::tags::RUST_OPTION_NONE) => self.null(),
-            _ => {
-                if let Some(label) = label {
+            _ => match label {
+                Some(label) if is_variant => match self.enums {
+                    Enums::External => {
+                        self.key(Key::Label)?;
+                        self.value(label.as_str())
+                    }
+                    Enums::Internal | Enums::Adjacent => {
+                        self.object_begin()?;
+                        self.enum_tag_entry(label)?;
+                        self.object_end()
+                    }
+                    Enums::Untagged => self.null(),
+                },
+                Some(label) => {
+                    self.key(Key::Label)?;
                     self.value(label.as_str())
-                } else {
-                    self.null()
                 }
-            }
+                None => self.null(),
+            },
         }
     }
 
@@ -404,39 +562,31 @@ where
         _: Option<&sval::Tag>,
         label: Option<&sval::Label>,
         _: Option<&sval::Index>,
-        num_entries_hint: Option<usize>,
+        _: Option<usize>,
     ) -> sval::Result {
         self.internally_tagged_begin(label)?;
-        self.map_begin(num_entries_hint)
+        self.object_begin()
     }
 
     fn record_value_begin(&mut self, tag: Option<&sval::Tag>, label: &sval::Label) -> sval::Result {
         self.is_internally_tagged = false;
 
-        if !self.is_current_depth_empty {
-            self.out
-                .write_str(",\"")
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        } else {
-            self.out
-                .write_char('"')
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        }
+        self.object_entry_begin()?;
+        self.out.write_char('"')?;
 
         // If the field is JSON native then it doesn't require escaping
-        if let Some(&tags::JSON_NATIVE) = tag {
-            self.out
-                .write_str(label.as_str())
-                .map_err(|e| self.err(Error::from_fmt(e)))?;
-        } else {
-            escape_str(label.as_str(), &mut self.out).map_err(|e| self.err(Error::from_fmt(e)))?;
-        }
+        self.escape(label.as_str(), tag == Some(&tags::JSON_NATIVE))?;
+
+        self.out.write_char('"')?;
+        self.colon()?;
 
-        self.out
-            .write_str("\":")
-            .map_err(|e| self.err(Error::from_fmt(e)))?;
+        self.path.record_value_begin(label);
 
-        self.map_value_begin()
+        Ok(())
+    }
+
+    fn record_value_end(&mut self, _: Option<&sval::Tag>, _: &sval::Label) -> sval::Result {
+        self.object_value_end()
     }
 
     fn record_end(
@@ -445,7 +595,7 @@ where
         label: Option<&sval::Label>,
         _: Option<&sval::Index>,
     ) -> sval::Result {
-        self.map_end()?;
+        self.object_end()?;
         self.internally_tagged_end(label)
     }
 
@@ -475,14 +625,64 @@ impl<'sval, W> Formatter<W>
 where
     W: Write,
 {
+    fn entry_begin(&mut self) -> sval::Result {
+        if !self.is_current_depth_empty {
+            self.out.write_char(',')?;
+        }
+
+        if let Some(ref pretty) = self.config.pretty {
+            pretty.write_newline(&mut self.out, self.depth)?;
+        }
+
+        Ok(())
+    }
+
+    fn entries_end(&mut self) -> sval::Result {
+        self.depth -= 1;
+
+        // Empty maps and seqs are written on a single line
+        if let Some(ref pretty) = self.config.pretty {
+            if !self.is_current_depth_empty {
+                pretty.write_newline(&mut self.out, self.depth)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn colon(&mut self) -> sval::Result {
+        if let Some(ref pretty) = self.config.pretty {
+            pretty.write_colon(&mut self.out)?;
+        } else {
+            self.out.write_char(':')?;
+        }
+
+        Ok(())
+    }
+
     fn internally_tagged_begin(&mut self, label: Option<&sval::Label>) -> sval::Result {
-        // If there's a label then begin a map, using the label as the key
+        // If there's a label then begin a map, according to the enum's representation
         if self.is_internally_tagged {
+            self.is_internally_tagged = false;
+
             if let Some(label) = label {
-                self.internally_tagged_map_begin(label)?;
+                match self.enums {
+                    Enums::External => self.internally_tagged_map_begin(label)?,
+                    Enums::Internal => {
+                        self.object_begin()?;
+                        self.enum_tag_entry(label)?;
+
+                        // The variant's value is written into the same object
+                        self.is_enum_content = true;
+                    }
+                    Enums::Adjacent => {
+                        self.object_begin()?;
+                        self.enum_tag_entry(label)?;
+                        self.object_key(self.config.enum_content_field)?;
+                    }
+                    Enums::Untagged => (),
+                }
             }
-
-            self.is_internally_tagged = false;
         }
 
         Ok(())
@@ -497,25 +697,225 @@ where
     }
 
     fn internally_tagged_map_begin(&mut self, label: &sval::Label) -> sval::Result {
-        self.map_begin(Some(1))?;
+        self.object_begin()?;
+        self.object_key(label.as_str())
+    }
+
+    /**
+    Write the field holding the label of an internally or adjacently tagged variant.
+    */
+    fn enum_tag_entry(&mut self, label: &sval::Label) -> sval::Result {
+        self.object_key(self.config.enum_tag_field)?;
+
+        self.out.write_char('"')?;
+        self.escape(label.as_str(), false)?;
+        self.out.write_char('"')?;
 
-        self.map_key_begin()?;
-        escape_str(label.as_str(), &mut self.out).map_err(|e| self.err(Error::from_fmt(e)))?;
-        self.map_key_end()?;
+        self.object_value_end()
+    }
 
-        self.map_value_begin()
+    /**
+    Fail if the value of an internally tagged variant isn't a record or map.
+    */
+    fn enum_content(&self) -> sval::Result {
+        if self.is_enum_content {
+            Err(sval::Error::unsupported()
+                .with_message("internally tagged variants must contain a record or map"))
+        } else {
+            Ok(())
+        }
     }
 
     fn internally_tagged_map_end(&mut self) -> sval::Result {
-        self.map_value_end()?;
-        self.map_end()?;
+        self.object_value_end()?;
+        self.object_end()?;
+
+        Ok(())
+    }
+
+    fn object_begin(&mut self) -> sval::Result {
+        // The object of an internally tagged variant is already open
+        if self.is_enum_content {
+            self.is_enum_content = false;
+            return Ok(());
+        }
+
+        if self.is_key {
+            return Err(Error::invalid_key());
+        }
+
+        self.is_current_depth_empty = true;
+        self.depth += 1;
+        self.out.write_char('{')?;
+
+        #[cfg(feature = "alloc")]
+        if self.config.canonical {
+            self.objects.push(Default::default());
+        }
+
+        Ok(())
+    }
+
+    fn object_entry_begin(&mut self) -> sval::Result {
+        // Canonical entries are buffered and separated once they're sorted
+        if self.config.canonical {
+            #[cfg(feature = "alloc")]
+            self.out.entry_begin();
+
+            Ok(())
+        } else {
+            self.entry_begin()
+        }
+    }
+
+    fn object_key_begin(&mut self) -> sval::Result {
+        self.is_key = true;
+        self.is_text_quoted = false;
+        self.is_internally_tagged = false;
+
+        self.path.map_key_begin();
+
+        self.object_entry_begin()?;
+        self.out.write_char('"')?;
+
+        Ok(())
+    }
+
+    fn object_key(&mut self, key: &str) -> sval::Result {
+        self.object_key_begin()?;
+        self.path.map_key_fragment(key);
+        self.escape(key, false)?;
+        self.object_key_end()
+    }
+
+    fn object_key_end(&mut self) -> sval::Result {
+        self.out.write_char('"')?;
+        self.colon()?;
+
+        self.is_key = false;
+        self.is_text_quoted = true;
+
+        self.path.map_key_end();
+
+        Ok(())
+    }
+
+    fn object_value_end(&mut self) -> sval::Result {
+        self.is_current_depth_empty = false;
+
+        #[cfg(feature = "alloc")]
+        if self.config.canonical {
+            let object = self.objects.last_mut().expect("missing object");
+            self.out.entry_end(object)?;
+        }
+
+        self.path.pop();
+
+        Ok(())
+    }
+
+    fn object_end(&mut self) -> sval::Result {
+        #[cfg(feature = "alloc")]
+        if self.config.canonical {
+            let object = self.objects.pop().expect("missing object");
+            self.out.entries(object)?;
+        }
+
+        self.entries_end()?;
+        self.out.write_char('}')?;
+
+        Ok(())
+    }
+
+    /**
+    Write a fragment of text, escaping it according to the configuration.
+
+    Text that's JSON native is already escaped, but may still need
+    any additional escaping for non-ASCII or HTML characters.
+    */
+    fn escape(&mut self, v: &str, is_json_native: bool) -> fmt::Result {
+        match (self.config.ascii_only, self.config.html_safe) {
+            (false, false) if is_json_native => self.out.write_str(v),
+            (false, false) => escape_str(v, &mut self.out),
+            (ascii_only, html_safe) => {
+                escape_str_extended(v, &mut self.out, !is_json_native, ascii_only, html_safe)
+            }
+        }
+    }
+
+    fn integer(&mut self, v: impl itoa::Integer, is_safe: bool) -> sval::Result {
+        self.key(Key::Number)?;
+
+        // Keys are already quoted
+        let quote =
+            !is_safe && self.is_text_quoted && self.config.large_integers == LargeIntegers::String;
+
+        if quote {
+            self.out.write_char('"')?;
+        }
+
+        self.out.write_str(itoa::Buffer::new().format(v))?;
+
+        if quote {
+            self.out.write_char('"')?;
+        }
 
         Ok(())
     }
+
+    fn non_finite(&mut self, is_nan: bool, is_negative: bool) -> sval::Result {
+        match self.config.non_finite {
+            NonFinite::Null => self.out.write_str("null")?,
+            NonFinite::Error => return Err(Error::non_finite()),
+            NonFinite::String => {
+                write_non_finite_str(&mut self.out, is_nan, is_negative, self.is_text_quoted)?
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+    Check whether a non-text value can be written as a map key.
+
+    Text keys are always allowed, so this only needs to be called for other values.
+    */
+    fn key(&mut self, key: Key) -> sval::Result {
+        self.enum_content()?;
+
+        if !self.is_key {
+            return Ok(());
+        }
+
+        if matches!(
+            (self.config.map_keys, key),
+            (MapKeys::Labels, Key::Label)
+                | (
+                    MapKeys::Stringify,
+                    Key::Label | Key::Bool | Key::Number | Key::Binary
+                )
+        ) {
+            Ok(())
+        } else {
+            Err(Error::invalid_key())
+        }
+    }
+}
+
+pub(crate) fn enums_from_tag(tag: Option<&sval::Tag>) -> Option<Enums> {
+    match tag {
+        Some(&tags::EXTERNALLY_TAGGED) => Some(Enums::External),
+        Some(&tags::INTERNALLY_TAGGED) => Some(Enums::Internal),
+        Some(&tags::ADJACENTLY_TAGGED) => Some(Enums::Adjacent),
+        Some(&tags::UNTAGGED) => Some(Enums::Untagged),
+        _ => None,
+    }
 }
 
 enum TextHandler {
     Number(NumberTextHandler),
+    #[cfg(feature = "alloc")]
+    Canonical(alloc::string::String),
 }
 
 struct NumberTextHandler {
@@ -523,27 +923,36 @@ struct NumberTextHandler {
     sign_negative: bool,
     leading_zeroes: usize,
     is_nan_or_infinity: bool,
+    non_finite: NonFinite,
+    quote_non_finite: bool,
 }
 
 impl TextHandler {
-    fn number() -> Self {
+    fn number(non_finite: NonFinite, quote_non_finite: bool) -> Self {
         TextHandler::Number(NumberTextHandler {
             sign_negative: false,
             leading_zeroes: 0,
             at_start: true,
             is_nan_or_infinity: false,
+            non_finite,
+            quote_non_finite,
         })
     }
 
-    fn text_fragment(&mut self, v: &str, out: impl Write) -> fmt::Result {
+    fn text_fragment(&mut self, v: &str, out: impl Write) -> sval::Result {
         match self {
             TextHandler::Number(number) => number.text_fragment(v, out),
+            #[cfg(feature = "alloc")]
+            TextHandler::Canonical(number) => {
+                number.push_str(v);
+                Ok(())
+            }
         }
     }
 }
 
 impl NumberTextHandler {
-    fn text_fragment(&mut self, v: &str, mut out: impl Write) -> fmt::Result {
+    fn text_fragment(&mut self, v: &str, mut out: impl Write) -> sval::Result {
         if !self.is_nan_or_infinity {
             let mut range = 0..0;
 
@@ -590,12 +999,21 @@ impl NumberTextHandler {
                         range.start += 1;
                         range.end += 1;
                     }
-                    // `snan`, `nan`, `inf` in any casing should write `null`
+                    // `snan`, `nan`, `inf` in any casing are non-finite
                     b's' | b'n' | b'i' | b'S' | b'N' | b'I' => {
                         self.is_nan_or_infinity = true;
                         self.at_start = false;
 
-                        out.write_str("null")?;
+                        match self.non_finite {
+                            NonFinite::Null => out.write_str("null")?,
+                            NonFinite::Error => return Err(Error::non_finite()),
+                            NonFinite::String => write_non_finite_str(
+                                &mut out,
+                                !matches!(b, b'i' | b'I'),
+                                self.sign_negative,
+                                self.quote_non_finite,
+                            )?,
+                        }
 
                         range.start = 0;
                         range.end = 0;
@@ -621,6 +1039,34 @@ impl NumberTextHandler {
     }
 }
 
+fn write_non_finite_str(
+    mut out: impl Write,
+    is_nan: bool,
+    is_negative: bool,
+    quote: bool,
+) -> fmt::Result {
+    if quote {
+        out.write_char('"')?;
+    }
+
+    out.write_str(match (is_nan, is_negative) {
+        (true, _) => "NaN",
+        (false, false) => "Infinity",
+        (false, true) => "-Infinity",
+    })?;
+
+    if quote {
+        out.write_char('"')?;
+    }
+
+    Ok(())
+}
+
+/**
+The largest integer that can be represented exactly by a JavaScript number, `2^53 - 1`.
+*/
+const MAX_SAFE_INTEGER: u64 = 9_007_199_254_740_991;
+
 /*
 This `escape_str` implementation has been shamelessly lifted from dtolnay's `miniserde`:
 https://github.com/dtolnay/miniserde
@@ -668,6 +1114,67 @@ fn escape_str(value: &str, mut out: impl Write) -> Result<(), fmt::Error> {
     Ok(())
 }
 
+/**
+Escape a fragment of text with additional escapes for non-ASCII or HTML characters.
+
+Fragments are always complete UTF-8, so characters never need to be carried
+over from one fragment to the next.
+*/
+fn escape_str_extended(
+    value: &str,
+    mut out: impl Write,
+    minimal: bool,
+    ascii_only: bool,
+    html_safe: bool,
+) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in value.char_indices() {
+        let is_escaped = match c {
+            '<' | '>' | '&' => html_safe,
+            '\u{2028}' | '\u{2029}' => html_safe || ascii_only,
+            c if !c.is_ascii() => ascii_only,
+            c => minimal && ESCAPE[c as usize] != 0,
+        };
+
+        if !is_escaped {
+            continue;
+        }
+
+        if start < i {
+            out.write_str(&value[start..i])?;
+        }
+
+        if c.is_ascii() && ESCAPE[c as usize] != 0 {
+            escape_str(c.encode_utf8(&mut [0; 1]), &mut out)?;
+        } else {
+            let mut utf16 = [0; 2];
+            for unit in c.encode_utf16(&mut utf16) {
+                write_unicode_escape(&mut out, *unit)?;
+            }
+        }
+
+        start = i + c.len_utf8();
+    }
+
+    if start != value.len() {
+        out.write_str(&value[start..])?;
+    }
+
+    Ok(())
+}
+
+fn write_unicode_escape(mut out: impl Write, unit: u16) -> fmt::Result {
+    static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+
+    out.write_str("\\u")?;
+    for shift in [12, 8, 4, 0] {
+        out.write_char(HEX_DIGITS[((unit >> shift) & 0xF) as usize] as char)?;
+    }
+
+    Ok(())
+}
+
 const BB: u8 = b'b'; // \x08
 const TT: u8 = b't'; // \x09
 const NN: u8 = b'n'; // \x0A
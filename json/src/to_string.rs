@@ -1,4 +1,4 @@
-use crate::Error;
+use crate::{Config, Error, PrettyConfig};
 
 use alloc::string::String;
 
@@ -8,3 +8,35 @@ pub fn stream_to_string(v: impl sval::Value) -> Result<String, Error> {
 
     Ok(out)
 }
+
+/**
+Stream a value as pretty-printed JSON into a string.
+*/
+pub fn stream_to_string_pretty(v: impl sval::Value, config: PrettyConfig) -> Result<String, Error> {
+    let mut out = String::new();
+    crate::stream_to_fmt_pretty(&mut out, v, config)?;
+
+    Ok(out)
+}
+
+/**
+Stream a value as JSON into a string using the given configuration.
+*/
+pub fn stream_to_string_with(v: impl sval::Value, config: Config) -> Result<String, Error> {
+    let mut out = String::new();
+    crate::stream_to_fmt_with(&mut out, v, config)?;
+
+    Ok(out)
+}
+
+/**
+Stream a value as canonical JSON into a string.
+
+See [`Config::with_canonical`] for details.
+*/
+pub fn stream_to_string_canonical(v: impl sval::Value) -> Result<String, Error> {
+    let mut out = String::new();
+    crate::stream_to_fmt_canonical(&mut out, v)?;
+
+    Ok(out)
+}
@@ -1,4 +1,4 @@
-use crate::{Error, ErrorKind};
+use crate::{Config, Error, PrettyConfig};
 
 use std::{
     fmt,
@@ -6,43 +6,78 @@ use std::{
 };
 
 pub fn stream_to_writer(io: impl Write, v: impl sval::Value) -> Result<(), Error> {
-    struct IoToFmt<W> {
-        io: W,
-        err: Option<io::Error>,
-    }
+    stream_to_io(io, |fmt| crate::stream_to_fmt(fmt, v))
+}
+
+/**
+Stream a value as pretty-printed JSON into a writer.
+*/
+pub fn stream_to_writer_pretty(
+    io: impl Write,
+    v: impl sval::Value,
+    config: PrettyConfig,
+) -> Result<(), Error> {
+    stream_to_io(io, |fmt| crate::stream_to_fmt_pretty(fmt, v, config))
+}
+
+/**
+Stream a value as JSON into a writer using the given configuration.
+*/
+pub fn stream_to_writer_with(
+    io: impl Write,
+    v: impl sval::Value,
+    config: Config,
+) -> Result<(), Error> {
+    stream_to_io(io, |fmt| crate::stream_to_fmt_with(fmt, v, config))
+}
+
+/**
+Stream a value as canonical JSON into a writer.
 
-    impl<W: Write> fmt::Write for IoToFmt<W> {
-        fn write_str(&mut self, v: &str) -> fmt::Result {
-            let mut buf = v.as_bytes();
-
-            while buf.len() > 0 {
-                match self.io.write(buf) {
-                    Ok(0) => break,
-                    Ok(n) => {
-                        buf = &buf[n..];
-                    }
-                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                    Err(e) => {
-                        self.err = Some(e);
-                        return Err(fmt::Error);
-                    }
+See [`Config::with_canonical`] for details.
+*/
+pub fn stream_to_writer_canonical(io: impl Write, v: impl sval::Value) -> Result<(), Error> {
+    stream_to_io(io, |fmt| crate::stream_to_fmt_canonical(fmt, v))
+}
+
+pub(crate) struct IoToFmt<W> {
+    io: W,
+    err: Option<io::Error>,
+}
+
+impl<W: Write> fmt::Write for IoToFmt<W> {
+    fn write_str(&mut self, v: &str) -> fmt::Result {
+        let mut buf = v.as_bytes();
+
+        while buf.len() > 0 {
+            match self.io.write(buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    buf = &buf[n..];
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.err = Some(e);
+                    return Err(fmt::Error);
                 }
             }
-
-            Ok(())
         }
+
+        Ok(())
     }
+}
 
+pub(crate) fn stream_to_io<W: Write>(
+    io: W,
+    stream: impl FnOnce(&mut IoToFmt<W>) -> Result<(), Error>,
+) -> Result<(), Error> {
     let mut io = IoToFmt { io, err: None };
 
-    match crate::stream_to_fmt(&mut io, v) {
+    match stream(&mut io) {
         Ok(()) => Ok(()),
-        Err(mut e) => {
-            if let Some(io) = io.err {
-                e.kind = ErrorKind::IO(io);
-            }
-
-            Err(e)
-        }
+        Err(e) => match io.err {
+            Some(io) => Err(Error::from(io)),
+            None => Err(e),
+        },
     }
 }
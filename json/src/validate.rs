@@ -0,0 +1,1149 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{self, Write as _};
+
+use crate::{Error, JsonSliceReader};
+
+/**
+A compiled JSON Schema document.
+
+Schemas support a subset of draft 2020-12: the `type`, `properties`, `required`,
+`additionalProperties`, `items`, `enum`, `minimum`, `maximum`, `exclusiveMinimum`,
+`exclusiveMaximum`, and `pattern` keywords. Values in `enum` must be `null`,
+booleans, numbers, or strings. Annotations like `title` and `description` are
+ignored, but keywords that apply other schemas, like `$ref` or `anyOf`, fail to
+compile with an `Unsupported` error rather than being silently skipped.
+
+Values are validated against a schema in a single pass, by streaming them
+through a [`Validator`]. Values are checked against the shape they'd have when
+written as JSON with the default configuration, so enum variants are validated
+as objects with the variant label as their only key.
+*/
+pub struct Schema {
+    root: Node,
+    patterns: Option<Box<dyn PatternMatcher + Send + Sync>>,
+}
+
+/**
+A matcher for the regular expressions used by the `pattern` keyword.
+
+Regular expressions aren't supported by this crate directly, so a matcher needs
+to be given to [`Schema::with_pattern_matcher`] to validate schemas that use `pattern`.
+*/
+pub trait PatternMatcher {
+    /**
+    Check whether some text matches a pattern.
+
+    Patterns aren't implicitly anchored, so they can match anywhere in the text.
+    */
+    fn is_match(&self, pattern: &str, text: &str) -> bool;
+}
+
+impl<F: Fn(&str, &str) -> bool> PatternMatcher for F {
+    fn is_match(&self, pattern: &str, text: &str) -> bool {
+        (self)(pattern, text)
+    }
+}
+
+/**
+A single failure validating a value against a [`Schema`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    instance_path: String,
+    keyword: &'static str,
+    message: String,
+}
+
+impl ValidationError {
+    /**
+    Get the JSON Pointer to the value that failed, like `/items/3/price`.
+
+    The path of the root value is the empty string.
+    */
+    pub fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+
+    /**
+    Get the schema keyword that failed, like `type` or `required`.
+    */
+    pub fn keyword(&self) -> &'static str {
+        self.keyword
+    }
+
+    /**
+    Get a description of the failure.
+    */
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at `{}` (`{}`)",
+            self.message, self.instance_path, self.keyword
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+impl Schema {
+    /**
+    Compile a JSON Schema document.
+    */
+    pub fn compile(json: &str) -> Result<Self, Error> {
+        let mut builder = JsonBuilder::default();
+        JsonSliceReader::new(json.as_bytes()).stream_to_end(&mut builder)?;
+
+        Schema::compile_json(builder.root)
+    }
+
+    /**
+    Compile a JSON Schema document from an `sval::Value`.
+    */
+    pub fn compile_value(schema: impl sval::Value) -> Result<Self, Error> {
+        let mut builder = JsonBuilder::default();
+        schema.stream(&mut builder).map_err(Error::new)?;
+
+        Schema::compile_json(builder.root)
+    }
+
+    fn compile_json(json: Option<Json>) -> Result<Self, Error> {
+        let json = json.ok_or_else(|| {
+            Error::new(sval::Error::invalid_value().with_message("the schema is empty"))
+        })?;
+
+        Ok(Schema {
+            root: Node::compile(&json)?,
+            patterns: None,
+        })
+    }
+
+    /**
+    Use the given matcher for the `pattern` keyword.
+
+    If a schema uses `pattern` without a matcher, then validating any string
+    it applies to fails with a `pattern` error.
+    */
+    pub fn with_pattern_matcher(
+        mut self,
+        matcher: impl PatternMatcher + Send + Sync + 'static,
+    ) -> Self {
+        self.patterns = Some(Box::new(matcher));
+        self
+    }
+
+    /**
+    Validate a value against this schema.
+
+    If the value itself fails to stream then that's also returned as a failure,
+    with an empty keyword.
+    */
+    pub fn validate(&self, value: impl sval::Value) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator::new(self);
+
+        if let Err(err) = value.stream(&mut validator) {
+            validator.error("", format_args!("the value failed to stream: {}", err));
+        }
+
+        validator.finish()
+    }
+}
+
+impl fmt::Debug for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Schema")
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+/**
+A compiled schema, or subschema.
+*/
+#[derive(Debug, Default)]
+struct Node {
+    // A `false` schema, which doesn't allow any values
+    // A `true` schema is just a node without any keywords
+    is_false: bool,
+    types: Option<Types>,
+    properties: Vec<(String, Node)>,
+    required: Vec<String>,
+    additional_properties: Option<Box<Node>>,
+    items: Option<Box<Node>>,
+    values: Option<Vec<Literal>>,
+    minimum: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    pattern: Option<String>,
+}
+
+impl Node {
+    fn compile(schema: &Json) -> Result<Self, Error> {
+        let members = match schema {
+            Json::Bool(true) => return Ok(Node::default()),
+            Json::Bool(false) => {
+                return Ok(Node {
+                    is_false: true,
+                    ..Default::default()
+                })
+            }
+            Json::Object(members) => members,
+            _ => {
+                return Err(Error::new(
+                    sval::Error::invalid_value()
+                        .with_message("a JSON Schema must be an object or boolean"),
+                ))
+            }
+        };
+
+        let mut node = Node::default();
+
+        for (keyword, value) in members {
+            match (keyword.as_str(), value) {
+                ("type", Json::String(ty)) => {
+                    node.types = Some(Types::from_name(ty).ok_or_else(|| invalid("type"))?);
+                }
+                ("type", Json::Array(types)) => {
+                    let mut all = Types(0);
+
+                    for ty in types {
+                        match ty {
+                            Json::String(ty) => {
+                                all.0 |= Types::from_name(ty).ok_or_else(|| invalid("type"))?.0
+                            }
+                            _ => return Err(invalid("type")),
+                        }
+                    }
+
+                    node.types = Some(all);
+                }
+                ("properties", Json::Object(properties)) => {
+                    for (property, schema) in properties {
+                        node.properties
+                            .push((property.clone(), Node::compile(schema)?));
+                    }
+                }
+                ("required", Json::Array(required)) => {
+                    for property in required {
+                        match property {
+                            Json::String(property) => node.required.push(property.clone()),
+                            _ => return Err(invalid("required")),
+                        }
+                    }
+                }
+                ("additionalProperties", schema) => {
+                    node.additional_properties = Some(Box::new(Node::compile(schema)?));
+                }
+                ("items", schema) => {
+                    node.items = Some(Box::new(Node::compile(schema)?));
+                }
+                ("enum", Json::Array(values)) => {
+                    let mut literals = Vec::new();
+
+                    for value in values {
+                        literals.push(match value {
+                            Json::Null => Literal::Null,
+                            Json::Bool(v) => Literal::Bool(*v),
+                            Json::Number(v) => Literal::Number(*v),
+                            Json::String(v) => Literal::String(v.clone()),
+                            Json::Array(_) | Json::Object(_) => {
+                                return Err(Error::new(sval::Error::unsupported().with_message(
+                                    "only `null`, booleans, numbers, and strings are supported in `enum`",
+                                )))
+                            }
+                        });
+                    }
+
+                    node.values = Some(literals);
+                }
+                ("minimum", Json::Number(v)) => node.minimum = Some(*v),
+                ("maximum", Json::Number(v)) => node.maximum = Some(*v),
+                ("exclusiveMinimum", Json::Number(v)) => node.exclusive_minimum = Some(*v),
+                ("exclusiveMaximum", Json::Number(v)) => node.exclusive_maximum = Some(*v),
+                ("pattern", Json::String(pattern)) => node.pattern = Some(pattern.clone()),
+                (
+                    "type" | "properties" | "required" | "enum" | "minimum" | "maximum"
+                    | "exclusiveMinimum" | "exclusiveMaximum" | "pattern",
+                    _,
+                ) => return Err(invalid(keyword)),
+                (
+                    "$ref"
+                    | "$dynamicRef"
+                    | "allOf"
+                    | "anyOf"
+                    | "oneOf"
+                    | "not"
+                    | "if"
+                    | "then"
+                    | "else"
+                    | "dependentSchemas"
+                    | "prefixItems"
+                    | "contains"
+                    | "patternProperties"
+                    | "propertyNames"
+                    | "unevaluatedItems"
+                    | "unevaluatedProperties",
+                    _,
+                ) => {
+                    return Err(Error::new(sval::Error::unsupported().with_message(
+                        format_args!("unsupported JSON Schema keyword `{}`", keyword),
+                    )))
+                }
+                // Other keywords are annotations, or aren't checked
+                _ => (),
+            }
+        }
+
+        Ok(node)
+    }
+
+    fn property(&self, property: &str) -> Option<&Node> {
+        self.properties
+            .iter()
+            .find(|(candidate, _)| candidate == property)
+            .map(|(_, node)| node)
+    }
+}
+
+fn invalid(keyword: &str) -> Error {
+    Error::new(
+        sval::Error::invalid_value()
+            .with_message(format_args!("invalid JSON Schema keyword `{}`", keyword)),
+    )
+}
+
+/**
+A set of JSON types.
+*/
+#[derive(Debug, Clone, Copy)]
+struct Types(u8);
+
+impl Types {
+    const NAMES: [&'static str; 7] = [
+        "null", "boolean", "object", "array", "number", "integer", "string",
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Types::NAMES
+            .iter()
+            .position(|candidate| *candidate == name)
+            .map(|index| Types(1 << index))
+    }
+
+    fn contains(self, name: &str) -> bool {
+        Types::from_name(name)
+            .map(|ty| self.0 & ty.0 != 0)
+            .unwrap_or(false)
+    }
+
+    fn allows(self, instance: &Instance) -> bool {
+        match *instance {
+            // Integers are also numbers
+            Instance::Number(_, true) => self.contains("integer") || self.contains("number"),
+            ref instance => self.contains(instance.type_name()),
+        }
+    }
+}
+
+impl fmt::Display for Types {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+
+        for (index, name) in Types::NAMES.iter().enumerate() {
+            if self.0 & (1 << index) != 0 {
+                if !first {
+                    f.write_str(" or ")?;
+                }
+                first = false;
+
+                f.write_str(name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+A value allowed by the `enum` keyword.
+*/
+#[derive(Debug)]
+enum Literal {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Literal {
+    fn matches(&self, instance: &Instance) -> bool {
+        match (self, instance) {
+            (Literal::Null, Instance::Null) => true,
+            (Literal::Bool(a), Instance::Bool(b)) => a == b,
+            (Literal::Number(a), Instance::Number(b, _)) => a == b,
+            (Literal::String(a), Instance::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/**
+A value being validated.
+*/
+enum Instance<'a> {
+    Null,
+    Bool(bool),
+    // A number, and whether it's an integer
+    Number(f64, bool),
+    String(&'a str),
+    Object,
+    Array,
+}
+
+impl<'a> Instance<'a> {
+    fn number(v: f64) -> Self {
+        // Every float beyond 2^53 is an integer, below it the value survives truncation
+        const EXACT: f64 = 9007199254740992.0;
+
+        let is_integer = v.is_finite() && (v >= EXACT || v <= -EXACT || v == (v as i64) as f64);
+
+        Instance::Number(v, is_integer)
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Instance::Null => "null",
+            Instance::Bool(_) => "boolean",
+            Instance::Number(_, _) => "number",
+            Instance::String(_) => "string",
+            Instance::Object => "object",
+            Instance::Array => "array",
+        }
+    }
+}
+
+/**
+A stream that validates a value against a [`Schema`].
+
+The validator checks the value as it's streamed, without buffering anything
+but the text of map keys and strings. Validation doesn't stop at the first
+failure; all of them are collected and returned by [`Validator::finish`].
+*/
+pub struct Validator<'a> {
+    schema: &'a Schema,
+    // The schema for the next value, or `None` if it's unconstrained
+    next: Option<&'a Node>,
+    frames: Vec<Frame<'a>>,
+    path: Vec<Segment>,
+    key: Option<String>,
+    // The number of maps or sequences being skipped because they're part of a map key
+    skip: usize,
+    text: Option<(&'a Node, String)>,
+    is_number: bool,
+    // Whether the next tagged value is the variant of an enum
+    is_variant: bool,
+    // Whether each enum being streamed opened an object for its variant
+    variants: Vec<bool>,
+    errors: Vec<ValidationError>,
+}
+
+struct Frame<'a> {
+    node: Option<&'a Node>,
+    // Whether each of the node's required properties has been seen
+    seen: Vec<bool>,
+}
+
+enum Segment {
+    Key(String),
+    Index(usize),
+    // The index of the next value in a sequence
+    Seq(usize),
+}
+
+impl<'a> Validator<'a> {
+    /**
+    Create a validator for a schema.
+    */
+    pub fn new(schema: &'a Schema) -> Self {
+        Validator {
+            schema,
+            next: Some(&schema.root),
+            frames: Vec::new(),
+            path: Vec::new(),
+            key: None,
+            skip: 0,
+            text: None,
+            is_number: false,
+            is_variant: false,
+            variants: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /**
+    Whether the value streamed so far is valid.
+    */
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /**
+    Get the failures found so far.
+    */
+    pub fn errors(&self) -> &[ValidationError] {
+        &self.errors
+    }
+
+    /**
+    Complete validation, returning any failures.
+    */
+    pub fn finish(self) -> Result<(), Vec<ValidationError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn error(&mut self, keyword: &'static str, message: impl fmt::Display) {
+        let mut instance_path = String::new();
+
+        for segment in &self.path {
+            match segment {
+                Segment::Key(key) => {
+                    instance_path.push('/');
+
+                    for c in key.chars() {
+                        match c {
+                            '~' => instance_path.push_str("~0"),
+                            '/' => instance_path.push_str("~1"),
+                            c => instance_path.push(c),
+                        }
+                    }
+                }
+                Segment::Index(index) => {
+                    let _ = write!(instance_path, "/{}", index);
+                }
+                Segment::Seq(_) => (),
+            }
+        }
+
+        self.errors.push(ValidationError {
+            instance_path,
+            keyword,
+            message: message.to_string(),
+        });
+    }
+
+    fn check(&mut self, node: &'a Node, instance: Instance) {
+        if node.is_false {
+            self.error("false", "no value is allowed here");
+            return;
+        }
+
+        if let Some(types) = node.types {
+            if !types.allows(&instance) {
+                self.error(
+                    "type",
+                    format_args!("expected {}, found {}", types, instance.type_name()),
+                );
+            }
+        }
+
+        if let Some(ref values) = node.values {
+            if !values.iter().any(|value| value.matches(&instance)) {
+                self.error("enum", "the value isn't one of the allowed values");
+            }
+        }
+
+        match instance {
+            Instance::Number(v, _) => {
+                if let Some(minimum) = node.minimum {
+                    if v < minimum {
+                        self.error("minimum", format_args!("{} is less than {}", v, minimum));
+                    }
+                }
+
+                if let Some(maximum) = node.maximum {
+                    if v > maximum {
+                        self.error("maximum", format_args!("{} is greater than {}", v, maximum));
+                    }
+                }
+
+                if let Some(minimum) = node.exclusive_minimum {
+                    if v <= minimum {
+                        self.error(
+                            "exclusiveMinimum",
+                            format_args!("{} is not greater than {}", v, minimum),
+                        );
+                    }
+                }
+
+                if let Some(maximum) = node.exclusive_maximum {
+                    if v >= maximum {
+                        self.error(
+                            "exclusiveMaximum",
+                            format_args!("{} is not less than {}", v, maximum),
+                        );
+                    }
+                }
+            }
+            Instance::String(v) => {
+                if let Some(ref pattern) = node.pattern {
+                    match self.schema.patterns {
+                        Some(ref patterns) => {
+                            if !patterns.is_match(pattern, v) {
+                                self.error(
+                                    "pattern",
+                                    format_args!("the value doesn't match `{}`", pattern),
+                                );
+                            }
+                        }
+                        None => self.error("pattern", "no pattern matcher is configured"),
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn scalar(&mut self, instance: Instance) {
+        if let Some(node) = self.next.take() {
+            self.check(node, instance);
+        }
+    }
+
+    /**
+    Append a scalar to the map key being streamed.
+
+    Returns `false` if there's no map key.
+    */
+    fn key_scalar(&mut self, v: impl fmt::Display) -> bool {
+        if self.skip > 0 {
+            return true;
+        }
+
+        match self.key {
+            Some(ref mut key) => {
+                let _ = write!(key, "{}", v);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn container_begin(&mut self, instance: Instance) {
+        let node = self.next.take();
+
+        // Only objects need to track their required properties
+        let seen = match (node, &instance) {
+            (Some(node), Instance::Object) => node.required.iter().map(|_| false).collect(),
+            _ => Vec::new(),
+        };
+
+        if let Some(node) = node {
+            self.check(node, instance);
+        }
+
+        self.frames.push(Frame { node, seen });
+    }
+
+    fn container_end(&mut self) {
+        if let Some(frame) = self.frames.pop() {
+            if let Some(node) = frame.node {
+                for (property, seen) in node.required.iter().zip(frame.seen) {
+                    if !seen {
+                        self.error(
+                            "required",
+                            format_args!("missing required property `{}`", property),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /**
+    Begin the value of an object property.
+    */
+    fn property_begin(&mut self, key: String) {
+        let node = match self.frames.last_mut() {
+            Some(Frame {
+                node: Some(node),
+                seen,
+            }) => {
+                for (property, seen) in node.required.iter().zip(seen.iter_mut()) {
+                    if *property == key {
+                        *seen = true;
+                    }
+                }
+
+                Some(*node)
+            }
+            _ => None,
+        };
+
+        let unexpected = node
+            .filter(|node| node.property(&key).is_none())
+            .and_then(|node| node.additional_properties.as_deref())
+            .map(|additional| additional.is_false)
+            .unwrap_or(false);
+
+        self.next = node.and_then(|node| {
+            node.property(&key)
+                .or(node.additional_properties.as_deref())
+        });
+
+        self.path.push(Segment::Key(key));
+
+        if unexpected {
+            self.error("additionalProperties", "the property isn't allowed");
+            self.next = None;
+        }
+    }
+}
+
+impl<'a, 'sval> sval::Stream<'sval> for Validator<'a> {
+    fn null(&mut self) -> sval::Result {
+        if !self.key_scalar("null") {
+            self.scalar(Instance::Null);
+        }
+
+        Ok(())
+    }
+
+    fn bool(&mut self, value: bool) -> sval::Result {
+        if !self.key_scalar(value) {
+            self.scalar(Instance::Bool(value));
+        }
+
+        Ok(())
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+        if self.key.is_none() && self.skip == 0 {
+            if let Some(node) = self.next.take() {
+                self.text = Some((node, String::new()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+        if !self.key_scalar(fragment) {
+            if let Some((_, ref mut text)) = self.text {
+                text.push_str(fragment);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn text_end(&mut self) -> sval::Result {
+        if let Some((node, text)) = self.text.take() {
+            if self.is_number {
+                match text.parse::<f64>() {
+                    Ok(v) => self.check(node, Instance::number(v)),
+                    Err(_) => self.error("type", "the value isn't a valid number"),
+                }
+            } else {
+                self.check(node, Instance::String(&text));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn i64(&mut self, value: i64) -> sval::Result {
+        if !self.key_scalar(value) {
+            self.scalar(Instance::Number(value as f64, true));
+        }
+
+        Ok(())
+    }
+
+    fn f64(&mut self, value: f64) -> sval::Result {
+        if !self.key_scalar(value) {
+            self.scalar(Instance::number(value));
+        }
+
+        Ok(())
+    }
+
+    fn map_begin(&mut self, _: Option<usize>) -> sval::Result {
+        if self.key.is_some() || self.skip > 0 {
+            self.skip += 1;
+        } else {
+            self.container_begin(Instance::Object);
+        }
+
+        Ok(())
+    }
+
+    fn map_key_begin(&mut self) -> sval::Result {
+        if self.skip == 0 {
+            self.is_variant = false;
+            self.key = Some(String::new());
+        }
+
+        Ok(())
+    }
+
+    fn map_key_end(&mut self) -> sval::Result {
+        Ok(())
+    }
+
+    fn map_value_begin(&mut self) -> sval::Result {
+        if self.skip == 0 {
+            self.is_variant = false;
+
+            let key = self.key.take().unwrap_or_default();
+            self.property_begin(key);
+        }
+
+        Ok(())
+    }
+
+    fn map_value_end(&mut self) -> sval::Result {
+        if self.skip == 0 {
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn map_end(&mut self) -> sval::Result {
+        if self.skip > 0 {
+            self.skip -= 1;
+        } else {
+            self.container_end();
+        }
+
+        Ok(())
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
+        if self.key.is_some() || self.skip > 0 {
+            self.skip += 1;
+        } else {
+            self.container_begin(Instance::Array);
+            self.path.push(Segment::Seq(0));
+        }
+
+        Ok(())
+    }
+
+    fn seq_value_begin(&mut self) -> sval::Result {
+        if self.skip == 0 {
+            self.is_variant = false;
+
+            let index = match self.path.last_mut() {
+                Some(Segment::Seq(next)) => {
+                    let index = *next;
+                    *next += 1;
+                    index
+                }
+                _ => 0,
+            };
+
+            self.next = self
+                .frames
+                .last()
+                .and_then(|frame| frame.node)
+                .and_then(|node| node.items.as_deref());
+            self.path.push(Segment::Index(index));
+        }
+
+        Ok(())
+    }
+
+    fn seq_value_end(&mut self) -> sval::Result {
+        if self.skip == 0 {
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+
+    fn seq_end(&mut self) -> sval::Result {
+        if self.skip > 0 {
+            self.skip -= 1;
+        } else {
+            self.path.pop();
+            self.container_end();
+        }
+
+        Ok(())
+    }
+
+    fn enum_begin(
+        &mut self,
+        _: Option<&sval::Tag>,
+        _: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.variants.push(false);
+        self.is_variant = self.skip == 0 && self.key.is_none();
+
+        Ok(())
+    }
+
+    fn enum_end(
+        &mut self,
+        _: Option<&sval::Tag>,
+        _: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.is_variant = false;
+
+        if let Some(true) = self.variants.pop() {
+            self.path.pop();
+            self.container_end();
+        }
+
+        Ok(())
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.is_number = true;
+        }
+
+        // Variants are written as an object with their label as the key
+        if self.is_variant {
+            self.is_variant = false;
+
+            if let Some(label) = label {
+                self.container_begin(Instance::Object);
+                self.property_begin(label.as_str().into());
+
+                if let Some(variant) = self.variants.last_mut() {
+                    *variant = true;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        _: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.is_number = false;
+        }
+
+        Ok(())
+    }
+
+    fn tag(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        label: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        self.is_variant = false;
+
+        match (tag, label) {
+            (Some(&sval::tags::RUST_OPTION_NONE), _) | (_, None) => self.null(),
+            (_, Some(label)) => {
+                if !self.key_scalar(label.as_str()) {
+                    self.scalar(Instance::String(label.as_str()));
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/**
+A JSON document, used to compile schemas.
+*/
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+#[derive(Default)]
+struct JsonBuilder {
+    stack: Vec<Partial>,
+    text: String,
+    is_key: bool,
+    is_number: bool,
+    root: Option<Json>,
+}
+
+enum Partial {
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>, Option<String>),
+}
+
+impl JsonBuilder {
+    fn push(&mut self, value: Json) -> sval::Result {
+        match (self.stack.last_mut(), value) {
+            (Some(Partial::Object(_, key)), Json::String(value)) if self.is_key => {
+                *key = Some(value);
+            }
+            (_, _) if self.is_key => {
+                return Err(sval::Error::unsupported().with_message("a map key must be a string"))
+            }
+            (Some(Partial::Array(values)), value) => values.push(value),
+            (Some(Partial::Object(members, key)), value) => {
+                let key = key.take().ok_or_else(sval::Error::invalid_protocol)?;
+                members.push((key, value));
+            }
+            (None, value) => self.root = Some(value),
+        }
+
+        Ok(())
+    }
+}
+
+impl<'sval> sval::Stream<'sval> for JsonBuilder {
+    fn null(&mut self) -> sval::Result {
+        self.push(Json::Null)
+    }
+
+    fn bool(&mut self, value: bool) -> sval::Result {
+        self.push(Json::Bool(value))
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+        self.text.clear();
+
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+        self.text.push_str(fragment);
+
+        Ok(())
+    }
+
+    fn text_end(&mut self) -> sval::Result {
+        let text = core::mem::take(&mut self.text);
+
+        if self.is_number && !self.is_key {
+            let number = text
+                .parse()
+                .map_err(|_| sval::Error::invalid_value().with_message("invalid number"))?;
+
+            self.push(Json::Number(number))
+        } else {
+            self.push(Json::String(text))
+        }
+    }
+
+    fn i64(&mut self, value: i64) -> sval::Result {
+        self.push(Json::Number(value as f64))
+    }
+
+    fn f64(&mut self, value: f64) -> sval::Result {
+        self.push(Json::Number(value))
+    }
+
+    fn map_begin(&mut self, _: Option<usize>) -> sval::Result {
+        self.stack.push(Partial::Object(Vec::new(), None));
+
+        Ok(())
+    }
+
+    fn map_key_begin(&mut self) -> sval::Result {
+        self.is_key = true;
+
+        Ok(())
+    }
+
+    fn map_key_end(&mut self) -> sval::Result {
+        self.is_key = false;
+
+        Ok(())
+    }
+
+    fn map_value_begin(&mut self) -> sval::Result {
+        Ok(())
+    }
+
+    fn map_value_end(&mut self) -> sval::Result {
+        Ok(())
+    }
+
+    fn map_end(&mut self) -> sval::Result {
+        match self.stack.pop() {
+            Some(Partial::Object(members, _)) => self.push(Json::Object(members)),
+            _ => Err(sval::Error::invalid_protocol()),
+        }
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
+        self.stack.push(Partial::Array(Vec::new()));
+
+        Ok(())
+    }
+
+    fn seq_value_begin(&mut self) -> sval::Result {
+        Ok(())
+    }
+
+    fn seq_value_end(&mut self) -> sval::Result {
+        Ok(())
+    }
+
+    fn seq_end(&mut self) -> sval::Result {
+        match self.stack.pop() {
+            Some(Partial::Array(values)) => self.push(Json::Array(values)),
+            _ => Err(sval::Error::invalid_protocol()),
+        }
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        _: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.is_number = true;
+        }
+
+        Ok(())
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&sval::Tag>,
+        _: Option<&sval::Label>,
+        _: Option<&sval::Index>,
+    ) -> sval::Result {
+        if let Some(&sval::tags::NUMBER) = tag {
+            self.is_number = false;
+        }
+
+        Ok(())
+    }
+}
@@ -11,6 +11,12 @@ fn assert_json(v: impl sval::Value + serde::Serialize) {
     let actual = format!("{}", sval_json::stream_to_string(&v).unwrap());
 
     assert_eq!(expected, actual);
+
+    let expected = serde_json::to_string_pretty(&v).unwrap();
+    let actual =
+        sval_json::stream_to_string_pretty(&v, sval_json::PrettyConfig::default()).unwrap();
+
+    assert_eq!(expected, actual);
 }
 
 #[derive(Value, Serialize)]
@@ -216,6 +222,539 @@ fn stream_exotic_unnamed_enum() {
     );
 }
 
+#[test]
+fn stream_conformance() {
+    for case in sval_test::conformance::Suite::new().cases() {
+        for json in [
+            sval_json::stream_to_string(case),
+            sval_json::stream_to_string_pretty(case, sval_json::PrettyConfig::default()),
+        ] {
+            let json = json.unwrap_or_else(|e| panic!("case `{}` failed: {}", case.name(), e));
+
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(&json) {
+                panic!(
+                    "case `{}` produced invalid JSON {}: {}",
+                    case.name(),
+                    json,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn stream_generated_round_trip() {
+    sval_test::generate::Generator::new(0)
+        .with_text_keys()
+        .check(512, |value| {
+            sval::validate(value).unwrap();
+
+            let json = sval_json::stream_to_string(value).unwrap();
+            let round_tripped = sval_json::stream_to_string(sval_json::from_str(&json)).unwrap();
+
+            assert_eq!(json, round_tripped);
+        });
+}
+
+#[test]
+fn stream_snapshot() {
+    sval_test::assert_snapshot(
+        &MapStruct {
+            field_0: 42,
+            field_1: true,
+            field_2: "a",
+        },
+        r#"
+        record_begin label="MapStruct" num_entries=3
+          record_value_begin label="field_0"
+            i32 42
+          record_value_end label="field_0"
+          record_value_begin label="field_1"
+            bool true
+          record_value_end label="field_1"
+          record_value_begin label="field_2"
+            text_begin num_bytes=1
+              text_fragment "a"
+            text_end
+          record_value_end label="field_2"
+        record_end label="MapStruct"
+        "#,
+    );
+}
+
+#[test]
+fn stream_snapshot_replay() {
+    let recording: sval_test::Recording = r#"
+        # An enum variant with a fragmented field
+        enum_begin label="Enum"
+          record_begin label="Variant" index=1
+            record_value_begin tag="svalnum" label="a"
+              tagged_begin tag="svalnum"
+                text_begin
+                  text_fragment "1."
+                  text_fragment_computed "5"
+                text_end
+              tagged_end tag="svalnum"
+            record_value_end tag="svalnum" label="a"
+            record_value_begin label="b"
+              binary_begin num_bytes=2
+                binary_fragment 0x01ff
+              binary_end
+            record_value_end label="b"
+          record_end label="Variant" index=1
+        enum_end label="Enum"
+        "#
+    .parse()
+    .unwrap();
+
+    sval::validate(&recording).unwrap();
+
+    assert_eq!(
+        "{\"Variant\":{\"a\":1.5,\"b\":[1,255]}}",
+        sval_json::stream_to_string(&recording).unwrap()
+    );
+
+    for invalid in [
+        "unknown_token",
+        "i32 a",
+        "text_fragment unquoted",
+        "text_fragment \"unterminated",
+        "binary_fragment 0x0",
+        "record_value_begin",
+        "map_begin num_entries=1 extra=1",
+    ] {
+        assert!(
+            invalid.parse::<sval_test::Recording>().is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn stream_snapshot_round_trip() {
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    assert_send_sync::<sval_test::Recording>();
+
+    sval_test::generate::check(256, |value| {
+        // Replaying a recording streams exactly the same tokens
+        let replayed = sval_test::Recording::record(value).unwrap();
+        assert_eq!(format!("{:?}", value), format!("{:?}", replayed));
+
+        let snapshot = value.to_string();
+        let parsed: sval_test::Recording = snapshot.parse().unwrap();
+
+        assert_eq!(snapshot, parsed.to_string());
+    });
+}
+
+#[test]
+fn stream_rename_all() {
+    #[derive(Value, Serialize)]
+    #[sval(rename_all = "camelCase")]
+    #[serde(rename_all = "camelCase")]
+    struct CamelCase {
+        field_a: i32,
+        field_b_c: i32,
+        #[sval(rename = "d_renamed")]
+        #[serde(rename = "d_renamed")]
+        field_d: i32,
+        r#type: i32,
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(rename_all = "kebab-case")]
+    #[serde(rename_all = "kebab-case")]
+    struct KebabCase {
+        field_a: i32,
+        field_b_c: i32,
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(rename_all = "SCREAMING_SNAKE_CASE")]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    struct ScreamingSnakeCase {
+        field_a: i32,
+        field_b_c: i32,
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(rename_all = "PascalCase")]
+    #[serde(rename_all = "PascalCase")]
+    struct PascalCase {
+        field_a: i32,
+        field_b_c: i32,
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(rename_all = "snake_case")]
+    #[serde(rename_all = "snake_case")]
+    enum SnakeCase {
+        VariantA,
+        VariantB(i32),
+        VariantC(i32, i32),
+        #[sval(rename_all = "camelCase")]
+        #[serde(rename_all = "camelCase")]
+        VariantD {
+            field_a: i32,
+        },
+    }
+
+    assert_json(CamelCase {
+        field_a: 1,
+        field_b_c: 2,
+        field_d: 3,
+        r#type: 4,
+    });
+
+    assert_json(KebabCase {
+        field_a: 1,
+        field_b_c: 2,
+    });
+
+    assert_json(ScreamingSnakeCase {
+        field_a: 1,
+        field_b_c: 2,
+    });
+
+    assert_json(PascalCase {
+        field_a: 1,
+        field_b_c: 2,
+    });
+
+    assert_json(SnakeCase::VariantA);
+    assert_json(SnakeCase::VariantB(1));
+    assert_json(SnakeCase::VariantC(1, 2));
+    assert_json(SnakeCase::VariantD { field_a: 1 });
+}
+
+#[test]
+fn stream_rename_all_acronyms() {
+    #[derive(Value)]
+    #[sval(rename_all = "kebab-case")]
+    #[allow(clippy::upper_case_acronyms)]
+    enum Enum {
+        HTTPServer,
+        HttpServer,
+        Server2,
+    }
+
+    for variant in [Enum::HTTPServer, Enum::HttpServer] {
+        assert_eq!(
+            "\"http-server\"",
+            sval_json::stream_to_string(variant).unwrap()
+        );
+    }
+
+    assert_eq!(
+        "\"server2\"",
+        sval_json::stream_to_string(Enum::Server2).unwrap()
+    );
+}
+
+#[test]
+fn stream_flatten() {
+    use std::collections::BTreeMap;
+
+    #[derive(Value, Serialize)]
+    struct Outer {
+        a: i32,
+        #[sval(flatten)]
+        #[serde(flatten)]
+        b: Inner,
+        #[sval(flatten)]
+        #[serde(flatten)]
+        c: BTreeMap<String, i32>,
+        #[sval(flatten)]
+        #[serde(flatten)]
+        d: Option<Nested>,
+    }
+
+    #[derive(Value, Serialize)]
+    struct Inner {
+        e: bool,
+        f: Vec<Inner>,
+    }
+
+    #[derive(Value, Serialize)]
+    struct Nested {
+        #[sval(flatten)]
+        #[serde(flatten)]
+        g: Inner2,
+    }
+
+    #[derive(Value, Serialize)]
+    struct Inner2 {
+        h: &'static str,
+    }
+
+    let mut c = BTreeMap::new();
+    c.insert("i".to_owned(), 1);
+    c.insert("j".to_owned(), 2);
+
+    for d in [
+        None,
+        Some(Nested {
+            g: Inner2 { h: "h" },
+        }),
+    ] {
+        assert_json(Outer {
+            a: 1,
+            b: Inner {
+                e: true,
+                f: vec![Inner {
+                    e: false,
+                    f: vec![],
+                }],
+            },
+            c: c.clone(),
+            d,
+        });
+    }
+
+    sval_test::assert_snapshot(
+        &Nested {
+            g: Inner2 { h: "h" },
+        },
+        r#"
+        record_begin label="Nested"
+          record_value_begin label="h"
+            text_begin num_bytes=1
+              text_fragment "h"
+            text_end
+          record_value_end label="h"
+        record_end label="Nested"
+        "#,
+    );
+}
+
+#[test]
+fn stream_transparent() {
+    #[derive(Value, Serialize)]
+    #[sval(transparent)]
+    #[serde(transparent)]
+    struct Newtype(i32);
+
+    #[derive(Value, Serialize)]
+    #[sval(transparent)]
+    #[serde(transparent)]
+    struct Named {
+        a: Vec<Newtype>,
+    }
+
+    #[derive(Value, Serialize)]
+    struct Record {
+        a: Newtype,
+        b: Named,
+    }
+
+    assert_json(Newtype(1));
+    assert_json(Named {
+        a: vec![Newtype(1), Newtype(2)],
+    });
+    assert_json(Record {
+        a: Newtype(1),
+        b: Named { a: vec![] },
+    });
+
+    sval_test::assert_snapshot(&Newtype(1), "i32 1");
+}
+
+#[test]
+fn stream_with() {
+    use std::time::Duration;
+
+    fn duration_millis<'sval, S: sval::Stream<'sval> + ?Sized>(
+        duration: &'sval Duration,
+        stream: &mut S,
+    ) -> sval::Result {
+        stream.u128(duration.as_millis())
+    }
+
+    fn serialize_duration_millis<S: serde::Serializer>(
+        duration: &Duration,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    #[derive(Value, Serialize)]
+    struct Record {
+        #[sval(stream_with = "duration_millis")]
+        #[serde(serialize_with = "serialize_duration_millis")]
+        a: Duration,
+        b: i32,
+    }
+
+    #[derive(Value, Serialize)]
+    struct Tuple(
+        #[sval(stream_with = "duration_millis")]
+        #[serde(serialize_with = "serialize_duration_millis")]
+        Duration,
+        i32,
+    );
+
+    #[derive(Value, Serialize)]
+    #[sval(transparent)]
+    #[serde(transparent)]
+    struct Transparent(
+        #[sval(stream_with = "duration_millis")]
+        #[serde(serialize_with = "serialize_duration_millis")]
+        Duration,
+    );
+
+    #[derive(Value, Serialize, Clone)]
+    #[sval(stream_with = "stream_container")]
+    #[serde(into = "Vec<i32>")]
+    struct Container {
+        a: i32,
+        b: i32,
+    }
+
+    impl From<Container> for Vec<i32> {
+        fn from(container: Container) -> Self {
+            vec![container.a, container.b]
+        }
+    }
+
+    fn stream_container<'sval, S: sval::Stream<'sval> + ?Sized>(
+        container: &'sval Container,
+        stream: &mut S,
+    ) -> sval::Result {
+        stream.seq_begin(Some(2))?;
+
+        for value in [&container.a, &container.b] {
+            stream.seq_value_begin()?;
+            stream.value(value)?;
+            stream.seq_value_end()?;
+        }
+
+        stream.seq_end()
+    }
+
+    assert_json(Record {
+        a: Duration::from_secs(1),
+        b: 2,
+    });
+    assert_json(Tuple(Duration::from_secs(1), 2));
+    assert_json(Transparent(Duration::from_millis(5)));
+    assert_json(Container { a: 1, b: 2 });
+}
+
+#[test]
+fn stream_skip() {
+    struct NotValue;
+
+    #[derive(Value, Serialize)]
+    struct Record {
+        a: i32,
+        #[sval(skip)]
+        #[serde(skip)]
+        #[allow(dead_code)]
+        b: NotValue,
+        #[sval(skip_if = "Option::is_none")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        c: Option<i32>,
+        #[sval(skip_if = "Vec::is_empty")]
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        d: Vec<i32>,
+    }
+
+    #[derive(Value, Serialize)]
+    enum Enum {
+        Record {
+            #[sval(skip)]
+            #[serde(skip)]
+            #[allow(dead_code)]
+            a: NotValue,
+            #[sval(skip_if = "Option::is_none")]
+            #[serde(skip_serializing_if = "Option::is_none")]
+            b: Option<bool>,
+        },
+    }
+
+    #[derive(Value)]
+    struct Static {
+        a: i32,
+        #[sval(skip)]
+        #[allow(dead_code)]
+        b: NotValue,
+    }
+
+    #[derive(Value)]
+    struct Tuple(
+        i32,
+        #[sval(skip)]
+        #[allow(dead_code)]
+        NotValue,
+        bool,
+    );
+
+    for c in [None, Some(1)] {
+        for d in [vec![], vec![1, 2]] {
+            assert_json(Record {
+                a: 1,
+                b: NotValue,
+                c,
+                d: d.clone(),
+            });
+        }
+    }
+
+    assert_json(Enum::Record {
+        a: NotValue,
+        b: None,
+    });
+    assert_json(Enum::Record {
+        a: NotValue,
+        b: Some(true),
+    });
+
+    // The number of entries is only known when no fields are conditionally skipped
+    sval_test::assert_snapshot(
+        &Record {
+            a: 1,
+            b: NotValue,
+            c: None,
+            d: vec![],
+        },
+        r#"
+        record_begin label="Record"
+          record_value_begin label="a"
+            i32 1
+          record_value_end label="a"
+        record_end label="Record"
+        "#,
+    );
+
+    sval_test::assert_snapshot(
+        &Static { a: 1, b: NotValue },
+        r#"
+        record_begin label="Static" num_entries=1
+          record_value_begin label="a"
+            i32 1
+          record_value_end label="a"
+        record_end label="Static"
+        "#,
+    );
+
+    sval_test::assert_snapshot(
+        &Tuple(1, NotValue, true),
+        r#"
+        tuple_begin label="Tuple" num_entries=2
+          tuple_value_begin index=0
+            i32 1
+          tuple_value_end index=0
+          tuple_value_begin index=1
+            bool true
+          tuple_value_end index=1
+        tuple_end label="Tuple"
+        "#,
+    );
+}
+
 #[test]
 fn stream_to_io() {
     let mut buf = Vec::new();
@@ -235,3 +774,2402 @@ fn stream_to_io() {
         String::from_utf8(buf).unwrap()
     );
 }
+
+#[test]
+fn stream_error_path() {
+    struct Fail;
+
+    impl sval::Value for Fail {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, _: &mut S) -> sval::Result {
+            Err(sval::Error::custom("failed to stream"))
+        }
+    }
+
+    #[derive(Value)]
+    struct Outer {
+        a: Vec<std::collections::BTreeMap<&'static str, Fail>>,
+    }
+
+    let err = sval_json::stream_to_string(Outer {
+        a: vec![Default::default(), {
+            let mut map = std::collections::BTreeMap::new();
+            map.insert("b", Fail);
+            map
+        }],
+    })
+    .unwrap_err();
+
+    assert_eq!(sval::ErrorKind::Custom, err.kind());
+    assert_eq!(
+        err.path(),
+        &[
+            sval::PathSegment::Label(sval::Label::new("a")),
+            sval::PathSegment::Index(sval::Index::new(1)),
+            sval::PathSegment::Key(sval::Label::new("b")),
+        ]
+    );
+    assert_eq!("failed to stream at `.a[1][\"b\"]`", err.to_string());
+}
+
+#[test]
+fn stream_error_invalid_key() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(vec![1], 2);
+
+    let err = sval_json::stream_to_string(map).unwrap_err();
+
+    assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+}
+
+#[derive(Value, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    A,
+    B,
+}
+
+#[test]
+fn stream_map_keys_stringify() {
+    use std::collections::BTreeMap;
+
+    assert_json(BTreeMap::from([(1u32, "a"), (2, "b")]));
+    assert_json(BTreeMap::from([(-1i64, "a"), (2, "b")]));
+    assert_json(BTreeMap::from([(false, "a"), (true, "b")]));
+    assert_json(BTreeMap::from([(Key::A, 1), (Key::B, 2)]));
+}
+
+#[test]
+fn stream_map_keys_policy() {
+    use std::collections::BTreeMap;
+
+    let text = sval_json::Config::new().with_map_keys(sval_json::MapKeys::Text);
+    let labels = sval_json::Config::new().with_map_keys(sval_json::MapKeys::Labels);
+    let stringify = sval_json::Config::new().with_map_keys(sval_json::MapKeys::Stringify);
+
+    // Keys are stringified by default, like `serde_json`
+    assert_eq!(stringify, sval_json::Config::new());
+
+    assert_eq!(
+        "{\"a\":1}",
+        sval_json::stream_to_string_with(BTreeMap::from([("a", 1)]), text).unwrap()
+    );
+    assert_eq!(
+        "{\"A\":1}",
+        sval_json::stream_to_string_with(BTreeMap::from([(Key::A, 1)]), labels).unwrap()
+    );
+
+    for config in [text, labels] {
+        let err = sval_json::stream_to_string_with(BTreeMap::from([(1, "a")]), config).unwrap_err();
+        assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+
+        let err =
+            sval_json::stream_to_string_with(BTreeMap::from([(true, "a")]), config).unwrap_err();
+        assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+    }
+
+    let err = sval_json::stream_to_string_with(BTreeMap::from([(Key::A, 1)]), text).unwrap_err();
+    assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+
+    // Binary keys are only allowed when they're stringified
+    struct BinaryKey;
+
+    impl sval::Value for BinaryKey {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            stream.map_begin(Some(1))?;
+            stream.map_key_begin()?;
+            stream.binary_begin(Some(2))?;
+            stream.binary_fragment(b"ab")?;
+            stream.binary_end()?;
+            stream.map_key_end()?;
+            stream.map_value_begin()?;
+            stream.i32(1)?;
+            stream.map_value_end()?;
+            stream.map_end()
+        }
+    }
+
+    for config in [text, labels] {
+        let err =
+            sval_json::stream_to_string_with(BinaryKey, config.with_binary(sval_json::Binary::Hex))
+                .unwrap_err();
+        assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+    }
+
+    assert_eq!(
+        "{\"6162\":1}",
+        sval_json::stream_to_string_with(BinaryKey, stringify.with_binary(sval_json::Binary::Hex))
+            .unwrap()
+    );
+    assert!(sval_json::stream_to_string_with(BinaryKey, stringify).is_err());
+}
+
+#[test]
+fn stream_map_keys_pairs() {
+    use std::collections::BTreeMap;
+
+    let pairs = sval_json::Config::new().with_map_keys(sval_json::MapKeys::Pairs);
+
+    assert_eq!(
+        "[[1,\"a\"],[2,\"b\"]]",
+        sval_json::stream_to_string_with(BTreeMap::from([(1, "a"), (2, "b")]), pairs).unwrap()
+    );
+    assert_eq!(
+        "[[[1,2],[[\"A\",true]]]]",
+        sval_json::stream_to_string_with(
+            BTreeMap::from([(vec![1, 2], BTreeMap::from([(Key::A, true)]))]),
+            pairs
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        "[]",
+        sval_json::stream_to_string_with(BTreeMap::<i32, i32>::new(), pairs).unwrap()
+    );
+
+    // Records are still written as objects
+    assert_eq!(
+        "[[1,{\"field_0\":42,\"field_1\":true,\"field_2\":\"Hello\"}]]",
+        sval_json::stream_to_string_with(
+            BTreeMap::from([(
+                1,
+                MapStruct {
+                    field_0: 42,
+                    field_1: true,
+                    field_2: "Hello",
+                }
+            )]),
+            pairs
+        )
+        .unwrap()
+    );
+
+    assert_eq!(
+        "[\n  [\n    1,\n    \"a\"\n  ]\n]",
+        sval_json::stream_to_string_with(
+            BTreeMap::from([(1, "a")]),
+            pairs.with_pretty(sval_json::PrettyConfig::new())
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn read_roundtrip() {
+    for json in [
+        sval_json::stream_to_string(MapStruct {
+            field_0: 42,
+            field_1: true,
+            field_2: "abc",
+        })
+        .unwrap(),
+        String::from(r#"[1,-2.5e10,0.25,null,true,false,"",{},[],{"a":[{"b":null}]}]"#),
+        String::from("42"),
+    ] {
+        assert_eq!(
+            json,
+            sval_json::stream_to_string(sval_json::from_str(&json)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn read_whitespace() {
+    let json = " {\n\t\"a\" : [ 1 , 2 ] ,\r\n \"b\" : null } ";
+
+    assert_eq!(
+        r#"{"a":[1,2],"b":null}"#,
+        sval_json::stream_to_string(sval_json::from_str(json)).unwrap()
+    );
+}
+
+#[test]
+fn read_escapes() {
+    for (json, expected) in [
+        (r#""a\"b\\c\/d""#, "a\"b\\c/d"),
+        (r#""\b\f\n\r\t""#, "\u{8}\u{c}\n\r\t"),
+        (r#""\u00e9\u4e2d""#, "é中"),
+        (r#""\ud83d\ude00!""#, "😀!"),
+        ("\"é中😀\"", "é中😀"),
+    ] {
+        assert_eq!(
+            expected,
+            sval::from_value::<String>(sval_json::from_str(json)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn read_from_value() {
+    use std::collections::BTreeMap;
+
+    let json = r#"{"a": [1, 2, 3], "b": [-1]}"#;
+
+    let value: BTreeMap<String, Vec<i64>> = sval::from_value(sval_json::from_str(json)).unwrap();
+
+    assert_eq!(vec![1, 2, 3], value["a"]);
+    assert_eq!(vec![-1], value["b"]);
+
+    let value: f64 = sval::from_value(sval_json::from_slice(b"1.5e3")).unwrap();
+
+    assert_eq!(1500.0, value);
+}
+
+#[test]
+fn read_from_value_derive() {
+    #[derive(Value, FromValue, Debug, PartialEq)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Value, FromValue, Debug, PartialEq)]
+    struct Shapes {
+        origin: Point,
+        shapes: Vec<Shape>,
+    }
+
+    #[derive(Value, FromValue, Debug, PartialEq)]
+    enum Shape {
+        Empty,
+        Dot(Point),
+        Line(Point, Point),
+        Circle { center: Point, radius: f64 },
+    }
+
+    assert_eq!(
+        Point { x: 1, y: 2 },
+        sval::from_value::<Point>(sval_json::from_str(r#"{"x":1,"y":2}"#)).unwrap()
+    );
+
+    // Keys with escapes are streamed in multiple fragments
+    assert_eq!(
+        Point { x: 1, y: 2 },
+        sval::from_value::<Point>(sval_json::from_str(r#"{"y":2,"\u0078":1}"#)).unwrap()
+    );
+
+    assert_eq!(
+        Shape::Empty,
+        sval::from_value::<Shape>(sval_json::from_str(r#"{"Empty":null}"#)).unwrap()
+    );
+
+    let expected = Shapes {
+        origin: Point { x: 0, y: 0 },
+        shapes: vec![
+            Shape::Empty,
+            Shape::Dot(Point { x: 1, y: 1 }),
+            Shape::Line(Point { x: 1, y: 2 }, Point { x: 3, y: 4 }),
+            Shape::Circle {
+                center: Point { x: -1, y: -1 },
+                radius: 2.5,
+            },
+        ],
+    };
+
+    let json = sval_json::stream_to_string(&expected).unwrap();
+
+    assert_eq!(
+        expected,
+        sval::from_value::<Shapes>(sval_json::from_str(&json)).unwrap()
+    );
+
+    for json in [
+        r#"{"x":1,"y":2,"z":3}"#,
+        r#"{"x":1}"#,
+        r#"{"xx":1,"y":2}"#,
+        r#"[1,2]"#,
+    ] {
+        assert!(sval::from_value::<Point>(sval_json::from_str(json)).is_err());
+    }
+
+    for json in [r#""Unknown""#, r#""Dot""#, r#"{"Unknown":null}"#] {
+        assert!(sval::from_value::<Shape>(sval_json::from_str(json)).is_err());
+    }
+
+    // Errors name the unexpected field or variant
+    for (json, expected) in [
+        (r#"{"x":1,"z":3}"#, "unknown field `z`"),
+        (r#"{"x":1,"xy\u007a":3}"#, "unknown field `xyz`"),
+    ] {
+        let err = sval::from_value::<Point>(sval_json::from_str(json)).unwrap_err();
+        assert_eq!(Some(expected), err.message());
+    }
+
+    for (json, expected) in [
+        (r#""Unknown""#, "unknown unit variant `Unknown`"),
+        (r#""Dot""#, "unknown unit variant `Dot`"),
+        (r#"{"Circ":null}"#, "unknown variant `Circ`"),
+    ] {
+        let err = sval::from_value::<Shape>(sval_json::from_str(json)).unwrap_err();
+        assert_eq!(Some(expected), err.message());
+    }
+}
+
+#[test]
+fn read_invalid() {
+    for (json, expected, line, column) in [
+        ("", "a value", 1, 1),
+        ("[1, 2", "`,` or `]`", 1, 6),
+        ("[1,]", "a value", 1, 4),
+        ("{\"a\" 1}", "`:`", 1, 6),
+        ("{\n  \"a\": 1,\n  2: 3\n}", "a string key", 3, 3),
+        ("{\"a\": 1 \"b\": 2}", "`,` or `}`", 1, 9),
+        ("[1] 2", "the end of input", 1, 5),
+        ("[1}", "`,` or `]`", 1, 3),
+        ("tru", "`true`", 1, 4),
+        ("nul", "`null`", 1, 4),
+        ("-", "a digit", 1, 2),
+        ("1.", "a digit", 1, 3),
+        ("1e+", "a digit", 1, 4),
+        ("01", "the end of input", 1, 2),
+        ("\"abc", "`\"`", 1, 5),
+        ("\"é\\x\"", "a valid escape sequence", 1, 4),
+        ("\"\\u00g0\"", "a hex digit", 1, 6),
+        ("\"\\ud83d\"", "a low surrogate", 1, 8),
+        ("\"\\ud83d\\u0041\"", "a low surrogate", 1, 8),
+        ("\"\\ude00\"", "a high surrogate", 1, 2),
+        ("\"a\nb\"", "an escaped control character", 1, 3),
+    ] {
+        let err = sval_json::JsonSliceReader::new(json.as_bytes())
+            .stream_to_end(&mut sval_buffer::ValueBuf::new())
+            .unwrap_err();
+
+        let position = err.position().unwrap();
+
+        assert_eq!(Some(expected), err.expected(), "{:?}", json);
+        assert_eq!(
+            (line, column),
+            (position.line(), position.column()),
+            "{:?}",
+            json
+        );
+        assert_eq!(sval::ErrorKind::InvalidValue, err.kind());
+
+        assert_eq!(
+            format!("expected {} at line {}, column {}", expected, line, column),
+            sval::Error::from(err).to_string()
+        );
+    }
+}
+
+#[test]
+fn read_invalid_utf8() {
+    let err = sval_json::JsonSliceReader::new(b"\"ab\xff\"")
+        .stream_to_end(&mut sval_buffer::ValueBuf::new())
+        .unwrap_err();
+
+    assert_eq!(Some("valid UTF-8"), err.expected());
+    assert_eq!(3, err.position().unwrap().offset());
+}
+
+#[test]
+fn read_max_depth() {
+    let json = "[".repeat(129);
+
+    let err = sval_json::JsonSliceReader::new(json.as_bytes())
+        .stream_to_end(&mut sval_buffer::ValueBuf::new())
+        .unwrap_err();
+
+    assert_eq!(128, err.position().unwrap().offset());
+}
+
+struct Chunked<'a> {
+    src: &'a [u8],
+    chunk: usize,
+}
+
+impl<'a> std::io::Read for Chunked<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.chunk.min(buf.len()).min(self.src.len());
+
+        buf[..len].copy_from_slice(&self.src[..len]);
+        self.src = &self.src[len..];
+
+        Ok(len)
+    }
+}
+
+fn read_io(json: &str, chunk: usize, capacity: usize) -> Result<String, sval_json::Error> {
+    let mut buf = sval_buffer::ValueBuf::new();
+
+    sval_json::JsonReader::with_capacity(
+        capacity,
+        Chunked {
+            src: json.as_bytes(),
+            chunk,
+        },
+    )
+    .stream_to_end(&mut buf)?;
+
+    sval_json::stream_to_string(&buf)
+}
+
+#[test]
+fn read_io_roundtrip() {
+    for json in [
+        r#"{"field_0":42,"field_1":true,"field_2":"abc"}"#,
+        r#"[1,-2.5e10,0.25,null,true,false,"",{},[],{"a":[{"b":null}]}]"#,
+        r#"{"é中😀":"\"\\\/\b\f\n\r\t\u00e9\ud83d\ude00é中😀"}"#,
+        "  [ 123456789012345678901234567890 , \"a long string without any escapes\" ]  ",
+        "42",
+    ] {
+        let expected = sval_json::stream_to_string(sval_json::from_str(json)).unwrap();
+
+        for chunk in [1, 2, 3, 5, 4096] {
+            for capacity in [0, 13, 4096] {
+                assert_eq!(
+                    expected,
+                    read_io(json, chunk, capacity).unwrap(),
+                    "{:?} (chunk: {}, capacity: {})",
+                    json,
+                    chunk,
+                    capacity
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn read_io_invalid() {
+    for (json, expected, line, column) in [
+        ("", "a value", 1, 1),
+        ("{\n  \"a\": 1,\n  2: 3\n}", "a string key", 3, 3),
+        ("[\"é\", 1}", "`,` or `]`", 1, 8),
+        ("tru", "`true`", 1, 4),
+        ("1.", "a digit", 1, 3),
+        ("\"abc", "`\"`", 1, 5),
+        ("\"\\ud83d\"", "a low surrogate", 1, 8),
+        ("\"\\ude00\"", "a high surrogate", 1, 2),
+    ] {
+        for chunk in [1, 4096] {
+            let err = read_io(json, chunk, 0).unwrap_err();
+            let position = err.position().unwrap();
+
+            assert_eq!(Some(expected), err.expected(), "{:?}", json);
+            assert_eq!(
+                (line, column),
+                (position.line(), position.column()),
+                "{:?}",
+                json
+            );
+        }
+    }
+
+    let err = read_io("\"ab\u{e9}", 1, 0).unwrap_err();
+    assert_eq!(Some("`\"`"), err.expected());
+
+    let mut buf = sval_buffer::ValueBuf::new();
+    let err = sval_json::JsonReader::new(&b"\"ab\xff\""[..])
+        .stream_to_end(&mut buf)
+        .unwrap_err();
+
+    assert_eq!(Some("valid UTF-8"), err.expected());
+    assert_eq!(3, err.position().unwrap().offset());
+}
+
+#[test]
+fn read_io_bounded() {
+    use std::io::Read as _;
+
+    // A stream that only tracks the largest text fragment it sees
+    #[derive(Default)]
+    struct Fragments {
+        len: usize,
+        max: usize,
+    }
+
+    impl<'sval> sval::Stream<'sval> for Fragments {
+        fn null(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn bool(&mut self, _: bool) -> sval::Result {
+            Ok(())
+        }
+
+        fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+            Ok(())
+        }
+
+        fn text_fragment_computed(&mut self, fragment: &str) -> sval::Result {
+            self.len += fragment.len();
+            self.max = self.max.max(fragment.len());
+
+            Ok(())
+        }
+
+        fn text_end(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn i64(&mut self, _: i64) -> sval::Result {
+            Ok(())
+        }
+
+        fn f64(&mut self, _: f64) -> sval::Result {
+            Ok(())
+        }
+
+        fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
+            Ok(())
+        }
+
+        fn seq_value_begin(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn seq_value_end(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn seq_end(&mut self) -> sval::Result {
+            Ok(())
+        }
+    }
+
+    let len = 1024 * 1024;
+
+    let json = (&b"[\""[..])
+        .chain(std::io::repeat(b'a').take(len as u64))
+        .chain(&b"\",1]"[..]);
+
+    let mut fragments = Fragments::default();
+    sval_json::from_reader(json)
+        .stream_to_end(&mut fragments)
+        .unwrap();
+
+    assert_eq!(len + 1, fragments.len);
+    assert!(fragments.max <= 8 * 1024);
+}
+
+#[test]
+fn stream_pretty_empty() {
+    assert_json(Vec::<i32>::new());
+    assert_json(vec![Vec::<i32>::new(), vec![1]]);
+    assert_json(std::collections::BTreeMap::<String, Vec<i32>>::new());
+}
+
+#[test]
+fn stream_pretty_config() {
+    let config = sval_json::PrettyConfig::new()
+        .with_indent_width(1)
+        .with_indent_char('\t')
+        .with_newline(sval_json::Newline::CrLf)
+        .with_space_after_colon(false);
+
+    let value = MapStruct {
+        field_0: 42,
+        field_1: true,
+        field_2: "Hello",
+    };
+
+    assert_eq!(
+        "{\r\n\t\"field_0\":42,\r\n\t\"field_1\":true,\r\n\t\"field_2\":\"Hello\"\r\n}",
+        sval_json::stream_to_string_pretty(&value, config).unwrap()
+    );
+
+    let mut out = Vec::new();
+    sval_json::stream_to_writer_pretty(&mut out, vec![vec![1, 2], vec![]], config).unwrap();
+
+    assert_eq!(
+        "[\r\n\t[\r\n\t\t1,\r\n\t\t2\r\n\t],\r\n\t[]\r\n]",
+        String::from_utf8(out).unwrap()
+    );
+}
+
+#[test]
+fn stream_ndjson() {
+    let values = [
+        MapStruct {
+            field_0: 1,
+            field_1: true,
+            field_2: "a\nb",
+        },
+        MapStruct {
+            field_0: 2,
+            field_1: false,
+            field_2: "c",
+        },
+    ];
+
+    let expected = "{\"field_0\":1,\"field_1\":true,\"field_2\":\"a\\nb\"}\n{\"field_0\":2,\"field_1\":false,\"field_2\":\"c\"}\n";
+
+    assert_eq!(
+        expected,
+        sval_json::stream_to_string_ndjson(&values).unwrap()
+    );
+
+    let mut out = Vec::new();
+    sval_json::stream_to_writer_ndjson(&mut out, &values).unwrap();
+
+    assert_eq!(expected, String::from_utf8(out).unwrap());
+}
+
+#[test]
+fn stream_ndjson_raw_newline() {
+    struct Native(&'static str);
+
+    impl sval::Value for Native {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            stream.tagged_begin(Some(&sval_json::tags::JSON_NATIVE), None, None)?;
+            stream.value(self.0)?;
+            stream.tagged_end(Some(&sval_json::tags::JSON_NATIVE), None, None)
+        }
+    }
+
+    let err =
+        sval_json::stream_to_string_ndjson([Native("a"), Native("b"), Native("c\nd")]).unwrap_err();
+
+    let position = err.position().unwrap();
+
+    assert_eq!(3, position.line());
+    assert_eq!(8, position.offset());
+    assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+}
+
+#[test]
+fn stream_ndjson_partial_line() {
+    enum Line {
+        Ok(i32),
+        Fail,
+    }
+
+    impl sval::Value for Line {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            match self {
+                Line::Ok(v) => stream.i32(*v),
+                // Fail partway through the value
+                Line::Fail => {
+                    stream.seq_begin(None)?;
+                    stream.seq_value_begin()?;
+                    stream.i32(1)?;
+                    stream.seq_value_end()?;
+
+                    sval::error()
+                }
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let err = sval_json::stream_to_fmt_ndjson(&mut out, [Line::Ok(1), Line::Fail, Line::Ok(3)])
+        .unwrap_err();
+
+    // Only the lines of values that succeeded are written
+    assert_eq!("1\n", out);
+    assert_eq!(2, err.position().unwrap().line());
+    assert_eq!(2, err.position().unwrap().offset());
+}
+
+#[test]
+fn read_ndjson() {
+    let json = "{\"a\":1}\n\n  [1, 2]\r\n\"b\"";
+
+    let lines = sval_json::from_str_ndjson(json)
+        .map(|line| (line.line(), sval_json::stream_to_string(line).unwrap()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec![
+            (1, String::from("{\"a\":1}")),
+            (3, String::from("[1,2]")),
+            (4, String::from("\"b\"")),
+        ],
+        lines
+    );
+
+    let mut lines = sval_json::from_slice_ndjson(b"1\n[1,\n2 3\n");
+
+    assert!(lines
+        .next()
+        .unwrap()
+        .reader()
+        .stream_to_end(&mut sval_buffer::ValueBuf::new())
+        .is_ok());
+
+    for (line, column) in [(2, 4), (3, 3)] {
+        let err = lines
+            .next()
+            .unwrap()
+            .reader()
+            .stream_to_end(&mut sval_buffer::ValueBuf::new())
+            .unwrap_err();
+
+        let position = err.position().unwrap();
+
+        assert_eq!((line, column), (position.line(), position.column()));
+    }
+
+    assert!(lines.next().is_none());
+}
+
+struct Bytes<'a> {
+    bytes: &'a [u8],
+    tag: Option<sval::Tag>,
+}
+
+impl<'a> sval::Value for Bytes<'a> {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        if let Some(ref tag) = self.tag {
+            stream.tagged_begin(Some(tag), None, None)?;
+        }
+
+        // Stream in small fragments to make sure encoding is incremental
+        stream.binary_begin(Some(self.bytes.len()))?;
+        for fragment in self.bytes.chunks(2) {
+            stream.binary_fragment_computed(fragment)?;
+        }
+        stream.binary_end()?;
+
+        if let Some(ref tag) = self.tag {
+            stream.tagged_end(Some(tag), None, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn stream_binary(bytes: &[u8], binary: sval_json::Binary) -> String {
+    sval_json::stream_to_string_with(
+        Bytes { bytes, tag: None },
+        sval_json::Config::new().with_binary(binary),
+    )
+    .unwrap()
+}
+
+#[test]
+fn stream_binary_encodings() {
+    use sval_json::Binary;
+
+    for (bytes, base64, base64_url, hex) in [
+        (&b""[..], "", "", ""),
+        (b"f", "Zg==", "Zg", "66"),
+        (b"fo", "Zm8=", "Zm8", "666f"),
+        (b"foo", "Zm9v", "Zm9v", "666f6f"),
+        (b"foob", "Zm9vYg==", "Zm9vYg", "666f6f62"),
+        (b"fooba", "Zm9vYmE=", "Zm9vYmE", "666f6f6261"),
+        (b"foobar", "Zm9vYmFy", "Zm9vYmFy", "666f6f626172"),
+        (&[0xfb, 0xff], "+/8=", "-_8", "fbff"),
+    ] {
+        assert_eq!(
+            format!("\"{}\"", base64),
+            stream_binary(bytes, Binary::Base64)
+        );
+        assert_eq!(
+            format!("\"{}\"", base64_url),
+            stream_binary(bytes, Binary::Base64Url)
+        );
+        assert_eq!(format!("\"{}\"", hex), stream_binary(bytes, Binary::Hex));
+    }
+
+    assert_eq!("[1,2,3]", stream_binary(&[1, 2, 3], Binary::Array));
+    assert_eq!("[]", stream_binary(&[], Binary::Array));
+}
+
+#[test]
+fn stream_binary_tagged() {
+    assert_eq!(
+        "[\"AQID\",\"010203\"]",
+        sval_json::stream_to_string((
+            Bytes {
+                bytes: &[1, 2, 3],
+                tag: Some(sval_json::tags::BASE64),
+            },
+            Bytes {
+                bytes: &[1, 2, 3],
+                tag: Some(sval_json::tags::HEX),
+            },
+        ))
+        .unwrap()
+    );
+}
+
+#[test]
+fn stream_binary_read() {
+    struct Read<'a>(&'a [u8]);
+
+    impl<'a> sval::Value for Read<'a> {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            sval::stream_read(stream, self.0).map_err(sval::Error::from)
+        }
+    }
+
+    let bytes = (0..=255).cycle().take(1000).collect::<Vec<u8>>();
+
+    let json = sval_json::stream_to_string_with(
+        Read(&bytes),
+        sval_json::Config::new().with_binary(sval_json::Binary::Base64),
+    )
+    .unwrap();
+
+    assert_eq!(
+        decode_binary(&json, sval_json::tags::BASE64).unwrap(),
+        bytes
+    );
+}
+
+/**
+Read a JSON string tagged with a binary encoding, and decode it.
+*/
+fn decode_binary(json: &str, tag: sval::Tag) -> sval::Result<Vec<u8>> {
+    struct Tagged<'a>(&'a sval_json::JsonSlice, sval::Tag);
+
+    impl<'a> sval::Value for Tagged<'a> {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            stream.tagged_begin(Some(&self.1), None, None)?;
+            stream.value(self.0)?;
+            stream.tagged_end(Some(&self.1), None, None)
+        }
+    }
+
+    #[derive(Default)]
+    struct Collect {
+        is_binary: bool,
+        bytes: Vec<u8>,
+    }
+
+    impl<'sval> sval::Stream<'sval> for Collect {
+        fn null(&mut self) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn bool(&mut self, _: bool) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn text_fragment_computed(&mut self, _: &str) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn text_end(&mut self) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn binary_begin(&mut self, _: Option<usize>) -> sval::Result {
+            self.is_binary = true;
+            Ok(())
+        }
+
+        fn binary_fragment_computed(&mut self, fragment: &[u8]) -> sval::Result {
+            self.bytes.extend_from_slice(fragment);
+            Ok(())
+        }
+
+        fn binary_end(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn i64(&mut self, _: i64) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn f64(&mut self, _: f64) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn seq_value_begin(&mut self) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn seq_value_end(&mut self) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+
+        fn seq_end(&mut self) -> sval::Result {
+            Err(sval::Error::unsupported())
+        }
+    }
+
+    let mut decode = sval_json::DecodeBinary::new(Collect::default());
+    sval::Value::stream(&Tagged(sval_json::from_str(json), tag), &mut decode)?;
+
+    let collect = decode.into_inner();
+    assert!(collect.is_binary);
+
+    Ok(collect.bytes)
+}
+
+#[test]
+fn read_binary_encodings() {
+    for (bytes, json, tag) in [
+        (&b""[..], "\"\"", sval_json::tags::BASE64),
+        (b"f", "\"Zg==\"", sval_json::tags::BASE64),
+        (b"f", "\"Zg\"", sval_json::tags::BASE64),
+        (b"foob", "\"Zm9vYg==\"", sval_json::tags::BASE64),
+        (b"fooba", "\"Zm9vYmE=\"", sval_json::tags::BASE64),
+        (b"foobar", "\"Zm9vYmFy\"", sval_json::tags::BASE64),
+        (&[0xfb, 0xff], "\"+/8=\"", sval_json::tags::BASE64),
+        (&[0xfb, 0xff], "\"-_8\"", sval_json::tags::BASE64_URL),
+        (&[0xfb, 0xff], "\"FBff\"", sval_json::tags::HEX),
+    ] {
+        assert_eq!(bytes, &*decode_binary(json, tag).unwrap(), "{}", json);
+    }
+
+    for (json, tag) in [
+        ("\"Z\"", sval_json::tags::BASE64),
+        ("\"Zg=\"", sval_json::tags::BASE64),
+        ("\"Zg===\"", sval_json::tags::BASE64),
+        ("\"Zg==Zg==\"", sval_json::tags::BASE64),
+        ("\"-_8\"", sval_json::tags::BASE64),
+        ("\"+/8\"", sval_json::tags::BASE64_URL),
+        ("\"abc\"", sval_json::tags::HEX),
+        ("\"xy\"", sval_json::tags::HEX),
+    ] {
+        assert_eq!(
+            sval::ErrorKind::InvalidValue,
+            decode_binary(json, tag).unwrap_err().kind(),
+            "{}",
+            json
+        );
+    }
+}
+
+#[test]
+fn stream_canonical_rfc8785() {
+    let json = r#"{
+        "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+        "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+        "literals": [null, true, false]
+    }"#;
+
+    assert_eq!(
+        r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#,
+        sval_json::stream_to_string_canonical(sval_json::from_str(json)).unwrap()
+    );
+}
+
+#[test]
+fn stream_canonical_sorting() {
+    let json = r#"{
+        "\u20ac": "Euro Sign",
+        "\r": "Carriage Return",
+        "\ufb33": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\ud83d\ude00": "Emoji: Grinning Face",
+        "\u0080": "Control",
+        "\u00f6": "Latin Small Letter O With Diaeresis"
+    }"#;
+
+    assert_eq!(
+        "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\"ö\":\"Latin Small Letter O With Diaeresis\",\"€\":\"Euro Sign\",\"😀\":\"Emoji: Grinning Face\",\"דּ\":\"Hebrew Letter Dalet With Dagesh\"}",
+        sval_json::stream_to_string_canonical(sval_json::from_str(json)).unwrap()
+    );
+
+    // Records and maps are sorted at every level
+    assert_eq!(
+        r#"[{"field_0":42,"field_1":true,"field_2":"Hello"},{"1":{"a":1,"b":2},"10":{},"2":[]}]"#,
+        sval_json::stream_to_string_canonical(sval_json::from_str(
+            r#"[{"field_2":"Hello","field_0":42,"field_1":true},{"2":[],"10":{},"1":{"b":2,"a":1}}]"#
+        ))
+        .unwrap()
+    );
+
+    assert_eq!(
+        r#"{"MapStruct":{"field_0":42,"field_1":true,"field_2":"Hello"}}"#,
+        sval_json::stream_to_string_canonical(Enum::MapStruct {
+            field_0: 42,
+            field_1: true,
+            field_2: "Hello",
+        })
+        .unwrap()
+    );
+
+    assert_eq!(
+        r#"{"1":"a","10":"c","2":"b"}"#,
+        sval_json::stream_to_string_canonical(std::collections::BTreeMap::from([
+            (1, "a"),
+            (2, "b"),
+            (10, "c")
+        ]))
+        .unwrap()
+    );
+}
+
+#[test]
+fn stream_canonical_sorting_escaped_keys() {
+    // Keys that are already JSON are written as-is, so may contain escapes
+    struct NativeKeys(&'static [&'static str]);
+
+    impl sval::Value for NativeKeys {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            stream.record_begin(None, None, None, Some(self.0.len()))?;
+
+            for (i, key) in self.0.iter().enumerate() {
+                let tag = Some(&sval_json::tags::JSON_NATIVE);
+                let label = sval::Label::new(key);
+
+                stream.record_value_begin(tag, &label)?;
+                stream.u64(i as u64)?;
+                stream.record_value_end(tag, &label)?;
+            }
+
+            stream.record_end(None, None, None)
+        }
+    }
+
+    // Escaped surrogate pairs sort by their UTF-16 code units like any other character
+    assert_eq!(
+        r#"{"a":1,"\ud83d\ude00":0,"\ufb33":2}"#,
+        sval_json::stream_to_string_canonical(NativeKeys(&["\\ud83d\\ude00", "a", "\\ufb33"]))
+            .unwrap()
+    );
+
+    for keys in [
+        &["\\ud83d"],
+        &["\\ude00"],
+        &["\\ud83da"],
+        &["\\ud83d\\u0061"],
+        &["\\u00"],
+    ] {
+        assert_eq!(
+            sval::ErrorKind::InvalidValue,
+            sval_json::stream_to_string_canonical(NativeKeys(keys))
+                .unwrap_err()
+                .kind(),
+            "{}",
+            keys[0]
+        );
+    }
+}
+
+#[test]
+fn stream_canonical_numbers() {
+    for (bits, expected) in [
+        (0x0000000000000000u64, "0"),
+        (0x8000000000000000, "0"),
+        (0x0000000000000001, "5e-324"),
+        (0x8000000000000001, "-5e-324"),
+        (0x7fefffffffffffff, "1.7976931348623157e+308"),
+        (0xffefffffffffffff, "-1.7976931348623157e+308"),
+        (0x4340000000000000, "9007199254740992"),
+        (0xc340000000000000, "-9007199254740992"),
+        (0x4430000000000000, "295147905179352830000"),
+        (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+        (0x44b52d02c7e14af6, "1e+23"),
+        (0x44b52d02c7e14af7, "1.0000000000000001e+23"),
+        (0x444b1ae4d6e2ef4e, "999999999999999700000"),
+        (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+        (0x444b1ae4d6e2ef50, "1e+21"),
+        (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        (0x3eb0c6f7a0b5ed8d, "0.000001"),
+        (0x41b3de4355555553, "333333333.3333332"),
+        (0x41b3de4355555554, "333333333.33333325"),
+        (0x41b3de4355555555, "333333333.3333333"),
+        (0x41b3de4355555556, "333333333.3333334"),
+        (0x41b3de4355555557, "333333333.33333343"),
+        (0xbecbf647612f3696, "-0.0000033333333333333333"),
+        (0x43143ff3c1cb0959, "1424953923781206.2"),
+    ] {
+        assert_eq!(
+            expected,
+            sval_json::stream_to_string_canonical(f64::from_bits(bits)).unwrap()
+        );
+    }
+
+    assert_eq!(
+        "0.5",
+        sval_json::stream_to_string_canonical(0.5f32).unwrap()
+    );
+    assert_eq!("42", sval_json::stream_to_string_canonical(42u64).unwrap());
+    assert_eq!(
+        "100",
+        sval_json::stream_to_string_canonical(sval_json::from_str("1e2")).unwrap()
+    );
+
+    for v in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        assert_eq!(
+            sval::ErrorKind::InvalidValue,
+            sval_json::stream_to_string_canonical(v).unwrap_err().kind()
+        );
+    }
+}
+
+#[test]
+fn stream_canonical_ignores_pretty() {
+    let config = sval_json::Config::new()
+        .with_pretty(sval_json::PrettyConfig::new())
+        .with_canonical(true);
+
+    assert_eq!(
+        r#"{"a":[1,2]}"#,
+        sval_json::stream_to_string_with(sval_json::from_str(r#"{ "a": [1, 2] }"#), config)
+            .unwrap()
+    );
+}
+
+struct NumberText<'a>(&'a str);
+
+impl<'a> sval::Value for NumberText<'a> {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        stream.tagged_begin(Some(&sval::tags::NUMBER), None, None)?;
+        stream.value(self.0)?;
+        stream.tagged_end(Some(&sval::tags::NUMBER), None, None)
+    }
+}
+
+#[test]
+fn stream_non_finite() {
+    use sval_json::{Config, NonFinite};
+
+    let values = (
+        f64::NAN,
+        f64::INFINITY,
+        f32::NEG_INFINITY,
+        NumberText("-inf"),
+        NumberText("NaN"),
+        1.5f64,
+    );
+
+    assert_eq!(
+        "[null,null,null,null,null,1.5]",
+        sval_json::stream_to_string(&values).unwrap()
+    );
+    assert_eq!(
+        r#"["NaN","Infinity","-Infinity","-Infinity","NaN",1.5]"#,
+        sval_json::stream_to_string_with(&values, Config::new().with_non_finite(NonFinite::String))
+            .unwrap()
+    );
+
+    fn stream_error(v: impl sval::Value) -> sval::ErrorKind {
+        sval_json::stream_to_string_with(v, Config::new().with_non_finite(NonFinite::Error))
+            .unwrap_err()
+            .kind()
+    }
+
+    assert_eq!(sval::ErrorKind::InvalidValue, stream_error(f64::NAN));
+    assert_eq!(sval::ErrorKind::InvalidValue, stream_error(f32::INFINITY));
+    assert_eq!(
+        sval::ErrorKind::InvalidValue,
+        stream_error(NumberText("inf"))
+    );
+}
+
+#[test]
+fn stream_large_integers() {
+    use sval_json::{Config, LargeIntegers};
+
+    let values = (
+        9007199254740991u64,
+        9007199254740992u64,
+        -9007199254740991i64,
+        -9007199254740992i64,
+        u128::MAX,
+        i128::MIN,
+        42u8,
+    );
+
+    assert_eq!(
+        "[9007199254740991,9007199254740992,-9007199254740991,-9007199254740992,340282366920938463463374607431768211455,-170141183460469231731687303715884105728,42]",
+        sval_json::stream_to_string(values).unwrap()
+    );
+    assert_eq!(
+        r#"[9007199254740991,"9007199254740992",-9007199254740991,"-9007199254740992","340282366920938463463374607431768211455","-170141183460469231731687303715884105728",42]"#,
+        sval_json::stream_to_string_with(
+            values,
+            Config::new().with_large_integers(LargeIntegers::String)
+        )
+        .unwrap()
+    );
+
+    // Map keys are already quoted
+    assert_eq!(
+        r#"{"18446744073709551615":1}"#,
+        sval_json::stream_to_string_with(
+            std::collections::BTreeMap::from([(u64::MAX, 1)]),
+            Config::new().with_large_integers(LargeIntegers::String)
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn read_decode_numbers() {
+    use sval::build::{Builder, FromValue};
+    use sval_json::{Config, LargeIntegers, NonFinite};
+
+    fn decode<T: FromValue>(json: &str) -> sval::Result<T> {
+        let mut builder = sval_json::DecodeNumbers::new(T::builder());
+        sval::Value::stream(sval_json::from_str(json), &mut builder)?;
+
+        builder.into_inner().finish()
+    }
+
+    let config = Config::new()
+        .with_non_finite(NonFinite::String)
+        .with_large_integers(LargeIntegers::String);
+
+    let json = sval_json::stream_to_string_with(
+        (f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 2.5f64),
+        config,
+    )
+    .unwrap();
+    let (nan, inf, neg_inf, finite) = decode::<(f64, f64, f64, f64)>(&json).unwrap();
+    assert!(nan.is_nan());
+    assert_eq!(f64::INFINITY, inf);
+    assert_eq!(f64::NEG_INFINITY, neg_inf);
+    assert_eq!(2.5, finite);
+
+    let values = (u64::MAX, i128::MIN, 9007199254740991u64);
+    let json = sval_json::stream_to_string_with(values, config).unwrap();
+    assert_eq!(values, decode::<(u64, i128, u64)>(&json).unwrap());
+
+    // Other strings are still strings
+    assert_eq!(
+        vec![
+            "NaN".to_owned(),
+            "9007199254740991".to_owned(),
+            "9007199254740993".to_owned(),
+            "01234567890123456789".to_owned(),
+            "a longer string that can't be a number".to_owned(),
+        ],
+        decode::<Vec<String>>(
+            r#"["NaN","9007199254740991","9007199254740993","01234567890123456789","a longer string that can't be a number"]"#
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        std::collections::BTreeMap::from([("NaN".to_owned(), 1.0)]),
+        decode::<std::collections::BTreeMap<String, f64>>(r#"{"NaN":1}"#).unwrap()
+    );
+}
+
+#[test]
+fn stream_escape_ascii_only() {
+    let config = sval_json::Config::new().with_ascii_only(true);
+
+    assert_eq!(
+        r#""a\"\n\u00e9\u4e2d\ud83d\ude00<&>""#,
+        sval_json::stream_to_string_with("a\"\né中😀<&>", config).unwrap()
+    );
+
+    // Map keys and record labels are escaped too
+    assert_eq!(
+        r#"{"\u00e9":{"\u4e2d":1}}"#,
+        sval_json::stream_to_string_with(
+            std::collections::BTreeMap::from([(
+                "é",
+                std::collections::BTreeMap::from([("中", 1)])
+            )]),
+            config
+        )
+        .unwrap()
+    );
+
+    // The output reads back as the original text
+    let json = sval_json::stream_to_string_with("é中😀\u{2028}", config).unwrap();
+    assert!(json.is_ascii());
+    assert_eq!(
+        "\"é中😀\u{2028}\"",
+        sval_json::stream_to_string(sval_json::from_str(&json)).unwrap()
+    );
+}
+
+#[test]
+fn stream_escape_html_safe() {
+    let config = sval_json::Config::new().with_html_safe(true);
+
+    assert_eq!(
+        r#""\u003c/script\u003e \u0026 é \u2028\u2029 \"""#,
+        sval_json::stream_to_string_with("</script> & é \u{2028}\u{2029} \"", config).unwrap()
+    );
+
+    assert_eq!(
+        r#""\u003c\u00e9\u003e""#,
+        sval_json::stream_to_string_with("<é>", config.with_ascii_only(true)).unwrap()
+    );
+}
+
+#[test]
+fn stream_escape_fragments() {
+    struct Fragments<'a>(&'a [&'a str], Option<sval::Tag>);
+
+    impl<'a> sval::Value for Fragments<'a> {
+        fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(
+            &'sval self,
+            stream: &mut S,
+        ) -> sval::Result {
+            if let Some(ref tag) = self.1 {
+                stream.tagged_begin(Some(tag), None, None)?;
+            }
+
+            stream.text_begin(None)?;
+            for fragment in self.0 {
+                stream.text_fragment(fragment)?;
+            }
+            stream.text_end()?;
+
+            if let Some(ref tag) = self.1 {
+                stream.tagged_end(Some(tag), None, None)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    let config = sval_json::Config::new()
+        .with_ascii_only(true)
+        .with_html_safe(true);
+
+    assert_eq!(
+        r#""\u003ca\ud83d\ude00\u00e9\u0026\n""#,
+        sval_json::stream_to_string_with(Fragments(&["<", "a😀", "é", "&\n"], None), config)
+            .unwrap()
+    );
+
+    // JSON native text is already escaped, but still needs non-ASCII and HTML characters escaped
+    assert_eq!(
+        r#""\u003c\"\u00e9""#,
+        sval_json::stream_to_string_with(
+            Fragments(&["<\\\"", "é"], Some(sval_json::tags::JSON_NATIVE)),
+            config
+        )
+        .unwrap()
+    );
+}
+
+fn assert_json_with(v: impl sval::Value + serde::Serialize, config: sval_json::Config) {
+    let expected = serde_json::to_string(&v).unwrap();
+    let actual = sval_json::stream_to_string_with(&v, config).unwrap();
+
+    assert_eq!(expected, actual);
+
+    let expected = serde_json::to_string_pretty(&v).unwrap();
+    let actual = sval_json::stream_to_string_with(
+        &v,
+        config.with_pretty(sval_json::PrettyConfig::default()),
+    )
+    .unwrap();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn stream_enums_internal() {
+    #[derive(Value, Serialize)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Constant,
+        Tagged(MapStruct),
+        MapStruct { field_0: i32, field_1: bool },
+        Nested { inner: Option<Box<Internal>> },
+    }
+
+    let config = sval_json::Config::new().with_enums(sval_json::Enums::Internal);
+
+    assert_json_with(Internal::Constant, config);
+    assert_json_with(
+        Internal::Tagged(MapStruct {
+            field_0: 42,
+            field_1: true,
+            field_2: "Hello",
+        }),
+        config,
+    );
+    assert_json_with(
+        Internal::MapStruct {
+            field_0: 42,
+            field_1: true,
+        },
+        config,
+    );
+    assert_json_with(
+        Internal::Nested {
+            inner: Some(Box::new(Internal::Constant)),
+        },
+        config,
+    );
+
+    assert_eq!(
+        r#"{"kind":"Constant"}"#,
+        sval_json::stream_to_string_with(Internal::Constant, config.with_enum_tag_field("kind"))
+            .unwrap()
+    );
+}
+
+#[test]
+fn stream_enums_internal_unsupported() {
+    let config = sval_json::Config::new().with_enums(sval_json::Enums::Internal);
+
+    // Only records and maps can be merged with the tag
+    assert!(sval_json::stream_to_string_with(Enum::Tagged(42), config).is_err());
+    assert!(sval_json::stream_to_string_with(Enum::SeqStruct(42, true, "Hello"), config).is_err());
+}
+
+#[test]
+fn stream_enums_adjacent() {
+    #[derive(Value, Serialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Constant,
+        Tagged(i32),
+        MapStruct { field_0: i32, field_1: bool },
+        SeqStruct(i32, bool, &'static str),
+    }
+
+    let config = sval_json::Config::new()
+        .with_enums(sval_json::Enums::Adjacent)
+        .with_enum_tag_field("t")
+        .with_enum_content_field("c");
+
+    assert_json_with(Adjacent::Constant, config);
+    assert_json_with(Adjacent::Tagged(42), config);
+    assert_json_with(
+        Adjacent::MapStruct {
+            field_0: 42,
+            field_1: true,
+        },
+        config,
+    );
+    assert_json_with(Adjacent::SeqStruct(42, true, "Hello"), config);
+    assert_json_with(vec![Adjacent::Tagged(1), Adjacent::Constant], config);
+}
+
+#[test]
+fn stream_enums_untagged() {
+    #[derive(Value, Serialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Constant,
+        Tagged(i32),
+        MapStruct { field_0: i32, field_1: bool },
+        SeqStruct(i32, bool, &'static str),
+    }
+
+    let config = sval_json::Config::new().with_enums(sval_json::Enums::Untagged);
+
+    assert_json_with(Untagged::Constant, config);
+    assert_json_with(Untagged::Tagged(42), config);
+    assert_json_with(
+        Untagged::MapStruct {
+            field_0: 42,
+            field_1: true,
+        },
+        config,
+    );
+    assert_json_with(Untagged::SeqStruct(42, true, "Hello"), config);
+}
+
+#[test]
+fn stream_enums_tagged() {
+    #[derive(Value, Serialize)]
+    #[sval(tag = "sval_json::tags::INTERNALLY_TAGGED")]
+    #[serde(tag = "type")]
+    enum Internal {
+        Constant,
+        MapStruct { field_0: i32, inner: Adjacent },
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(tag = "sval_json::tags::ADJACENTLY_TAGGED")]
+    #[serde(tag = "type", content = "content")]
+    enum Adjacent {
+        Tagged(Untagged),
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(tag = "sval_json::tags::UNTAGGED")]
+    #[serde(untagged)]
+    enum Untagged {
+        Tagged(i32),
+    }
+
+    #[derive(Value, Serialize)]
+    #[sval(tag = "sval_json::tags::EXTERNALLY_TAGGED")]
+    enum External {
+        Tagged(Internal),
+    }
+
+    // The tag on each enum overrides the configured representation
+    for enums in [
+        sval_json::Enums::External,
+        sval_json::Enums::Internal,
+        sval_json::Enums::Adjacent,
+        sval_json::Enums::Untagged,
+    ] {
+        let config = sval_json::Config::new().with_enums(enums);
+
+        assert_json_with(Internal::Constant, config);
+        assert_json_with(
+            External::Tagged(Internal::MapStruct {
+                field_0: 42,
+                inner: Adjacent::Tagged(Untagged::Tagged(1)),
+            }),
+            config,
+        );
+    }
+}
+
+#[test]
+fn read_pointer() {
+    let json = r#"{"items":[{"price":1},{"price":2.5,"name":"b"}],"a/b":{"m~n":[true]},"":null}"#;
+
+    for (pointer, expected) in [
+        ("/items/1/price", "2.5"),
+        ("/items/1", r#"{"price":2.5,"name":"b"}"#),
+        ("/a~1b/m~0n/0", "true"),
+        ("/", "null"),
+        ("", json),
+    ] {
+        let pointer = sval_json::Pointer::parse(pointer).unwrap();
+
+        assert_eq!(
+            expected,
+            sval_json::stream_to_string(pointer.select(sval_json::from_str(json))).unwrap(),
+            "{}",
+            pointer
+        );
+    }
+}
+
+#[test]
+fn stream_pointer() {
+    #[derive(Value)]
+    struct Order {
+        items: Vec<Item>,
+        totals: std::collections::BTreeMap<i32, Option<f64>>,
+    }
+
+    #[derive(Value)]
+    enum Item {
+        Priced { price: f64, label: &'static str },
+    }
+
+    let order = Order {
+        items: vec![
+            Item::Priced {
+                price: 1.0,
+                label: "a",
+            },
+            Item::Priced {
+                price: 2.5,
+                label: "b",
+            },
+        ],
+        totals: std::collections::BTreeMap::from([(1, Some(3.5)), (10, None)]),
+    };
+
+    for (pointer, expected) in [
+        ("/items/1/price", "2.5"),
+        ("/items/0/label", r#""a""#),
+        ("/totals/1", "3.5"),
+        ("/totals/10", "null"),
+    ] {
+        let pointer = sval_json::Pointer::parse(pointer).unwrap();
+
+        assert_eq!(
+            expected,
+            sval_json::stream_to_string(pointer.select(&order)).unwrap(),
+            "{}",
+            pointer
+        );
+    }
+}
+
+#[test]
+fn read_pointer_borrowed() {
+    struct Fragments<'sval>(Vec<&'sval str>);
+
+    impl<'sval> sval::Stream<'sval> for Fragments<'sval> {
+        fn null(&mut self) -> sval::Result {
+            sval::error()
+        }
+
+        fn bool(&mut self, _: bool) -> sval::Result {
+            sval::error()
+        }
+
+        fn text_begin(&mut self, _: Option<usize>) -> sval::Result {
+            Ok(())
+        }
+
+        fn text_fragment(&mut self, fragment: &'sval str) -> sval::Result {
+            self.0.push(fragment);
+            Ok(())
+        }
+
+        fn text_fragment_computed(&mut self, _: &str) -> sval::Result {
+            sval::error()
+        }
+
+        fn text_end(&mut self) -> sval::Result {
+            Ok(())
+        }
+
+        fn i64(&mut self, _: i64) -> sval::Result {
+            sval::error()
+        }
+
+        fn f64(&mut self, _: f64) -> sval::Result {
+            sval::error()
+        }
+
+        fn seq_begin(&mut self, _: Option<usize>) -> sval::Result {
+            sval::error()
+        }
+
+        fn seq_value_begin(&mut self) -> sval::Result {
+            sval::error()
+        }
+
+        fn seq_value_end(&mut self) -> sval::Result {
+            sval::error()
+        }
+
+        fn seq_end(&mut self) -> sval::Result {
+            sval::error()
+        }
+    }
+
+    let json = sval_json::from_str(r#"{"skipped":[1,{"a":"b"}],"selected":"borrowed text"}"#);
+
+    let mut select = sval_json::Select::new(
+        sval_json::Pointer::parse("/selected").unwrap(),
+        Fragments(Vec::new()),
+    );
+    sval::Value::stream(json, &mut select).unwrap();
+
+    assert_eq!(vec!["borrowed text"], select.finish().unwrap().0);
+}
+
+#[test]
+fn read_pointer_unresolved() {
+    let json = r#"{"items":[{"price":1}],"n":1}"#;
+
+    for (pointer, unresolved) in [
+        ("/missing", 0),
+        ("/items/1", 1),
+        ("/items/01", 1),
+        ("/items/-", 1),
+        ("/items/0/price/0", 3),
+        ("/n/0", 1),
+    ] {
+        let mut select = sval_json::Select::new(
+            sval_json::Pointer::parse(pointer).unwrap(),
+            sval_buffer::ValueBuf::new(),
+        );
+        sval::Value::stream(sval_json::from_str(json), &mut select).unwrap();
+
+        let err = select.finish().unwrap_err();
+
+        assert_eq!(sval::ErrorKind::InvalidValue, err.kind(), "{}", pointer);
+        assert_eq!(Some(unresolved), err.unresolved_token(), "{}", pointer);
+
+        // Selected values fail when the pointer doesn't resolve, keeping the unresolved token
+        let err = sval_json::stream_to_string(
+            sval_json::Pointer::parse(pointer)
+                .unwrap()
+                .select(sval_json::from_str(json)),
+        )
+        .unwrap_err();
+
+        assert_eq!(sval::ErrorKind::InvalidValue, err.kind(), "{}", pointer);
+        assert_eq!(Some(unresolved), err.unresolved_token(), "{}", pointer);
+    }
+
+    // Pointers must be empty or start with `/`, and only escape `~0` and `~1`
+    assert!(sval_json::Pointer::parse("items").is_err());
+    assert!(sval_json::Pointer::parse("/items~2").is_err());
+    assert!(sval_json::Pointer::parse("/items~").is_err());
+}
+
+const ORDER_SCHEMA: &str = r#"{
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "title": "Order",
+    "type": "object",
+    "properties": {
+        "id": { "type": "integer", "minimum": 1 },
+        "status": { "enum": ["open", "closed"] },
+        "items": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "price": { "type": "number", "exclusiveMinimum": 0, "maximum": 100 }
+                },
+                "required": ["name", "price"]
+            }
+        },
+        "note": { "type": ["string", "null"] }
+    },
+    "required": ["id", "items"],
+    "additionalProperties": false
+}"#;
+
+#[derive(Value)]
+struct Order {
+    id: i64,
+    status: &'static str,
+    items: Vec<OrderItem>,
+    note: Option<&'static str>,
+}
+
+#[derive(Value)]
+struct OrderItem {
+    name: &'static str,
+    price: f64,
+}
+
+fn validation_failures(
+    result: Result<(), Vec<sval_json::ValidationError>>,
+) -> Vec<(String, &'static str)> {
+    result
+        .unwrap_err()
+        .into_iter()
+        .map(|err| (err.instance_path().to_owned(), err.keyword()))
+        .collect()
+}
+
+#[test]
+fn validate_schema() {
+    let schema = sval_json::Schema::compile(ORDER_SCHEMA).unwrap();
+
+    schema
+        .validate(Order {
+            id: 1,
+            status: "open",
+            items: vec![OrderItem {
+                name: "a",
+                price: 2.5,
+            }],
+            note: None,
+        })
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            ("/id".to_owned(), "minimum"),
+            ("/status".to_owned(), "enum"),
+            ("/items/1/price".to_owned(), "exclusiveMinimum"),
+            ("/items/2/price".to_owned(), "maximum"),
+        ],
+        validation_failures(schema.validate(Order {
+            id: 0,
+            status: "pending",
+            items: vec![
+                OrderItem {
+                    name: "a",
+                    price: 2.5,
+                },
+                OrderItem {
+                    name: "b",
+                    price: 0.0,
+                },
+                OrderItem {
+                    name: "c",
+                    price: 101.0,
+                },
+            ],
+            note: Some("gift"),
+        }))
+    );
+}
+
+#[test]
+fn validate_schema_json() {
+    let schema = sval_json::Schema::compile(ORDER_SCHEMA).unwrap();
+
+    schema
+        .validate(sval_json::from_str(
+            r#"{"id":1,"items":[{"name":"a","price":1e1}],"note":null}"#,
+        ))
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            ("/id".to_owned(), "type"),
+            ("/items/0".to_owned(), "required"),
+            ("/items/1/name".to_owned(), "type"),
+            ("/note".to_owned(), "type"),
+            ("/extra~1field".to_owned(), "additionalProperties"),
+        ],
+        validation_failures(schema.validate(sval_json::from_str(
+            r#"{
+                "id": 1.5,
+                "status": "open",
+                "items": [{"name": "a"}, {"name": 1, "price": 1}],
+                "note": 1,
+                "extra/field": {"nested": [1, 2, 3]}
+            }"#
+        )))
+    );
+
+    assert_eq!(
+        vec![("".to_owned(), "type")],
+        validation_failures(schema.validate(sval_json::from_str("[]")))
+    );
+}
+
+#[test]
+fn validate_schema_integer() {
+    let schema = sval_json::Schema::compile(r#"{ "type": "integer" }"#).unwrap();
+
+    for v in [0.0, 3.0, -3.0, 1e20, -1e20, 9007199254740993.0] {
+        schema.validate(v).unwrap();
+    }
+
+    for v in [1.5, -0.5, f64::NAN, f64::INFINITY] {
+        assert_eq!(
+            vec![("".to_owned(), "type")],
+            validation_failures(schema.validate(v))
+        );
+    }
+}
+
+#[test]
+fn validate_schema_enums() {
+    #[derive(Value)]
+    enum Shape {
+        Point,
+        Circle { radius: f64 },
+        Square(f64),
+    }
+
+    let schema = sval_json::Schema::compile(
+        r#"{
+            "type": "array",
+            "items": {
+                "type": ["string", "object"],
+                "properties": {
+                    "Circle": {
+                        "type": "object",
+                        "properties": { "radius": { "minimum": 0 } },
+                        "required": ["radius"]
+                    },
+                    "Square": { "type": "number", "minimum": 0 }
+                },
+                "additionalProperties": false
+            }
+        }"#,
+    )
+    .unwrap();
+
+    schema
+        .validate(vec![
+            Shape::Point,
+            Shape::Circle { radius: 1.0 },
+            Shape::Square(2.0),
+        ])
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            ("/0/Circle/radius".to_owned(), "minimum"),
+            ("/1/Square".to_owned(), "minimum"),
+        ],
+        validation_failures(
+            schema.validate(vec![Shape::Circle { radius: -1.0 }, Shape::Square(-2.0)])
+        )
+    );
+}
+
+#[test]
+fn validate_schema_pattern() {
+    let schema = r#"{ "type": "string", "pattern": "^a" }"#;
+
+    let unmatched = sval_json::Schema::compile(schema).unwrap();
+    assert_eq!(
+        vec![("".to_owned(), "pattern")],
+        validation_failures(unmatched.validate("abc"))
+    );
+
+    let matched = sval_json::Schema::compile(schema)
+        .unwrap()
+        .with_pattern_matcher(|pattern: &str, text: &str| {
+            text.starts_with(pattern.trim_start_matches('^'))
+        });
+
+    matched.validate("abc").unwrap();
+
+    let err = matched.validate("cba").unwrap_err();
+    assert_eq!("pattern", err[0].keyword());
+    assert_eq!(
+        "the value doesn't match `^a` at `` (`pattern`)",
+        err[0].to_string()
+    );
+}
+
+#[test]
+fn validate_schema_compile() {
+    sval_json::Schema::compile("true")
+        .unwrap()
+        .validate(1)
+        .unwrap();
+
+    assert_eq!(
+        vec![("".to_owned(), "false")],
+        validation_failures(sval_json::Schema::compile("false").unwrap().validate(1))
+    );
+
+    for (schema, kind) in [
+        (r##"{"$ref": "#/$defs/a"}"##, sval::ErrorKind::Unsupported),
+        (r#"{"anyOf": []}"#, sval::ErrorKind::Unsupported),
+        (r#"{"enum": [[1]]}"#, sval::ErrorKind::Unsupported),
+        (r#"{"type": "text"}"#, sval::ErrorKind::InvalidValue),
+        (r#"{"required": "id"}"#, sval::ErrorKind::InvalidValue),
+        ("1", sval::ErrorKind::InvalidValue),
+    ] {
+        assert_eq!(
+            kind,
+            sval_json::Schema::compile(schema).unwrap_err().kind(),
+            "{}",
+            schema
+        );
+    }
+}
+
+#[test]
+fn describe_derive() {
+    use sval::{
+        schema::{Describe, Field, Schema},
+        Index, Label, Tag,
+    };
+
+    const CONTAINER: Tag = Tag::new("container");
+    const FIELD: Tag = Tag::new("field");
+
+    #[derive(Describe)]
+    #[sval(tag = "CONTAINER")]
+    #[allow(dead_code)]
+    struct Record<T> {
+        #[sval(rename = "renamed", field_tag = "FIELD")]
+        a: Option<T>,
+        #[sval(tag = "sval::tags::NUMBER")]
+        b: &'static str,
+        c: Vec<Newtype>,
+    }
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    struct Newtype(Unit);
+
+    #[derive(Describe)]
+    struct Unit;
+
+    #[derive(Describe)]
+    #[sval(tag = "CONTAINER")]
+    #[allow(dead_code)]
+    enum Enum {
+        Unit,
+        Tuple(i32, bool),
+        Record { a: i32 },
+    }
+
+    const RECORD: &Schema = &Schema::Record {
+        tag: Some(CONTAINER),
+        label: Some(Label::new("Record")),
+        index: None,
+        fields: &[
+            Field {
+                tag: Some(FIELD),
+                label: Some(Label::new("renamed")),
+                index: None,
+                value: &Schema::Option(&Schema::U8),
+                optional: false,
+            },
+            Field::record(
+                Label::new("b"),
+                &Schema::Tagged {
+                    tag: Some(sval::tags::NUMBER),
+                    label: None,
+                    index: None,
+                    value: &Schema::Text,
+                },
+            ),
+            Field::record(Label::new("c"), &Schema::Seq(NEWTYPE)),
+        ],
+    };
+
+    const NEWTYPE: &Schema = &Schema::Tagged {
+        tag: None,
+        label: Some(Label::new("Newtype")),
+        index: None,
+        value: &Schema::Tag {
+            tag: None,
+            label: Some(Label::new("Unit")),
+            index: None,
+        },
+    };
+
+    const ENUM: &Schema = &Schema::Enum {
+        tag: Some(CONTAINER),
+        label: Some(Label::new("Enum")),
+        index: None,
+        variants: &[
+            Schema::Tag {
+                tag: Some(CONTAINER),
+                label: Some(Label::new("Unit")),
+                index: Some(Index::new(0)),
+            },
+            Schema::Tuple {
+                tag: Some(CONTAINER),
+                label: Some(Label::new("Tuple")),
+                index: Some(Index::new(1)),
+                fields: &[
+                    Field::tuple(Index::new(0), &Schema::I32),
+                    Field::tuple(Index::new(1), &Schema::Bool),
+                ],
+            },
+            Schema::Record {
+                tag: Some(CONTAINER),
+                label: Some(Label::new("Record")),
+                index: Some(Index::new(2)),
+                fields: &[Field::record(Label::new("a"), &Schema::I32)],
+            },
+        ],
+    };
+
+    assert_eq!(RECORD, <Record<u8>>::SCHEMA);
+    assert_eq!(ENUM, Enum::SCHEMA);
+}
+
+fn assert_describe<T: sval::schema::Describe + ?Sized>(
+    config: sval_json::Config,
+    expected: serde_json::Value,
+) {
+    let actual: serde_json::Value =
+        serde_json::from_str(&sval_json::describe_to_string_with::<T>(config).unwrap()).unwrap();
+
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn describe_transparent() {
+    use serde_json::json;
+
+    #[derive(Describe)]
+    #[sval(transparent)]
+    #[allow(dead_code)]
+    struct Newtype(Option<bool>);
+
+    assert_describe::<Newtype>(
+        sval_json::Config::new(),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": ["boolean", "null"]
+        }),
+    );
+}
+
+#[test]
+fn describe_skip() {
+    use serde_json::json;
+
+    struct NotDescribe;
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    struct Record {
+        a: i32,
+        #[sval(skip)]
+        b: NotDescribe,
+        #[sval(skip_if = "Option::is_none")]
+        c: Option<bool>,
+    }
+
+    assert_describe::<Record>(
+        sval_json::Config::new(),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Record",
+            "type": "object",
+            "properties": {
+                "a": { "type": "integer", "minimum": -2147483648i64, "maximum": 2147483647 },
+                "c": { "type": ["boolean", "null"] }
+            },
+            "required": ["a"],
+            "additionalProperties": false
+        }),
+    );
+}
+
+#[test]
+fn describe_record() {
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    struct Record {
+        id: u8,
+        name: Option<String>,
+        #[sval(tag = "sval::tags::NUMBER")]
+        price: String,
+        scores: Vec<f64>,
+        counts: BTreeMap<String, i64>,
+        pair: (i32, Unit),
+        nested: Option<Newtype>,
+    }
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    struct Newtype(bool);
+
+    #[derive(Describe)]
+    struct Unit;
+
+    assert_describe::<Record>(
+        sval_json::Config::new(),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Record",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "minimum": 0, "maximum": 255 },
+                "name": { "type": ["string", "null"] },
+                "price": { "type": ["number", "null"] },
+                "scores": { "type": "array", "items": { "type": ["number", "null"] } },
+                "counts": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "pair": {
+                    "type": "array",
+                    "prefixItems": [
+                        { "type": "integer", "minimum": -2147483648i64, "maximum": 2147483647 },
+                        { "const": "Unit" }
+                    ],
+                    "items": false,
+                    "minItems": 2,
+                    "maxItems": 2
+                },
+                "nested": { "type": ["boolean", "null"] }
+            },
+            "required": ["id", "name", "price", "scores", "counts", "pair", "nested"],
+            "additionalProperties": false
+        }),
+    );
+}
+
+#[test]
+fn describe_enums() {
+    use serde_json::json;
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    enum Enum {
+        Constant,
+        Tagged(i32),
+        MapStruct { field_0: bool },
+    }
+
+    let tagged = json!({ "type": "integer", "minimum": -2147483648i64, "maximum": 2147483647 });
+    let record = |tag: Option<&str>| {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+
+        if let Some(tag) = tag {
+            properties.insert(tag.into(), json!({ "const": "MapStruct" }));
+            required.push(json!(tag));
+        }
+
+        properties.insert("field_0".into(), json!({ "type": "boolean" }));
+        required.push(json!("field_0"));
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false
+        })
+    };
+
+    let config = sval_json::Config::new().with_enum_tag_field("kind");
+
+    assert_describe::<Enum>(
+        config,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Enum",
+            "oneOf": [
+                { "const": "Constant" },
+                {
+                    "type": "object",
+                    "properties": { "Tagged": tagged },
+                    "required": ["Tagged"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "properties": { "MapStruct": record(None) },
+                    "required": ["MapStruct"],
+                    "additionalProperties": false
+                }
+            ]
+        }),
+    );
+
+    assert_describe::<Enum>(
+        config.with_enums(sval_json::Enums::Adjacent),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Enum",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": { "const": "Constant" } },
+                    "required": ["kind"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "properties": { "kind": { "const": "Tagged" }, "content": tagged },
+                    "required": ["kind", "content"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "properties": { "kind": { "const": "MapStruct" }, "content": record(None) },
+                    "required": ["kind", "content"],
+                    "additionalProperties": false
+                }
+            ]
+        }),
+    );
+
+    assert_describe::<Enum>(
+        config.with_enums(sval_json::Enums::Untagged),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Enum",
+            "anyOf": [{ "type": "null" }, tagged, record(None)]
+        }),
+    );
+
+    #[derive(Describe)]
+    #[sval(tag = "sval_json::tags::INTERNALLY_TAGGED")]
+    #[allow(dead_code)]
+    enum Internal {
+        Constant,
+        MapStruct { field_0: bool },
+    }
+
+    // The tag on the enum overrides the configured representation
+    assert_describe::<Internal>(
+        config,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Internal",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": { "kind": { "const": "Constant" } },
+                    "required": ["kind"],
+                    "additionalProperties": false
+                },
+                record(Some("kind"))
+            ]
+        }),
+    );
+
+    let err =
+        sval_json::describe_to_string_with::<Enum>(config.with_enums(sval_json::Enums::Internal))
+            .unwrap_err();
+    assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+}
+
+#[test]
+fn describe_config() {
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[derive(Describe)]
+    #[allow(dead_code)]
+    struct Record {
+        #[sval(tag = "sval_json::tags::HEX")]
+        hex: Binary,
+        base64: Binary,
+        large: u64,
+        float: f32,
+        map: BTreeMap<i8, bool>,
+    }
+
+    struct Binary;
+
+    impl sval::schema::Describe for Binary {
+        const SCHEMA: &'static sval::Schema = &sval::Schema::Binary;
+    }
+
+    assert_describe::<Record>(
+        sval_json::Config::new()
+            .with_binary(sval_json::Binary::Base64)
+            .with_large_integers(sval_json::LargeIntegers::String)
+            .with_non_finite(sval_json::NonFinite::String)
+            .with_map_keys(sval_json::MapKeys::Pairs),
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Record",
+            "type": "object",
+            "properties": {
+                "hex": { "type": "string", "contentEncoding": "base16" },
+                "base64": { "type": "string", "contentEncoding": "base64" },
+                "large": { "type": ["integer", "string"], "pattern": "^[0-9]+$", "minimum": 0 },
+                "float": { "anyOf": [{ "type": "number" }, { "enum": ["NaN", "Infinity", "-Infinity"] }] },
+                "map": {
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "prefixItems": [
+                            { "type": "integer", "minimum": -128, "maximum": 127 },
+                            { "type": "boolean" }
+                        ],
+                        "items": false,
+                        "minItems": 2,
+                        "maxItems": 2
+                    }
+                }
+            },
+            "required": ["hex", "base64", "large", "float", "map"],
+            "additionalProperties": false
+        }),
+    );
+
+    let err = sval_json::describe_to_string_with::<BTreeMap<i8, bool>>(
+        sval_json::Config::new().with_map_keys(sval_json::MapKeys::Text),
+    )
+    .unwrap_err();
+    assert_eq!(sval::ErrorKind::Unsupported, err.kind());
+}
+
+#[test]
+fn describe_validate() {
+    #[derive(Value, Describe)]
+    struct Record {
+        id: u32,
+        name: Option<&'static str>,
+        tags: Vec<&'static str>,
+    }
+
+    let schema =
+        sval_json::Schema::compile(&sval_json::describe_to_string::<Record>().unwrap()).unwrap();
+
+    schema
+        .validate(Record {
+            id: 1,
+            name: None,
+            tags: vec!["a"],
+        })
+        .unwrap();
+}
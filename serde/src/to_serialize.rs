@@ -47,7 +47,13 @@ impl<V: sval::Value> serde::Serialize for ToSerialize<V> {
             })),
         };
 
-        let _ = self.0.stream(&mut stream);
+        if let Err(e) = self.0.stream(&mut stream) {
+            // Errors from the serializer itself are already stored in the state
+            // Any other errors come from the value being streamed
+            if !matches!(stream.state, State::Done(Some(Err(_)))) {
+                return Err(S::Error::custom(e));
+            }
+        }
 
         stream.finish()
     }
@@ -667,8 +673,9 @@ impl<S: serde::Serializer> State<S> {
             }
             Ok(None) => Ok(()),
             Err(e) => {
+                let err = sval::Error::custom(&e);
                 *self = State::Done(Some(Err(e)));
-                Err(sval::Error::new())
+                Err(err)
             }
         }
     }
@@ -681,9 +688,10 @@ fn try_catch<'sval, T, S: serde::Serializer>(
     match f(serializer) {
         Ok(v) => Ok(v),
         Err(e) => {
+            let err = sval::Error::custom(&e);
             serializer.state = State::Done(Some(Err(e)));
 
-            sval::error()
+            Err(err)
         }
     }
 }
@@ -67,32 +67,32 @@ struct StreamRecordVariant<S> {
 }
 
 #[derive(Debug)]
-struct Error;
+struct Error(sval::Error);
 
 impl From<Error> for sval::Error {
-    fn from(_: Error) -> sval::Error {
-        sval::Error::new()
+    fn from(err: Error) -> sval::Error {
+        err.0
     }
 }
 
 impl From<sval::Error> for Error {
-    fn from(_: sval::Error) -> Error {
-        Error
+    fn from(err: sval::Error) -> Error {
+        Error(err)
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "failed to stream a value")
+        fmt::Display::fmt(&self.0, f)
     }
 }
 
 impl serde::ser::Error for Error {
-    fn custom<T>(_: T) -> Self
+    fn custom<T>(msg: T) -> Self
     where
         T: fmt::Display,
     {
-        Error
+        Error(sval::Error::custom(msg))
     }
 }
 
@@ -0,0 +1,1067 @@
+/*!
+Build Rust values from a stream.
+
+The [`FromValue`] trait is the counterpart to [`Value`]. Where a `Value`
+produces tokens for a [`Stream`], a `FromValue` type provides a [`Builder`],
+which is a `Stream` that assembles an instance of that type from the tokens
+it receives.
+
+Builders for values that contain other values, like sequences, maps, and records,
+can be implemented using [`Compound`] and [`CompoundBuilder`]. A `CompoundBuilder`
+forwards tokens that belong to nested values to their own builders so that
+a `Compound` only needs to handle the tokens that describe its own structure.
+*/
+
+mod map;
+mod option;
+mod primitive;
+mod seq;
+
+#[cfg(feature = "alloc")]
+mod text;
+
+use crate::{Error, Index, Label, Result, Stream, Tag, Value};
+
+pub use self::{map::*, option::*, primitive::*, seq::*};
+
+#[cfg(feature = "alloc")]
+pub use self::text::*;
+
+/**
+A type that can be built from a stream of tokens.
+
+`FromValue` is like `serde`'s `Deserialize`.
+*/
+pub trait FromValue: Sized {
+    /**
+    The builder that assembles this type.
+    */
+    type Builder: Builder<Output = Self>;
+
+    /**
+    Get a new builder for this type.
+    */
+    fn builder() -> Self::Builder;
+}
+
+/**
+A stream that assembles a value from the tokens it receives.
+
+Builders accept tokens borrowed for any lifetime, so any assembled
+values need to be owned.
+*/
+pub trait Builder: for<'sval> Stream<'sval> {
+    /**
+    The value produced by this builder.
+    */
+    type Output;
+
+    /**
+    Complete the value.
+
+    This method will fail if the builder didn't receive a complete value.
+    */
+    fn finish(self) -> Result<Self::Output>;
+}
+
+/**
+Build a value of type `T` from a value.
+*/
+pub fn from_value<T: FromValue>(value: &(impl Value + ?Sized)) -> Result<T> {
+    let mut builder = T::builder();
+
+    value.stream(&mut builder)?;
+
+    builder.finish()
+}
+
+/**
+A builder that can be used as a trait object.
+
+This trait is implemented for all [`Builder`]s, so nested builders can be
+forwarded tokens without their parents needing to know their concrete types.
+*/
+pub trait NestedBuilder {
+    /**
+    Stream a token through the builder.
+    */
+    fn token(&mut self, token: Token) -> Result;
+}
+
+impl<B: Builder> NestedBuilder for B {
+    fn token(&mut self, token: Token) -> Result {
+        token.stream(self)
+    }
+}
+
+/**
+A single call to a [`Stream`].
+
+Tokens are passed between [`CompoundBuilder`]s and their nested builders.
+*/
+#[derive(Debug, Clone, Copy)]
+pub enum Token<'a> {
+    /**
+    [`Stream::null`].
+    */
+    Null,
+    /**
+    [`Stream::bool`].
+    */
+    Bool(bool),
+    /**
+    [`Stream::text_begin`].
+    */
+    TextBegin(Option<usize>),
+    /**
+    [`Stream::text_fragment_computed`].
+    */
+    TextFragment(&'a str),
+    /**
+    [`Stream::text_end`].
+    */
+    TextEnd,
+    /**
+    [`Stream::binary_begin`].
+    */
+    BinaryBegin(Option<usize>),
+    /**
+    [`Stream::binary_fragment_computed`].
+    */
+    BinaryFragment(&'a [u8]),
+    /**
+    [`Stream::binary_end`].
+    */
+    BinaryEnd,
+    /**
+    [`Stream::u8`].
+    */
+    U8(u8),
+    /**
+    [`Stream::u16`].
+    */
+    U16(u16),
+    /**
+    [`Stream::u32`].
+    */
+    U32(u32),
+    /**
+    [`Stream::u64`].
+    */
+    U64(u64),
+    /**
+    [`Stream::u128`].
+    */
+    U128(u128),
+    /**
+    [`Stream::i8`].
+    */
+    I8(i8),
+    /**
+    [`Stream::i16`].
+    */
+    I16(i16),
+    /**
+    [`Stream::i32`].
+    */
+    I32(i32),
+    /**
+    [`Stream::i64`].
+    */
+    I64(i64),
+    /**
+    [`Stream::i128`].
+    */
+    I128(i128),
+    /**
+    [`Stream::f32`].
+    */
+    F32(f32),
+    /**
+    [`Stream::f64`].
+    */
+    F64(f64),
+    /**
+    [`Stream::map_begin`].
+    */
+    MapBegin(Option<usize>),
+    /**
+    [`Stream::map_key_begin`].
+    */
+    MapKeyBegin,
+    /**
+    [`Stream::map_key_end`].
+    */
+    MapKeyEnd,
+    /**
+    [`Stream::map_value_begin`].
+    */
+    MapValueBegin,
+    /**
+    [`Stream::map_value_end`].
+    */
+    MapValueEnd,
+    /**
+    [`Stream::map_end`].
+    */
+    MapEnd,
+    /**
+    [`Stream::seq_begin`].
+    */
+    SeqBegin(Option<usize>),
+    /**
+    [`Stream::seq_value_begin`].
+    */
+    SeqValueBegin,
+    /**
+    [`Stream::seq_value_end`].
+    */
+    SeqValueEnd,
+    /**
+    [`Stream::seq_end`].
+    */
+    SeqEnd,
+    /**
+    [`Stream::enum_begin`].
+    */
+    EnumBegin(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::enum_end`].
+    */
+    EnumEnd(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::tagged_begin`].
+    */
+    TaggedBegin(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::tagged_end`].
+    */
+    TaggedEnd(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::tag`].
+    */
+    Tag(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::record_begin`].
+    */
+    RecordBegin(
+        Option<&'a Tag>,
+        Option<&'a Label<'a>>,
+        Option<&'a Index>,
+        Option<usize>,
+    ),
+    /**
+    [`Stream::record_value_begin`].
+    */
+    RecordValueBegin(Option<&'a Tag>, &'a Label<'a>),
+    /**
+    [`Stream::record_value_end`].
+    */
+    RecordValueEnd(Option<&'a Tag>, &'a Label<'a>),
+    /**
+    [`Stream::record_end`].
+    */
+    RecordEnd(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+    /**
+    [`Stream::tuple_begin`].
+    */
+    TupleBegin(
+        Option<&'a Tag>,
+        Option<&'a Label<'a>>,
+        Option<&'a Index>,
+        Option<usize>,
+    ),
+    /**
+    [`Stream::tuple_value_begin`].
+    */
+    TupleValueBegin(Option<&'a Tag>, &'a Index),
+    /**
+    [`Stream::tuple_value_end`].
+    */
+    TupleValueEnd(Option<&'a Tag>, &'a Index),
+    /**
+    [`Stream::tuple_end`].
+    */
+    TupleEnd(Option<&'a Tag>, Option<&'a Label<'a>>, Option<&'a Index>),
+}
+
+enum Depth {
+    Begin,
+    End,
+    Same,
+}
+
+impl<'a> Token<'a> {
+    /**
+    Stream this token through a [`Stream`].
+    */
+    pub fn stream<'sval, S: Stream<'sval> + ?Sized>(self, stream: &mut S) -> Result {
+        match self {
+            Token::Null => stream.null(),
+            Token::Bool(v) => stream.bool(v),
+            Token::TextBegin(num_bytes_hint) => stream.text_begin(num_bytes_hint),
+            Token::TextFragment(fragment) => stream.text_fragment_computed(fragment),
+            Token::TextEnd => stream.text_end(),
+            Token::BinaryBegin(num_bytes_hint) => stream.binary_begin(num_bytes_hint),
+            Token::BinaryFragment(fragment) => stream.binary_fragment_computed(fragment),
+            Token::BinaryEnd => stream.binary_end(),
+            Token::U8(v) => stream.u8(v),
+            Token::U16(v) => stream.u16(v),
+            Token::U32(v) => stream.u32(v),
+            Token::U64(v) => stream.u64(v),
+            Token::U128(v) => stream.u128(v),
+            Token::I8(v) => stream.i8(v),
+            Token::I16(v) => stream.i16(v),
+            Token::I32(v) => stream.i32(v),
+            Token::I64(v) => stream.i64(v),
+            Token::I128(v) => stream.i128(v),
+            Token::F32(v) => stream.f32(v),
+            Token::F64(v) => stream.f64(v),
+            Token::MapBegin(num_entries_hint) => stream.map_begin(num_entries_hint),
+            Token::MapKeyBegin => stream.map_key_begin(),
+            Token::MapKeyEnd => stream.map_key_end(),
+            Token::MapValueBegin => stream.map_value_begin(),
+            Token::MapValueEnd => stream.map_value_end(),
+            Token::MapEnd => stream.map_end(),
+            Token::SeqBegin(num_entries_hint) => stream.seq_begin(num_entries_hint),
+            Token::SeqValueBegin => stream.seq_value_begin(),
+            Token::SeqValueEnd => stream.seq_value_end(),
+            Token::SeqEnd => stream.seq_end(),
+            Token::EnumBegin(tag, label, index) => stream.enum_begin(tag, label, index),
+            Token::EnumEnd(tag, label, index) => stream.enum_end(tag, label, index),
+            Token::TaggedBegin(tag, label, index) => stream.tagged_begin(tag, label, index),
+            Token::TaggedEnd(tag, label, index) => stream.tagged_end(tag, label, index),
+            Token::Tag(tag, label, index) => stream.tag(tag, label, index),
+            Token::RecordBegin(tag, label, index, num_entries) => {
+                stream.record_begin(tag, label, index, num_entries)
+            }
+            Token::RecordValueBegin(tag, label) => stream.record_value_begin(tag, label),
+            Token::RecordValueEnd(tag, label) => stream.record_value_end(tag, label),
+            Token::RecordEnd(tag, label, index) => stream.record_end(tag, label, index),
+            Token::TupleBegin(tag, label, index, num_entries) => {
+                stream.tuple_begin(tag, label, index, num_entries)
+            }
+            Token::TupleValueBegin(tag, index) => stream.tuple_value_begin(tag, index),
+            Token::TupleValueEnd(tag, index) => stream.tuple_value_end(tag, index),
+            Token::TupleEnd(tag, label, index) => stream.tuple_end(tag, label, index),
+        }
+    }
+
+    fn depth(&self) -> Depth {
+        match self {
+            Token::TextBegin(_)
+            | Token::BinaryBegin(_)
+            | Token::MapBegin(_)
+            | Token::MapKeyBegin
+            | Token::MapValueBegin
+            | Token::SeqBegin(_)
+            | Token::SeqValueBegin
+            | Token::EnumBegin(_, _, _)
+            | Token::TaggedBegin(_, _, _)
+            | Token::RecordBegin(_, _, _, _)
+            | Token::RecordValueBegin(_, _)
+            | Token::TupleBegin(_, _, _, _)
+            | Token::TupleValueBegin(_, _) => Depth::Begin,
+            Token::TextEnd
+            | Token::BinaryEnd
+            | Token::MapKeyEnd
+            | Token::MapValueEnd
+            | Token::MapEnd
+            | Token::SeqValueEnd
+            | Token::SeqEnd
+            | Token::EnumEnd(_, _, _)
+            | Token::TaggedEnd(_, _, _)
+            | Token::RecordValueEnd(_, _)
+            | Token::RecordEnd(_, _, _)
+            | Token::TupleValueEnd(_, _)
+            | Token::TupleEnd(_, _, _) => Depth::End,
+            _ => Depth::Same,
+        }
+    }
+}
+
+/**
+What a [`Compound`] did with a token it was given.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Handled {
+    /**
+    The token was part of the compound value itself.
+    */
+    Consumed,
+    /**
+    The token began a nested value, so should be forwarded to its builder.
+    */
+    Forward,
+}
+
+/**
+The state for a builder of values that contain other values.
+
+A `Compound` is driven by a [`CompoundBuilder`], which only gives it the tokens
+that describe its own structure. Tokens that belong to a nested value are
+forwarded to the builder returned by [`Compound::nested`] until that value
+is complete.
+*/
+pub trait Compound {
+    /**
+    The value produced by this compound.
+    */
+    type Output;
+
+    /**
+    Get the builder for the nested value currently being built, if there is one.
+    */
+    fn nested(&mut self) -> Option<&mut dyn NestedBuilder>;
+
+    /**
+    Handle a token that isn't part of a nested value.
+
+    If the token begins a nested value itself, like a record variant of an enum,
+    then the compound can begin the nested builder and return [`Handled::Forward`].
+    */
+    fn token(&mut self, token: Token) -> Result<Handled>;
+
+    /**
+    Attach details about the nested value currently being built to an error
+    it produced.
+    */
+    fn nested_error(&self, err: Error) -> Error {
+        err
+    }
+
+    /**
+    Complete the value.
+    */
+    fn finish(self) -> Result<Self::Output>;
+}
+
+/**
+A [`Builder`] for a [`Compound`].
+*/
+#[derive(Debug)]
+pub struct CompoundBuilder<C> {
+    compound: C,
+    depth: usize,
+}
+
+impl<C> CompoundBuilder<C> {
+    /**
+    Create a builder over the given compound state.
+    */
+    pub fn new(compound: C) -> Self {
+        CompoundBuilder { compound, depth: 0 }
+    }
+}
+
+impl<C: Compound> CompoundBuilder<C> {
+    fn token(&mut self, token: Token) -> Result {
+        if let Some(r) = self.forward(token) {
+            return r;
+        }
+
+        match self.compound.token(token)? {
+            Handled::Consumed => Ok(()),
+            Handled::Forward => self.forward(token).unwrap_or_else(|| {
+                Err(Error::invalid_protocol()
+                    .with_message("a token was forwarded without a nested value to receive it"))
+            }),
+        }
+    }
+
+    fn forward(&mut self, token: Token) -> Option<Result> {
+        let nested = self.compound.nested()?;
+
+        let r = match token.depth() {
+            Depth::Begin => {
+                self.depth += 1;
+                nested.token(token)
+            }
+            Depth::End if self.depth > 0 => {
+                self.depth -= 1;
+                nested.token(token)
+            }
+            // The end of a value with no depth belongs to the compound
+            Depth::End => return None,
+            Depth::Same => nested.token(token),
+        };
+
+        Some(r.map_err(|err| self.compound.nested_error(err)))
+    }
+}
+
+impl<'sval, C: Compound> Stream<'sval> for CompoundBuilder<C> {
+    fn null(&mut self) -> Result {
+        self.token(Token::Null)
+    }
+
+    fn bool(&mut self, value: bool) -> Result {
+        self.token(Token::Bool(value))
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        self.token(Token::TextBegin(num_bytes_hint))
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+        self.token(Token::TextFragment(fragment))
+    }
+
+    fn text_end(&mut self) -> Result {
+        self.token(Token::TextEnd)
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        self.token(Token::BinaryBegin(num_bytes_hint))
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> Result {
+        self.token(Token::BinaryFragment(fragment))
+    }
+
+    fn binary_end(&mut self) -> Result {
+        self.token(Token::BinaryEnd)
+    }
+
+    fn u8(&mut self, value: u8) -> Result {
+        self.token(Token::U8(value))
+    }
+
+    fn u16(&mut self, value: u16) -> Result {
+        self.token(Token::U16(value))
+    }
+
+    fn u32(&mut self, value: u32) -> Result {
+        self.token(Token::U32(value))
+    }
+
+    fn u64(&mut self, value: u64) -> Result {
+        self.token(Token::U64(value))
+    }
+
+    fn u128(&mut self, value: u128) -> Result {
+        self.token(Token::U128(value))
+    }
+
+    fn i8(&mut self, value: i8) -> Result {
+        self.token(Token::I8(value))
+    }
+
+    fn i16(&mut self, value: i16) -> Result {
+        self.token(Token::I16(value))
+    }
+
+    fn i32(&mut self, value: i32) -> Result {
+        self.token(Token::I32(value))
+    }
+
+    fn i64(&mut self, value: i64) -> Result {
+        self.token(Token::I64(value))
+    }
+
+    fn i128(&mut self, value: i128) -> Result {
+        self.token(Token::I128(value))
+    }
+
+    fn f32(&mut self, value: f32) -> Result {
+        self.token(Token::F32(value))
+    }
+
+    fn f64(&mut self, value: f64) -> Result {
+        self.token(Token::F64(value))
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        self.token(Token::MapBegin(num_entries_hint))
+    }
+
+    fn map_key_begin(&mut self) -> Result {
+        self.token(Token::MapKeyBegin)
+    }
+
+    fn map_key_end(&mut self) -> Result {
+        self.token(Token::MapKeyEnd)
+    }
+
+    fn map_value_begin(&mut self) -> Result {
+        self.token(Token::MapValueBegin)
+    }
+
+    fn map_value_end(&mut self) -> Result {
+        self.token(Token::MapValueEnd)
+    }
+
+    fn map_end(&mut self) -> Result {
+        self.token(Token::MapEnd)
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        self.token(Token::SeqBegin(num_entries_hint))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        self.token(Token::SeqValueBegin)
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        self.token(Token::SeqValueEnd)
+    }
+
+    fn seq_end(&mut self) -> Result {
+        self.token(Token::SeqEnd)
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::EnumBegin(tag, label, index))
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::EnumEnd(tag, label, index))
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::TaggedBegin(tag, label, index))
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::TaggedEnd(tag, label, index))
+    }
+
+    fn tag(&mut self, tag: Option<&Tag>, label: Option<&Label>, index: Option<&Index>) -> Result {
+        self.token(Token::Tag(tag, label, index))
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries_hint: Option<usize>,
+    ) -> Result {
+        self.token(Token::RecordBegin(tag, label, index, num_entries_hint))
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        self.token(Token::RecordValueBegin(tag, label))
+    }
+
+    fn record_value_end(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        self.token(Token::RecordValueEnd(tag, label))
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::RecordEnd(tag, label, index))
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries_hint: Option<usize>,
+    ) -> Result {
+        self.token(Token::TupleBegin(tag, label, index, num_entries_hint))
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        self.token(Token::TupleValueBegin(tag, index))
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        self.token(Token::TupleValueEnd(tag, index))
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.token(Token::TupleEnd(tag, label, index))
+    }
+}
+
+impl<C: Compound> Builder for CompoundBuilder<C> {
+    type Output = C::Output;
+
+    fn finish(self) -> Result<Self::Output> {
+        if self.depth != 0 {
+            return Err(Error::invalid_protocol().with_message("the value is incomplete"));
+        }
+
+        self.compound.finish()
+    }
+}
+
+/**
+Storage for a nested value within a [`Compound`].
+
+A slot is either empty, building its value, or holding its built value.
+*/
+pub struct Slot<T: FromValue> {
+    state: SlotState<T>,
+}
+
+enum SlotState<T: FromValue> {
+    Empty,
+    Building(T::Builder),
+    Built(T),
+}
+
+impl<T: FromValue> Default for Slot<T> {
+    fn default() -> Self {
+        Slot::new()
+    }
+}
+
+impl<T: FromValue> Slot<T> {
+    /**
+    Create a new, empty slot.
+    */
+    pub fn new() -> Self {
+        Slot {
+            state: SlotState::Empty,
+        }
+    }
+
+    /**
+    Begin building the value for this slot.
+
+    This method will fail if the slot already has a value.
+    */
+    pub fn begin(&mut self) -> Result {
+        match self.state {
+            SlotState::Empty => {
+                self.state = SlotState::Building(T::builder());
+                Ok(())
+            }
+            _ => Err(Error::invalid_value().with_message("the value was already given")),
+        }
+    }
+
+    /**
+    Complete the value being built for this slot.
+    */
+    pub fn end(&mut self) -> Result {
+        match crate::std::mem::replace(&mut self.state, SlotState::Empty) {
+            SlotState::Building(builder) => {
+                self.state = SlotState::Built(builder.finish()?);
+                Ok(())
+            }
+            state => {
+                self.state = state;
+                Err(Error::invalid_protocol().with_message("the value was never begun"))
+            }
+        }
+    }
+
+    /**
+    Build the value for this slot by streaming directly to its builder.
+    */
+    pub fn build_with(&mut self, build: impl FnOnce(&mut T::Builder) -> Result) -> Result {
+        self.begin()?;
+
+        if let SlotState::Building(ref mut builder) = self.state {
+            build(builder)?;
+        }
+
+        self.end()
+    }
+
+    /**
+    Whether the value for this slot is currently being built.
+    */
+    pub fn is_building(&self) -> bool {
+        matches!(self.state, SlotState::Building(_))
+    }
+
+    /**
+    Get the builder for this slot, if its value is currently being built.
+    */
+    pub fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+        match self.state {
+            SlotState::Building(ref mut builder) => Some(builder),
+            _ => None,
+        }
+    }
+
+    /**
+    Take the built value out of this slot, leaving it empty.
+    */
+    pub fn take(&mut self) -> Option<T> {
+        match crate::std::mem::replace(&mut self.state, SlotState::Empty) {
+            SlotState::Built(value) => Some(value),
+            state => {
+                self.state = state;
+                None
+            }
+        }
+    }
+
+    /**
+    Complete the value for this slot, if it's being built, and take it.
+
+    This method will fail if the slot doesn't have a value.
+    */
+    pub fn finish(&mut self) -> Result<T> {
+        if self.is_building() {
+            self.end()?;
+        }
+
+        self.take()
+            .ok_or_else(|| Error::invalid_value().with_message("missing value"))
+    }
+}
+
+/**
+Match text streamed in fragments against a set of known labels.
+
+This lets a [`Compound`] find the field or variant named by a map key or text
+value without needing to buffer it first.
+*/
+#[derive(Debug, Clone)]
+pub struct LabelMatcher<const N: usize> {
+    len: usize,
+    candidates: [bool; N],
+    // The text streamed so far, once it can't match any label
+    #[cfg(feature = "alloc")]
+    unmatched: Option<crate::std::string::String>,
+}
+
+impl<const N: usize> Default for LabelMatcher<N> {
+    fn default() -> Self {
+        LabelMatcher::new()
+    }
+}
+
+impl<const N: usize> LabelMatcher<N> {
+    /**
+    Begin matching text, with every label as a candidate.
+    */
+    pub fn new() -> Self {
+        LabelMatcher {
+            len: 0,
+            candidates: [true; N],
+            #[cfg(feature = "alloc")]
+            unmatched: None,
+        }
+    }
+
+    /**
+    Match the next fragment of text.
+    */
+    pub fn fragment(&mut self, labels: &[&str; N], fragment: &str) {
+        #[cfg(feature = "alloc")]
+        let matched = self.matched(labels);
+
+        for (candidate, label) in self.candidates.iter_mut().zip(labels) {
+            *candidate = *candidate
+                && label
+                    .as_bytes()
+                    .get(self.len..)
+                    .map(|rest| rest.starts_with(fragment.as_bytes()))
+                    .unwrap_or(false);
+        }
+
+        self.len += fragment.len();
+
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(ref mut unmatched) = self.unmatched {
+                unmatched.push_str(fragment);
+            } else if !self.candidates.contains(&true) {
+                let mut unmatched = crate::std::string::String::from(matched.unwrap_or(""));
+                unmatched.push_str(fragment);
+
+                self.unmatched = Some(unmatched);
+            }
+        }
+    }
+
+    /**
+    Get the index of the label that matched all the text, if there is one.
+    */
+    pub fn finish(&self, labels: &[&str; N]) -> Option<usize> {
+        self.candidates
+            .iter()
+            .zip(labels)
+            .position(|(candidate, label)| *candidate && label.len() == self.len)
+    }
+
+    /**
+    Get the text that has been matched so far.
+
+    This can be used to report text that didn't match any label. Text that stops
+    matching every label is only kept when the `alloc` feature is enabled, so
+    without it this method will return an empty string in that case.
+    */
+    pub fn text<'a>(&'a self, labels: &[&'a str; N]) -> &'a str {
+        #[cfg(feature = "alloc")]
+        {
+            if let Some(ref unmatched) = self.unmatched {
+                return unmatched;
+            }
+        }
+
+        self.matched(labels).unwrap_or("")
+    }
+
+    fn matched<'a>(&self, labels: &[&'a str; N]) -> Option<&'a str> {
+        // Any remaining candidate starts with all the text so far
+        self.candidates
+            .iter()
+            .zip(labels)
+            .find(|(candidate, _)| **candidate)
+            .map(|(_, label)| &label[..self.len])
+    }
+}
+
+fn unexpected(expected: &str) -> Error {
+    Error::unsupported().with_message(format_args!("expected {}", expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_primitive() {
+        assert!(from_value::<bool>(&true).unwrap());
+        assert_eq!(42u8, from_value::<u8>(&42i64).unwrap());
+        assert_eq!(42.5f64, from_value::<f64>(&42.5f32).unwrap());
+        assert_eq!('a', from_value::<char>(&'a').unwrap());
+        assert_eq!((), from_value::<()>(&()).unwrap());
+
+        assert!(from_value::<u8>(&300).is_err());
+        assert!(from_value::<bool>(&1).is_err());
+    }
+
+    #[test]
+    fn build_number_text() {
+        assert_eq!(u128::MAX, from_value::<u128>(&u128::MAX).unwrap());
+        assert_eq!(i128::MIN, from_value::<i128>(&i128::MIN).unwrap());
+        assert_eq!(u128::MAX as f64, from_value::<f64>(&u128::MAX).unwrap());
+    }
+
+    #[test]
+    fn build_option() {
+        assert_eq!(Some(42), from_value::<Option<i32>>(&Some(42)).unwrap());
+        assert_eq!(None, from_value::<Option<i32>>(&None::<i32>).unwrap());
+        assert_eq!(Some(42), from_value::<Option<i32>>(&42).unwrap());
+        assert_eq!(
+            Some(Some(42)),
+            from_value::<Option<Option<i32>>>(&Some(Some(42))).unwrap()
+        );
+    }
+
+    #[test]
+    fn build_array_tuple() {
+        assert_eq!([1, 2, 3], from_value::<[u8; 3]>(&[1, 2, 3]).unwrap());
+        assert_eq!(
+            (1, true, Some('a')),
+            from_value::<(i32, bool, Option<char>)>(&(1, true, Some('a'))).unwrap()
+        );
+
+        assert!(from_value::<[u8; 3]>(&[1, 2]).is_err());
+        assert!(from_value::<(i32, bool)>(&(1,)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn build_collections() {
+        use crate::std::{collections::BTreeMap, string::String, vec, vec::Vec};
+
+        assert_eq!(
+            String::from("a string"),
+            from_value::<String>("a string").unwrap()
+        );
+
+        assert_eq!(
+            vec![vec![1, 2], vec![3]],
+            from_value::<Vec<Vec<i32>>>(&vec![vec![1, 2], vec![3]]).unwrap()
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(String::from("a"), vec![Some(1)]);
+        map.insert(String::from("b"), vec![None]);
+
+        assert_eq!(
+            map,
+            from_value::<BTreeMap<String, Vec<Option<i32>>>>(&map).unwrap()
+        );
+    }
+
+    #[test]
+    fn label_matcher() {
+        let labels = ["a", "ab", "b"];
+
+        let mut matcher = LabelMatcher::new();
+        matcher.fragment(&labels, "a");
+        assert_eq!(Some(0), matcher.finish(&labels));
+        matcher.fragment(&labels, "b");
+        assert_eq!(Some(1), matcher.finish(&labels));
+        matcher.fragment(&labels, "c");
+        assert_eq!(None, matcher.finish(&labels));
+
+        let mut matcher = LabelMatcher::new();
+        matcher.fragment(&labels, "");
+        assert_eq!(None, matcher.finish(&labels));
+        matcher.fragment(&labels, "ba");
+        assert_eq!(None, matcher.finish(&labels));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn label_matcher_text() {
+        let labels = ["abc", "b"];
+
+        let mut matcher = LabelMatcher::new();
+        matcher.fragment(&labels, "a");
+        assert_eq!("a", matcher.text(&labels));
+        matcher.fragment(&labels, "bd");
+        matcher.fragment(&labels, "e");
+        assert_eq!(None, matcher.finish(&labels));
+        assert_eq!("abde", matcher.text(&labels));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn build_error_path() {
+        use crate::std::{vec, vec::Vec};
+
+        let err = from_value::<Vec<(u8, u8)>>(&vec![(1, 2), (3, 300)]).unwrap_err();
+
+        assert_eq!(
+            err.path(),
+            &[
+                crate::PathSegment::Index(Index::new(1)),
+                crate::PathSegment::Index(Index::new(1))
+            ]
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    build::{unexpected, Compound, FromValue, Handled, NestedBuilder, Slot, Token},
+    std::iter::Extend,
+    Error, Result, Stream,
+};
+
+/**
+The state for a builder of a mapping of keys to values.
+
+Maps can be built from maps or records. Record fields are built into keys
+from their labels as text.
+*/
+pub struct MapCompound<M, K: FromValue, V: FromValue> {
+    map: M,
+    key: Slot<K>,
+    value: Slot<V>,
+    pending_key: Option<K>,
+}
+
+impl<M: Default, K: FromValue, V: FromValue> Default for MapCompound<M, K, V> {
+    fn default() -> Self {
+        MapCompound {
+            map: M::default(),
+            key: Slot::new(),
+            value: Slot::new(),
+            pending_key: None,
+        }
+    }
+}
+
+impl<M: Extend<(K, V)>, K: FromValue, V: FromValue> MapCompound<M, K, V> {
+    fn insert(&mut self) -> Result {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| Error::invalid_protocol().with_message("missing map key"))?;
+        let value = self.value.finish()?;
+
+        self.map.extend(Some((key, value)));
+
+        Ok(())
+    }
+}
+
+impl<M: Extend<(K, V)>, K: FromValue, V: FromValue> Compound for MapCompound<M, K, V> {
+    type Output = M;
+
+    fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+        if self.key.is_building() {
+            self.key.nested()
+        } else {
+            self.value.nested()
+        }
+    }
+
+    fn token(&mut self, token: Token) -> Result<Handled> {
+        match token {
+            Token::MapKeyBegin => {
+                self.key.begin()?;
+            }
+            Token::MapKeyEnd => {
+                self.pending_key = Some(self.key.finish()?);
+            }
+            Token::MapValueBegin => {
+                self.value.begin()?;
+            }
+            Token::MapValueEnd | Token::RecordValueEnd(_, _) => {
+                self.insert()?;
+            }
+            Token::RecordValueBegin(_, label) => {
+                self.key
+                    .build_with(|builder| builder.value_computed(label.as_str()))
+                    .map_err(|err| err.at_label(label))?;
+
+                self.pending_key = Some(self.key.finish()?);
+                self.value.begin()?;
+            }
+            Token::MapBegin(_)
+            | Token::MapEnd
+            | Token::RecordBegin(_, _, _, _)
+            | Token::RecordEnd(_, _, _)
+            | Token::TaggedBegin(_, _, _)
+            | Token::TaggedEnd(_, _, _) => (),
+            _ => return Err(unexpected("a map")),
+        }
+
+        Ok(Handled::Consumed)
+    }
+
+    fn finish(self) -> Result<M> {
+        Ok(self.map)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    use crate::{build::CompoundBuilder, std::collections::BTreeMap};
+
+    impl<K: FromValue + Ord, V: FromValue> FromValue for BTreeMap<K, V> {
+        type Builder = CompoundBuilder<MapCompound<BTreeMap<K, V>, K, V>>;
+
+        fn builder() -> Self::Builder {
+            CompoundBuilder::new(MapCompound::default())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_support {
+    use super::*;
+
+    use crate::{
+        build::CompoundBuilder,
+        std::{
+            collections::HashMap,
+            hash::{BuildHasher, Hash},
+        },
+    };
+
+    impl<K: FromValue + Eq + Hash, V: FromValue, H: BuildHasher + Default> FromValue
+        for HashMap<K, V, H>
+    {
+        type Builder = CompoundBuilder<MapCompound<HashMap<K, V, H>, K, V>>;
+
+        fn builder() -> Self::Builder {
+            CompoundBuilder::new(MapCompound::default())
+        }
+    }
+}
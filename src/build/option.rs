@@ -0,0 +1,90 @@
+use crate::{
+    build::{
+        unexpected, Compound, CompoundBuilder, FromValue, Handled, NestedBuilder, Slot, Token,
+    },
+    tags, Error, Result,
+};
+
+/**
+The state for a builder of `Option<T>`.
+
+Options are built from values tagged with [`tags::RUST_OPTION_SOME`] and
+[`tags::RUST_OPTION_NONE`]. Null is also treated as `None` and any other
+value as `Some`.
+*/
+pub struct OptionCompound<T: FromValue> {
+    value: Slot<T>,
+    is_none: bool,
+    is_tagged: bool,
+}
+
+impl<T: FromValue> Default for OptionCompound<T> {
+    fn default() -> Self {
+        OptionCompound {
+            value: Slot::new(),
+            is_none: false,
+            is_tagged: false,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    type Builder = CompoundBuilder<OptionCompound<T>>;
+
+    fn builder() -> Self::Builder {
+        CompoundBuilder::new(OptionCompound::default())
+    }
+}
+
+impl<T: FromValue> OptionCompound<T> {
+    fn is_empty(&self) -> bool {
+        !self.is_none && !self.is_tagged && !self.value.is_building()
+    }
+}
+
+impl<T: FromValue> Compound for OptionCompound<T> {
+    type Output = Option<T>;
+
+    fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+        self.value.nested()
+    }
+
+    fn token(&mut self, token: Token) -> Result<Handled> {
+        match token {
+            Token::TaggedBegin(Some(&tags::RUST_OPTION_SOME), _, _) if self.is_empty() => {
+                self.is_tagged = true;
+                self.value.begin()?;
+
+                Ok(Handled::Consumed)
+            }
+            Token::TaggedEnd(Some(&tags::RUST_OPTION_SOME), _, _) if self.is_tagged => {
+                self.value.end()?;
+
+                Ok(Handled::Consumed)
+            }
+            Token::Tag(Some(&tags::RUST_OPTION_NONE), _, _) | Token::Null if self.is_empty() => {
+                self.is_none = true;
+
+                Ok(Handled::Consumed)
+            }
+            _ if self.is_empty() => {
+                self.value.begin()?;
+
+                Ok(Handled::Forward)
+            }
+            _ => Err(unexpected("an option")),
+        }
+    }
+
+    fn finish(mut self) -> Result<Option<T>> {
+        if self.is_none {
+            return Ok(None);
+        }
+
+        if self.is_empty() {
+            return Err(Error::invalid_value().with_message("missing value"));
+        }
+
+        self.value.finish().map(Some)
+    }
+}
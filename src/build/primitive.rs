@@ -0,0 +1,555 @@
+use crate::{
+    build::{unexpected, Builder, FromValue},
+    std::{convert::TryInto, str},
+    tags, Error, Index, Label, Result, Stream, Tag,
+};
+
+/**
+A builder for `bool`.
+*/
+#[derive(Debug, Default)]
+pub struct BoolBuilder {
+    value: Option<bool>,
+}
+
+impl FromValue for bool {
+    type Builder = BoolBuilder;
+
+    fn builder() -> Self::Builder {
+        BoolBuilder::default()
+    }
+}
+
+impl<'sval> Stream<'sval> for BoolBuilder {
+    fn null(&mut self) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn bool(&mut self, value: bool) -> Result {
+        set(&mut self.value, value)
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn text_fragment_computed(&mut self, _: &str) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn text_end(&mut self) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn i64(&mut self, _: i64) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn f64(&mut self, _: f64) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        Err(unexpected("a boolean"))
+    }
+
+    fn seq_end(&mut self) -> Result {
+        Err(unexpected("a boolean"))
+    }
+}
+
+impl Builder for BoolBuilder {
+    type Output = bool;
+
+    fn finish(self) -> Result<bool> {
+        self.value.ok_or_else(missing)
+    }
+}
+
+/**
+A builder for `()`.
+
+Units can be built from either a [`tags::RUST_UNIT`] tag or null.
+*/
+#[derive(Debug, Default)]
+pub struct UnitBuilder {
+    value: Option<()>,
+}
+
+impl FromValue for () {
+    type Builder = UnitBuilder;
+
+    fn builder() -> Self::Builder {
+        UnitBuilder::default()
+    }
+}
+
+impl<'sval> Stream<'sval> for UnitBuilder {
+    fn tag(&mut self, tag: Option<&Tag>, _: Option<&Label>, _: Option<&Index>) -> Result {
+        match tag {
+            Some(&tags::RUST_UNIT) | None => set(&mut self.value, ()),
+            _ => Err(unexpected("a unit")),
+        }
+    }
+
+    fn null(&mut self) -> Result {
+        set(&mut self.value, ())
+    }
+
+    fn bool(&mut self, _: bool) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn text_fragment_computed(&mut self, _: &str) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn text_end(&mut self) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn i64(&mut self, _: i64) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn f64(&mut self, _: f64) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        Err(unexpected("a unit"))
+    }
+
+    fn seq_end(&mut self) -> Result {
+        Err(unexpected("a unit"))
+    }
+}
+
+impl Builder for UnitBuilder {
+    type Output = ();
+
+    fn finish(self) -> Result {
+        self.value.ok_or_else(missing)
+    }
+}
+
+/**
+A builder for `char`.
+
+Characters are built from text containing exactly one character.
+*/
+#[derive(Debug, Default)]
+pub struct CharBuilder {
+    value: Option<char>,
+    is_text: bool,
+}
+
+impl FromValue for char {
+    type Builder = CharBuilder;
+
+    fn builder() -> Self::Builder {
+        CharBuilder::default()
+    }
+}
+
+impl<'sval> Stream<'sval> for CharBuilder {
+    fn null(&mut self) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn bool(&mut self, _: bool) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> Result {
+        self.is_text = true;
+
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+        if !self.is_text {
+            return Err(unexpected("a character"));
+        }
+
+        for c in fragment.chars() {
+            set(&mut self.value, c)
+                .map_err(|_| Error::invalid_value().with_message("expected a single character"))?;
+        }
+
+        Ok(())
+    }
+
+    fn text_end(&mut self) -> Result {
+        self.is_text = false;
+
+        Ok(())
+    }
+
+    fn i64(&mut self, _: i64) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn f64(&mut self, _: f64) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        Err(unexpected("a character"))
+    }
+
+    fn seq_end(&mut self) -> Result {
+        Err(unexpected("a character"))
+    }
+}
+
+impl Builder for CharBuilder {
+    type Output = char;
+
+    fn finish(self) -> Result<char> {
+        self.value.ok_or_else(missing)
+    }
+}
+
+/**
+A builder for integers and binary floating points.
+
+Numbers can be built from any other number that fits, or from text
+tagged with [`tags::NUMBER`].
+*/
+#[derive(Debug)]
+pub struct NumberBuilder<T> {
+    value: Option<T>,
+    is_number: bool,
+    text: Option<NumberText>,
+}
+
+impl<T> Default for NumberBuilder<T> {
+    fn default() -> Self {
+        NumberBuilder {
+            value: None,
+            is_number: false,
+            text: None,
+        }
+    }
+}
+
+impl<T> NumberBuilder<T> {
+    fn text_begin(&mut self, expected: &str) -> Result {
+        if !self.is_number {
+            return Err(unexpected(expected));
+        }
+
+        self.text = Some(NumberText::new());
+
+        Ok(())
+    }
+
+    fn text_fragment(&mut self, fragment: &str, expected: &str) -> Result {
+        match self.text {
+            Some(ref mut text) => text.push(fragment),
+            None => Err(unexpected(expected)),
+        }
+    }
+
+    fn tagged_begin(&mut self, tag: Option<&Tag>) {
+        if let Some(&tags::NUMBER) = tag {
+            self.is_number = true;
+        }
+    }
+
+    fn tagged_end(&mut self, tag: Option<&Tag>) {
+        if let Some(&tags::NUMBER) = tag {
+            self.is_number = false;
+        }
+    }
+}
+
+// Numbers streamed as text are buffered into a fixed-size array
+// This is enough for any 128bit integer, or any float in its shortest form
+#[derive(Debug)]
+struct NumberText {
+    buf: [u8; 128],
+    len: usize,
+}
+
+impl NumberText {
+    fn new() -> Self {
+        NumberText {
+            buf: [0; 128],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, fragment: &str) -> Result {
+        let end = self.len + fragment.len();
+
+        if end > self.buf.len() {
+            return Err(Error::invalid_value().with_message("the number is too long"));
+        }
+
+        self.buf[self.len..end].copy_from_slice(fragment.as_bytes());
+        self.len = end;
+
+        Ok(())
+    }
+
+    fn as_str(&self) -> Result<&str> {
+        str::from_utf8(&self.buf[..self.len]).map_err(|_| Error::invalid_value())
+    }
+}
+
+macro_rules! number_builder_common {
+    ($ty:ident) => {
+        impl FromValue for $ty {
+            type Builder = NumberBuilder<$ty>;
+
+            fn builder() -> Self::Builder {
+                NumberBuilder::default()
+            }
+        }
+
+        impl Builder for NumberBuilder<$ty> {
+            type Output = $ty;
+
+            fn finish(self) -> Result<$ty> {
+                self.value.ok_or_else(missing)
+            }
+        }
+    };
+}
+
+macro_rules! number_builder_stream_common {
+    ($ty:ident, $expected:expr) => {
+        fn null(&mut self) -> Result {
+            Err(unexpected($expected))
+        }
+
+        fn bool(&mut self, _: bool) -> Result {
+            Err(unexpected($expected))
+        }
+
+        fn text_begin(&mut self, _: Option<usize>) -> Result {
+            NumberBuilder::text_begin(self, $expected)
+        }
+
+        fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+            NumberBuilder::text_fragment(self, fragment, $expected)
+        }
+
+        fn text_end(&mut self) -> Result {
+            let text = self.text.take().ok_or_else(|| unexpected($expected))?;
+
+            let value = text
+                .as_str()?
+                .parse::<$ty>()
+                .map_err(|_| Error::invalid_value().with_message(concat!("invalid ", $expected)))?;
+
+            set(&mut self.value, value)
+        }
+
+        fn tagged_begin(
+            &mut self,
+            tag: Option<&Tag>,
+            _: Option<&Label>,
+            _: Option<&Index>,
+        ) -> Result {
+            NumberBuilder::tagged_begin(self, tag);
+
+            Ok(())
+        }
+
+        fn tagged_end(
+            &mut self,
+            tag: Option<&Tag>,
+            _: Option<&Label>,
+            _: Option<&Index>,
+        ) -> Result {
+            NumberBuilder::tagged_end(self, tag);
+
+            Ok(())
+        }
+
+        fn seq_begin(&mut self, _: Option<usize>) -> Result {
+            Err(unexpected($expected))
+        }
+
+        fn seq_value_begin(&mut self) -> Result {
+            Err(unexpected($expected))
+        }
+
+        fn seq_value_end(&mut self) -> Result {
+            Err(unexpected($expected))
+        }
+
+        fn seq_end(&mut self) -> Result {
+            Err(unexpected($expected))
+        }
+    };
+}
+
+macro_rules! int_builder {
+    ($($ty:ident,)*) => {
+        $(
+            number_builder_common!($ty);
+
+            impl<'sval> Stream<'sval> for NumberBuilder<$ty> {
+                number_builder_stream_common!($ty, "an integer");
+
+                fn u8(&mut self, value: u8) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn u16(&mut self, value: u16) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn u32(&mut self, value: u32) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn u64(&mut self, value: u64) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn u128(&mut self, value: u128) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn i8(&mut self, value: i8) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn i16(&mut self, value: i16) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn i32(&mut self, value: i32) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn i64(&mut self, value: i64) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn i128(&mut self, value: i128) -> Result {
+                    set(&mut self.value, value.try_into().map_err(|_| out_of_range())?)
+                }
+
+                fn f64(&mut self, _: f64) -> Result {
+                    Err(unexpected("an integer"))
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! float_builder {
+    ($($ty:ident,)*) => {
+        $(
+            number_builder_common!($ty);
+
+            impl<'sval> Stream<'sval> for NumberBuilder<$ty> {
+                number_builder_stream_common!($ty, "a number");
+
+                fn u8(&mut self, value: u8) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn u16(&mut self, value: u16) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn u32(&mut self, value: u32) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn u64(&mut self, value: u64) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn u128(&mut self, value: u128) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn i8(&mut self, value: i8) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn i16(&mut self, value: i16) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn i32(&mut self, value: i32) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn i64(&mut self, value: i64) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn i128(&mut self, value: i128) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn f32(&mut self, value: f32) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+
+                fn f64(&mut self, value: f64) -> Result {
+                    set(&mut self.value, value as $ty)
+                }
+            }
+        )*
+    };
+}
+
+int_builder!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128,);
+
+float_builder!(f32, f64,);
+
+fn set<T>(slot: &mut Option<T>, value: T) -> Result {
+    if slot.is_some() {
+        return Err(Error::invalid_protocol().with_message("the value was already given"));
+    }
+
+    *slot = Some(value);
+
+    Ok(())
+}
+
+fn missing() -> Error {
+    Error::invalid_value().with_message("missing value")
+}
+
+fn out_of_range() -> Error {
+    Error::invalid_value().with_message("the number is out of range")
+}
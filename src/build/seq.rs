@@ -0,0 +1,446 @@
+use crate::{
+    build::{
+        unexpected, Compound, CompoundBuilder, FromValue, Handled, NestedBuilder, Slot, Token,
+    },
+    std::iter::Extend,
+    Error, Index, Result, Stream,
+};
+
+/**
+The state for a builder of a collection of values.
+
+Collections can be built from sequences, tuples, or binary data.
+*/
+pub struct SeqCompound<C, T: FromValue> {
+    collection: C,
+    value: Slot<T>,
+    index: usize,
+}
+
+impl<C: Default, T: FromValue> Default for SeqCompound<C, T> {
+    fn default() -> Self {
+        SeqCompound {
+            collection: C::default(),
+            value: Slot::new(),
+            index: 0,
+        }
+    }
+}
+
+impl<C: Extend<T>, T: FromValue> SeqCompound<C, T> {
+    fn push(&mut self, value: T) {
+        self.collection.extend(Some(value));
+        self.index += 1;
+    }
+}
+
+impl<C: Extend<T>, T: FromValue> Compound for SeqCompound<C, T> {
+    type Output = C;
+
+    fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+        self.value.nested()
+    }
+
+    fn token(&mut self, token: Token) -> Result<Handled> {
+        match token {
+            Token::SeqValueBegin | Token::TupleValueBegin(_, _) => {
+                self.value.begin()?;
+            }
+            Token::SeqValueEnd | Token::TupleValueEnd(_, _) => {
+                let value = self.value.finish()?;
+                self.push(value);
+            }
+            Token::BinaryFragment(fragment) => {
+                for byte in fragment {
+                    self.value.build_with(|builder| builder.u8(*byte))?;
+
+                    let value = self.value.finish()?;
+                    self.push(value);
+                }
+            }
+            Token::SeqBegin(_)
+            | Token::SeqEnd
+            | Token::TupleBegin(_, _, _, _)
+            | Token::TupleEnd(_, _, _)
+            | Token::BinaryBegin(_)
+            | Token::BinaryEnd
+            | Token::TaggedBegin(_, _, _)
+            | Token::TaggedEnd(_, _, _) => (),
+            _ => return Err(unexpected("a sequence")),
+        }
+
+        Ok(Handled::Consumed)
+    }
+
+    fn nested_error(&self, err: Error) -> Error {
+        err.at_index(&Index::new(self.index))
+    }
+
+    fn finish(self) -> Result<C> {
+        Ok(self.collection)
+    }
+}
+
+/**
+The state for a builder of an array of values.
+*/
+pub struct ArrayCompound<T: FromValue, const N: usize> {
+    values: [Option<T>; N],
+    value: Slot<T>,
+    index: usize,
+}
+
+impl<T: FromValue, const N: usize> Default for ArrayCompound<T, N> {
+    fn default() -> Self {
+        ArrayCompound {
+            values: [(); N].map(|_| None),
+            value: Slot::new(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: FromValue, const N: usize> FromValue for [T; N] {
+    type Builder = CompoundBuilder<ArrayCompound<T, N>>;
+
+    fn builder() -> Self::Builder {
+        CompoundBuilder::new(ArrayCompound::default())
+    }
+}
+
+impl<T: FromValue, const N: usize> ArrayCompound<T, N> {
+    fn push(&mut self, value: T) -> Result {
+        let slot = self
+            .values
+            .get_mut(self.index)
+            .ok_or_else(|| Error::invalid_value().with_message("too many values for the array"))?;
+
+        *slot = Some(value);
+        self.index += 1;
+
+        Ok(())
+    }
+}
+
+impl<T: FromValue, const N: usize> Compound for ArrayCompound<T, N> {
+    type Output = [T; N];
+
+    fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+        self.value.nested()
+    }
+
+    fn token(&mut self, token: Token) -> Result<Handled> {
+        match token {
+            Token::SeqValueBegin | Token::TupleValueBegin(_, _) => {
+                self.value.begin()?;
+            }
+            Token::SeqValueEnd | Token::TupleValueEnd(_, _) => {
+                let value = self.value.finish()?;
+                self.push(value)?;
+            }
+            Token::BinaryFragment(fragment) => {
+                for byte in fragment {
+                    self.value.build_with(|builder| builder.u8(*byte))?;
+
+                    let value = self.value.finish()?;
+                    self.push(value)?;
+                }
+            }
+            Token::SeqBegin(_)
+            | Token::SeqEnd
+            | Token::TupleBegin(_, _, _, _)
+            | Token::TupleEnd(_, _, _)
+            | Token::BinaryBegin(_)
+            | Token::BinaryEnd
+            | Token::TaggedBegin(_, _, _)
+            | Token::TaggedEnd(_, _, _) => (),
+            _ => return Err(unexpected("an array")),
+        }
+
+        Ok(Handled::Consumed)
+    }
+
+    fn nested_error(&self, err: Error) -> Error {
+        err.at_index(&Index::new(self.index))
+    }
+
+    fn finish(self) -> Result<[T; N]> {
+        if self.index != N {
+            return Err(Error::invalid_value().with_message("not enough values for the array"));
+        }
+
+        // Every value is guaranteed to be present by the check above
+        Ok(self.values.map(|value| value.unwrap()))
+    }
+}
+
+/**
+The state for a builder of a tuple.
+
+The `S` parameter is a tuple of [`Slot`]s, one for each value in the tuple.
+*/
+pub struct TupleCompound<S> {
+    slots: S,
+    index: usize,
+}
+
+macro_rules! tuple {
+    ($(
+        $len:expr => ( $(self.$i:tt: $ty:ident,)+ ),
+    )+) => {
+        $(
+            impl<$($ty: FromValue),+> FromValue for ($($ty,)+) {
+                type Builder = CompoundBuilder<TupleCompound<($(Slot<$ty>,)+)>>;
+
+                fn builder() -> Self::Builder {
+                    CompoundBuilder::new(TupleCompound {
+                        slots: ($(Slot::<$ty>::new(),)+),
+                        index: 0,
+                    })
+                }
+            }
+
+            impl<$($ty: FromValue),+> Compound for TupleCompound<($(Slot<$ty>,)+)> {
+                type Output = ($($ty,)+);
+
+                fn nested(&mut self) -> Option<&mut dyn NestedBuilder> {
+                    match self.index {
+                        $(
+                            $i => self.slots.$i.nested(),
+                        )+
+                        _ => None,
+                    }
+                }
+
+                fn token(&mut self, token: Token) -> Result<Handled> {
+                    match token {
+                        Token::SeqValueBegin | Token::TupleValueBegin(_, _) => match self.index {
+                            $(
+                                $i => self.slots.$i.begin()?,
+                            )+
+                            _ => return Err(Error::invalid_value().with_message(concat!("expected a tuple with ", $len, " values"))),
+                        },
+                        Token::SeqValueEnd | Token::TupleValueEnd(_, _) => {
+                            match self.index {
+                                $(
+                                    $i => self.slots.$i.end()?,
+                                )+
+                                _ => return Err(unexpected("a tuple")),
+                            }
+
+                            self.index += 1;
+                        }
+                        Token::SeqBegin(_)
+                        | Token::SeqEnd
+                        | Token::TupleBegin(_, _, _, _)
+                        | Token::TupleEnd(_, _, _)
+                        | Token::TaggedBegin(_, _, _)
+                        | Token::TaggedEnd(_, _, _) => (),
+                        _ => return Err(unexpected("a tuple")),
+                    }
+
+                    Ok(Handled::Consumed)
+                }
+
+                fn nested_error(&self, err: Error) -> Error {
+                    err.at_index(&Index::new(self.index))
+                }
+
+                fn finish(mut self) -> Result<Self::Output> {
+                    Ok(($(
+                        self.slots.$i.take().ok_or_else(|| {
+                            Error::invalid_value()
+                                .with_message(concat!("expected a tuple with ", $len, " values"))
+                        })?,
+                    )+))
+                }
+            }
+        )+
+    }
+}
+
+tuple! {
+    1 => (
+        self.0: T0,
+    ),
+    2 => (
+        self.0: T0,
+        self.1: T1,
+    ),
+    3 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+    ),
+    4 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+    ),
+    5 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+    ),
+    6 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+    ),
+    7 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+    ),
+    8 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+    ),
+    9 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+    ),
+    10 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+    ),
+    11 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+    ),
+    12 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+        self.11: T11,
+    ),
+    13 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+        self.11: T11,
+        self.12: T12,
+    ),
+    14 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+        self.11: T11,
+        self.12: T12,
+        self.13: T13,
+    ),
+    15 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+        self.11: T11,
+        self.12: T12,
+        self.13: T13,
+        self.14: T14,
+    ),
+    16 => (
+        self.0: T0,
+        self.1: T1,
+        self.2: T2,
+        self.3: T3,
+        self.4: T4,
+        self.5: T5,
+        self.6: T6,
+        self.7: T7,
+        self.8: T8,
+        self.9: T9,
+        self.10: T10,
+        self.11: T11,
+        self.12: T12,
+        self.13: T13,
+        self.14: T14,
+        self.15: T15,
+    ),
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    use crate::std::vec::Vec;
+
+    impl<T: FromValue> FromValue for Vec<T> {
+        type Builder = CompoundBuilder<SeqCompound<Vec<T>, T>>;
+
+        fn builder() -> Self::Builder {
+            CompoundBuilder::new(SeqCompound::default())
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    build::{unexpected, Builder, FromValue},
+    std::string::String,
+    Error, Result, Stream,
+};
+
+/**
+A builder for `String`.
+*/
+#[derive(Debug, Default)]
+pub struct StringBuilder {
+    value: Option<String>,
+    is_text: bool,
+}
+
+impl FromValue for String {
+    type Builder = StringBuilder;
+
+    fn builder() -> Self::Builder {
+        StringBuilder::default()
+    }
+}
+
+impl<'sval> Stream<'sval> for StringBuilder {
+    fn null(&mut self) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn bool(&mut self, _: bool) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        if self.value.is_some() {
+            return Err(Error::invalid_protocol().with_message("the value was already given"));
+        }
+
+        self.value = Some(String::with_capacity(num_bytes_hint.unwrap_or(0)));
+        self.is_text = true;
+
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+        match self.value {
+            Some(ref mut value) if self.is_text => {
+                value.push_str(fragment);
+
+                Ok(())
+            }
+            _ => Err(unexpected("text")),
+        }
+    }
+
+    fn text_end(&mut self) -> Result {
+        self.is_text = false;
+
+        Ok(())
+    }
+
+    fn i64(&mut self, _: i64) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn f64(&mut self, _: f64) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        Err(unexpected("text"))
+    }
+
+    fn seq_end(&mut self) -> Result {
+        Err(unexpected("text"))
+    }
+}
+
+impl Builder for StringBuilder {
+    type Output = String;
+
+    fn finish(self) -> Result<String> {
+        match self.value {
+            Some(value) if !self.is_text => Ok(value),
+            _ => Err(Error::invalid_value().with_message("missing value")),
+        }
+    }
+}
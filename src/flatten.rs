@@ -0,0 +1,650 @@
+use crate::{Error, Index, Label, Result, Stream, Tag, Value};
+
+#[cfg(feature = "alloc")]
+use crate::std::string::String;
+
+/**
+Stream the entries of a record or map into a record that's already been started.
+
+The value is streamed through a [`Flatten`] that forwards its entries to `stream`.
+*/
+pub fn flatten<'sval>(
+    stream: &mut (impl Stream<'sval> + ?Sized),
+    value: &'sval (impl Value + ?Sized),
+) -> Result {
+    Flatten::new(stream).value(value)
+}
+
+/**
+A stream that strips the framing from a record or map so its entries can be streamed inside another record.
+
+`Flatten` is used to implement `#[sval(flatten)]` in derived records. It expects to be given a single
+value after its wrapped stream has already received a `record_begin`. The fields of a record
+are forwarded as-is. The entries of a map are forwarded as record fields, with their keys as labels.
+The `record_begin` and `map_begin` of the flattened value, and their `num_entries` hints, aren't forwarded.
+The record being flattened into should use a `num_entries` hint of `None`, because the number of
+entries in the flattened value isn't known upfront.
+
+Tagged values, like `Some`, are flattened into their contents. Null values and standalone tags,
+like `None` or `()`, are flattened into no entries at all. Any other kind of value fails with an
+[`ErrorKind::Unsupported`](crate::ErrorKind::Unsupported) error.
+
+Map keys must be text. Without the `alloc` feature, keys must also be streamed as a single
+fragment that's borrowed for `'sval`.
+*/
+pub struct Flatten<'sval, S> {
+    stream: S,
+    depth: usize,
+    is_key: bool,
+    key: Option<Key<'sval>>,
+}
+
+enum Key<'sval> {
+    Empty,
+    Borrowed(&'sval str),
+    #[cfg(feature = "alloc")]
+    Owned(String),
+}
+
+impl<'sval, S> Flatten<'sval, S> {
+    /**
+    Wrap a stream, forwarding the entries of the value it receives.
+    */
+    pub fn new(stream: S) -> Self {
+        Flatten {
+            stream,
+            depth: 0,
+            is_key: false,
+            key: None,
+        }
+    }
+
+    /**
+    Get the underlying stream.
+    */
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    fn forward(&mut self, f: impl FnOnce(&mut S) -> Result) -> Result {
+        if self.is_key {
+            Err(invalid_key())
+        } else if self.depth > 0 {
+            f(&mut self.stream)
+        } else {
+            Err(unsupported())
+        }
+    }
+
+    fn forward_begin(&mut self, f: impl FnOnce(&mut S) -> Result) -> Result {
+        self.forward(f)?;
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    fn forward_end(&mut self, f: impl FnOnce(&mut S) -> Result) -> Result {
+        self.forward(f)?;
+        self.depth -= 1;
+
+        Ok(())
+    }
+
+    fn is_root(&self) -> bool {
+        self.depth == 0 && !self.is_key
+    }
+
+    fn push_key(&mut self, fragment: &'sval str) -> Result {
+        match self.key {
+            Some(Key::Empty) => {
+                self.key = Some(Key::Borrowed(fragment));
+
+                Ok(())
+            }
+            _ => self.push_key_computed(fragment),
+        }
+    }
+
+    fn push_key_computed(&mut self, fragment: &str) -> Result {
+        #[cfg(feature = "alloc")]
+        {
+            match self.key.take() {
+                Some(Key::Empty) | None => self.key = Some(Key::Owned(fragment.into())),
+                Some(Key::Borrowed(key)) => {
+                    let mut key = String::from(key);
+                    key.push_str(fragment);
+
+                    self.key = Some(Key::Owned(key));
+                }
+                Some(Key::Owned(mut key)) => {
+                    key.push_str(fragment);
+
+                    self.key = Some(Key::Owned(key));
+                }
+            }
+
+            Ok(())
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = fragment;
+
+            Err(Error::unsupported()
+                .with_message("flattened map keys must be a single borrowed fragment"))
+        }
+    }
+}
+
+impl<'sval, S: Stream<'sval>> Stream<'sval> for Flatten<'sval, S> {
+    fn value<V: Value + ?Sized>(&mut self, v: &'sval V) -> Result {
+        if self.depth > 0 && !self.is_key {
+            self.stream.value(v)
+        } else {
+            v.stream(self)
+        }
+    }
+
+    fn value_computed<V: Value + ?Sized>(&mut self, v: &V) -> Result {
+        if self.depth > 0 && !self.is_key {
+            self.stream.value_computed(v)
+        } else {
+            crate::stream::stream_computed(self, v)
+        }
+    }
+
+    fn null(&mut self) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward(|stream| stream.null())
+        }
+    }
+
+    fn bool(&mut self, value: bool) -> Result {
+        self.forward(|stream| stream.bool(value))
+    }
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        if self.is_key {
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.text_begin(num_bytes_hint))
+        }
+    }
+
+    fn text_fragment(&mut self, fragment: &'sval str) -> Result {
+        if self.is_key {
+            self.push_key(fragment)
+        } else {
+            self.forward(|stream| stream.text_fragment(fragment))
+        }
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+        if self.is_key {
+            self.push_key_computed(fragment)
+        } else {
+            self.forward(|stream| stream.text_fragment_computed(fragment))
+        }
+    }
+
+    fn text_end(&mut self) -> Result {
+        if self.is_key {
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.text_end())
+        }
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        self.forward_begin(|stream| stream.binary_begin(num_bytes_hint))
+    }
+
+    fn binary_fragment(&mut self, fragment: &'sval [u8]) -> Result {
+        self.forward(|stream| stream.binary_fragment(fragment))
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> Result {
+        self.forward(|stream| stream.binary_fragment_computed(fragment))
+    }
+
+    fn binary_end(&mut self) -> Result {
+        self.forward_end(|stream| stream.binary_end())
+    }
+
+    fn u8(&mut self, value: u8) -> Result {
+        self.forward(|stream| stream.u8(value))
+    }
+
+    fn u16(&mut self, value: u16) -> Result {
+        self.forward(|stream| stream.u16(value))
+    }
+
+    fn u32(&mut self, value: u32) -> Result {
+        self.forward(|stream| stream.u32(value))
+    }
+
+    fn u64(&mut self, value: u64) -> Result {
+        self.forward(|stream| stream.u64(value))
+    }
+
+    fn u128(&mut self, value: u128) -> Result {
+        self.forward(|stream| stream.u128(value))
+    }
+
+    fn i8(&mut self, value: i8) -> Result {
+        self.forward(|stream| stream.i8(value))
+    }
+
+    fn i16(&mut self, value: i16) -> Result {
+        self.forward(|stream| stream.i16(value))
+    }
+
+    fn i32(&mut self, value: i32) -> Result {
+        self.forward(|stream| stream.i32(value))
+    }
+
+    fn i64(&mut self, value: i64) -> Result {
+        self.forward(|stream| stream.i64(value))
+    }
+
+    fn i128(&mut self, value: i128) -> Result {
+        self.forward(|stream| stream.i128(value))
+    }
+
+    fn f32(&mut self, value: f32) -> Result {
+        self.forward(|stream| stream.f32(value))
+    }
+
+    fn f64(&mut self, value: f64) -> Result {
+        self.forward(|stream| stream.f64(value))
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.map_begin(num_entries_hint))
+        }
+    }
+
+    fn map_key_begin(&mut self) -> Result {
+        if self.is_root() {
+            self.is_key = true;
+            self.key = Some(Key::Empty);
+
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.map_key_begin())
+        }
+    }
+
+    fn map_key_end(&mut self) -> Result {
+        if self.is_key && self.depth == 0 {
+            self.is_key = false;
+
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.map_key_end())
+        }
+    }
+
+    fn map_value_begin(&mut self) -> Result {
+        if self.is_root() {
+            self.stream
+                .record_value_begin(None, &key_label(&self.key))?;
+            self.depth += 1;
+
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.map_value_begin())
+        }
+    }
+
+    fn map_value_end(&mut self) -> Result {
+        if self.depth == 1 && self.key.is_some() {
+            self.stream.record_value_end(None, &key_label(&self.key))?;
+            self.depth -= 1;
+            self.key = None;
+
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.map_value_end())
+        }
+    }
+
+    fn map_end(&mut self) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.map_end())
+        }
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        self.forward_begin(|stream| stream.seq_begin(num_entries_hint))
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        self.forward_begin(|stream| stream.seq_value_begin())
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        self.forward_end(|stream| stream.seq_value_end())
+    }
+
+    fn seq_end(&mut self) -> Result {
+        self.forward_end(|stream| stream.seq_end())
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.forward_begin(|stream| stream.enum_begin(tag, label, index))
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.forward_end(|stream| stream.enum_end(tag, label, index))
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        if self.depth == 0 {
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.tagged_begin(tag, label, index))
+        }
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        if self.depth == 0 {
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.tagged_end(tag, label, index))
+        }
+    }
+
+    fn tag(&mut self, tag: Option<&Tag>, label: Option<&Label>, index: Option<&Index>) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward(|stream| stream.tag(tag, label, index))
+        }
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries_hint: Option<usize>,
+    ) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.record_begin(tag, label, index, num_entries_hint))
+        }
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        if self.is_root() {
+            self.stream.record_value_begin(tag, label)?;
+            self.depth += 1;
+
+            Ok(())
+        } else {
+            self.forward_begin(|stream| stream.record_value_begin(tag, label))
+        }
+    }
+
+    fn record_value_end(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        self.forward_end(|stream| stream.record_value_end(tag, label))
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        if self.is_root() {
+            Ok(())
+        } else {
+            self.forward_end(|stream| stream.record_end(tag, label, index))
+        }
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries_hint: Option<usize>,
+    ) -> Result {
+        self.forward_begin(|stream| stream.tuple_begin(tag, label, index, num_entries_hint))
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        self.forward_begin(|stream| stream.tuple_value_begin(tag, index))
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        self.forward_end(|stream| stream.tuple_value_end(tag, index))
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.forward_end(|stream| stream.tuple_end(tag, label, index))
+    }
+}
+
+fn key_label<'a>(key: &'a Option<Key>) -> Label<'a> {
+    match key {
+        Some(Key::Borrowed(key)) => Label::from_computed(key),
+        #[cfg(feature = "alloc")]
+        Some(Key::Owned(key)) => Label::from_computed(key),
+        Some(Key::Empty) | None => Label::from_computed(""),
+    }
+}
+
+fn unsupported() -> Error {
+    Error::unsupported().with_message("only records and maps can be flattened")
+}
+
+fn invalid_key() -> Error {
+    Error::unsupported().with_message("flattened map keys must be text")
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    use crate::{
+        std::{collections::BTreeMap, string::String, vec, vec::Vec},
+        ErrorKind, Validate,
+    };
+
+    struct Outer<V>(V);
+
+    impl<V: Value> Value for Outer<V> {
+        fn stream<'sval, S: Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> Result {
+            stream.record_begin(None, Some(&Label::new("Outer")), None, None)?;
+
+            stream.record_value_begin(None, &Label::new("a"))?;
+            stream.i32(1)?;
+            stream.record_value_end(None, &Label::new("a"))?;
+
+            flatten(stream, &self.0)?;
+
+            stream.record_end(None, Some(&Label::new("Outer")), None)
+        }
+    }
+
+    struct Inner;
+
+    impl Value for Inner {
+        fn stream<'sval, S: Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> Result {
+            stream.record_begin(None, Some(&Label::new("Inner")), None, Some(1))?;
+
+            stream.record_value_begin(None, &Label::new("b"))?;
+            stream.seq_begin(None)?;
+            stream.seq_value_begin()?;
+            stream.map_begin(None)?;
+            stream.map_key_begin()?;
+            stream.value("c")?;
+            stream.map_key_end()?;
+            stream.map_value_begin()?;
+            stream.null()?;
+            stream.map_value_end()?;
+            stream.map_end()?;
+            stream.seq_value_end()?;
+            stream.seq_end()?;
+            stream.record_value_end(None, &Label::new("b"))?;
+
+            stream.record_end(None, Some(&Label::new("Inner")), None)
+        }
+    }
+
+    // Collects the labels of the outermost record
+    #[derive(Default)]
+    struct Labels {
+        labels: Vec<String>,
+        depth: usize,
+    }
+
+    impl<'sval> Stream<'sval> for Labels {
+        fn record_value_begin(&mut self, _: Option<&Tag>, label: &Label) -> Result {
+            if self.depth == 0 {
+                self.labels.push(label.as_str().into());
+            }
+            self.depth += 1;
+
+            Ok(())
+        }
+
+        fn record_value_end(&mut self, _: Option<&Tag>, _: &Label) -> Result {
+            self.depth -= 1;
+
+            Ok(())
+        }
+
+        fn null(&mut self) -> Result {
+            Ok(())
+        }
+
+        fn bool(&mut self, _: bool) -> Result {
+            Ok(())
+        }
+
+        fn text_begin(&mut self, _: Option<usize>) -> Result {
+            Ok(())
+        }
+
+        fn text_fragment_computed(&mut self, _: &str) -> Result {
+            Ok(())
+        }
+
+        fn text_end(&mut self) -> Result {
+            Ok(())
+        }
+
+        fn i64(&mut self, _: i64) -> Result {
+            Ok(())
+        }
+
+        fn f64(&mut self, _: f64) -> Result {
+            Ok(())
+        }
+
+        fn seq_begin(&mut self, _: Option<usize>) -> Result {
+            Ok(())
+        }
+
+        fn seq_value_begin(&mut self) -> Result {
+            Ok(())
+        }
+
+        fn seq_value_end(&mut self) -> Result {
+            Ok(())
+        }
+
+        fn seq_end(&mut self) -> Result {
+            Ok(())
+        }
+    }
+
+    fn labels(v: impl Value) -> Result<Vec<String>> {
+        let mut stream = Validate::new(Labels::default());
+        v.stream(&mut stream)?;
+
+        Ok(stream.finish()?.labels)
+    }
+
+    #[test]
+    fn flatten_record() {
+        assert_eq!(vec!["a", "b"], labels(Outer(Inner)).unwrap());
+        assert_eq!(vec!["a", "a", "b"], labels(Outer(Outer(Inner))).unwrap());
+    }
+
+    #[test]
+    fn flatten_map() {
+        let mut map = BTreeMap::new();
+        map.insert("b", 1);
+        map.insert("c", 2);
+
+        assert_eq!(vec!["a", "b", "c"], labels(Outer(&map)).unwrap());
+
+        let mut map = BTreeMap::new();
+        map.insert(String::from("b"), Inner);
+
+        assert_eq!(vec!["a", "b"], labels(Outer(&map)).unwrap());
+    }
+
+    #[test]
+    fn flatten_option() {
+        assert_eq!(vec!["a", "b"], labels(Outer(Some(Inner))).unwrap());
+        assert_eq!(vec!["a"], labels(Outer(None::<Inner>)).unwrap());
+    }
+
+    #[test]
+    fn flatten_unsupported() {
+        for err in [
+            labels(Outer(1)).unwrap_err(),
+            labels(Outer(vec![1])).unwrap_err(),
+            labels(Outer((1, 2))).unwrap_err(),
+        ] {
+            assert_eq!(ErrorKind::Unsupported, err.kind());
+            assert_eq!(
+                Some("only records and maps can be flattened"),
+                err.message()
+            );
+        }
+
+        let mut map = BTreeMap::new();
+        map.insert(1, 1);
+
+        let err = labels(Outer(map)).unwrap_err();
+        assert_eq!(Some("flattened map keys must be text"), err.message());
+    }
+}
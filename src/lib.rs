@@ -42,6 +42,22 @@ Many standard types in Rust implement the `Value` trait.
 [`Stream`] is a trait for data formats and visitors to implement that observes
 the structure of _values_. `Stream` is like `serde`'s `Serializer`.
 
+# The `FromValue` trait
+
+[`FromValue`] is a trait for data types to implement that can be built from
+the tokens of a stream. `FromValue` is like `serde`'s `Deserialize`.
+Types that implement `FromValue` provide a [`Builder`], which is a `Stream`
+that assembles the value.
+
+Many standard types in Rust implement the `FromValue` trait.
+
+`FromValue` can be derived when the `derive` feature is enabled.
+
+# The `Describe` trait
+
+[`Describe`] is a trait for data types to implement that describes the shape
+of their values up-front as a [`Schema`], without needing an instance to stream.
+
 # Data-model
 
 `sval`'s data-model is defined by the [`Stream`] trait. It includes:
@@ -67,6 +83,17 @@ tags include text that encodes RFC3339 timestamps or RFC4122 UUIDs.
 
 The [`tags`] module contains built-in tags. Other libraries may define their own tags too.
 
+# Validation
+
+When the `alloc` feature is enabled, [`Validate`] can wrap a stream to check the tokens
+it receives are well-formed. This is useful for testing hand-written `Value` implementations.
+
+# Flattening
+
+[`Flatten`] can wrap a stream that's inside a record to stream the fields of another
+record or the entries of a map directly into it. This is what `#[sval(flatten)]` uses
+in derived records.
+
 # Buffering
 
 Complex or arbitrarily-sized values like strings, maps, and sequences can all be
@@ -93,20 +120,37 @@ extern crate core;
 mod std {
     pub use crate::{
         alloc::{borrow, boxed, collections, string, vec},
-        core::{convert, fmt, hash, marker, mem, ops, result, str},
+        core::{convert, fmt, hash, iter, marker, mem, ops, result, slice, str},
     };
 }
 
 #[cfg(all(not(feature = "alloc"), not(feature = "std")))]
 extern crate core as std;
 
+pub mod build;
 mod data;
+mod flatten;
 mod result;
+pub mod schema;
 mod stream;
 mod value;
 
+#[cfg(feature = "alloc")]
+mod validate;
+
 #[doc(inline)]
-pub use self::{data::*, result::*, stream::*, value::*};
+pub use self::{
+    build::{from_value, Builder, FromValue},
+    data::*,
+    flatten::{flatten, Flatten},
+    result::*,
+    schema::{Describe, Schema},
+    stream::*,
+    value::*,
+};
+
+#[cfg(feature = "alloc")]
+pub use self::validate::{validate, Validate};
 
 /**
 A generic streaming result.
@@ -1,28 +1,428 @@
-use crate::std::fmt;
+use crate::{
+    std::{fmt, iter, slice},
+    Index, Label,
+};
+
+#[cfg(feature = "alloc")]
+use crate::std::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 
 /**
 An error encountered while streaming a value.
 
-Errors don't capture details of failures, that responsibility is left
-to the stream to surface.
+When the `alloc` feature is enabled, errors can carry an [`ErrorKind`],
+a message, and the path of labels, indexes, and keys down to the value
+that failed. Without an allocator, errors are zero-sized and any
+additional details are discarded.
 */
 #[derive(Debug)]
-pub struct Error(());
+pub struct Error {
+    #[cfg(feature = "alloc")]
+    detail: Option<Box<ErrorDetail>>,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+struct ErrorDetail {
+    kind: ErrorKind,
+    message: Option<String>,
+    // Segments are stored innermost first, in the order they're attached
+    // as the error unwinds, and reversed when they're read
+    path: Vec<PathSegment>,
+    #[cfg(feature = "std")]
+    source: Option<Box<dyn crate::std::error::Error + Send + Sync + 'static>>,
+}
+
+/**
+The kind of failure an [`Error`] represents.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /**
+    A generic failure without any more specific kind.
+    */
+    Other,
+    /**
+    The stream doesn't support the kind of value it was given.
+    */
+    Unsupported,
+    /**
+    Tokens were passed to the stream in an invalid order.
+    */
+    InvalidProtocol,
+    /**
+    The value had the right shape, but its contents weren't valid.
+
+    This includes missing or unknown fields and numbers that are out of range.
+    */
+    InvalidValue,
+    /**
+    A failure described entirely by its message.
+    */
+    Custom,
+    /**
+    A failure reading or writing some underlying input or output.
+    */
+    Io,
+}
+
+/**
+A segment in the path from the root of a value down to the value that failed.
+*/
+#[derive(Debug, PartialEq, Eq)]
+pub enum PathSegment {
+    /**
+    A field in a record.
+    */
+    Label(Label<'static>),
+    /**
+    A value in a tuple or sequence.
+    */
+    Index(Index),
+    /**
+    A value in a map, identified by the text of its key.
+    */
+    Key(Label<'static>),
+}
 
 impl Error {
     /**
     Create a new error.
 
-    More detailed diagnostic information will need to be stored elsewhere.
+    More detailed diagnostic information can be attached with methods
+    like [`Error::with_message`] and [`Error::at_label`].
     */
     pub fn new() -> Self {
-        Error(())
+        Error {
+            #[cfg(feature = "alloc")]
+            detail: None,
+        }
+    }
+
+    /**
+    Create a new error with a specific kind.
+    */
+    pub fn from_kind(kind: ErrorKind) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            let mut err = Error::new();
+            err.detail_mut().kind = kind;
+            err
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = kind;
+            Error::new()
+        }
+    }
+
+    /**
+    Create a new error for a value the stream doesn't support.
+    */
+    pub fn unsupported() -> Self {
+        Error::from_kind(ErrorKind::Unsupported)
+    }
+
+    /**
+    Create a new error for tokens passed in an invalid order.
+    */
+    pub fn invalid_protocol() -> Self {
+        Error::from_kind(ErrorKind::InvalidProtocol)
+    }
+
+    /**
+    Create a new error for a value whose contents weren't valid.
+    */
+    pub fn invalid_value() -> Self {
+        Error::from_kind(ErrorKind::InvalidValue)
+    }
+
+    /**
+    Create a new error from a custom message.
+    */
+    pub fn custom(message: impl fmt::Display) -> Self {
+        Error::from_kind(ErrorKind::Custom).with_message(message)
+    }
+
+    /**
+    Attach a message to this error.
+    */
+    pub fn with_message(self, message: impl fmt::Display) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            let mut err = self;
+            err.detail_mut().message = Some(message.to_string());
+            err
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = message;
+            self
+        }
+    }
+
+    /**
+    Mark this error as occurring within the record field with the given label.
+
+    Errors unwind from the value that failed out towards the root, so each
+    call adds a segment to the start of the path.
+    */
+    pub fn at_label(self, label: &Label) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            self.at(PathSegment::Label(label.to_owned()))
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = label;
+            self
+        }
+    }
+
+    /**
+    Mark this error as occurring within the tuple or sequence value at the given index.
+    */
+    pub fn at_index(self, index: &Index) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            self.at(PathSegment::Index(index.clone()))
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = index;
+            self
+        }
+    }
+
+    /**
+    Mark this error as occurring within the map value with the given key.
+    */
+    pub fn at_key(self, key: &str) -> Self {
+        #[cfg(feature = "alloc")]
+        {
+            self.at(PathSegment::Key(Label::from_owned(key.into())))
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            let _ = key;
+            self
+        }
+    }
+
+    /**
+    Get the kind of failure this error represents.
+
+    Errors without a specific kind, including all errors when the `alloc`
+    feature isn't enabled, return [`ErrorKind::Other`].
+    */
+    pub fn kind(&self) -> ErrorKind {
+        #[cfg(feature = "alloc")]
+        {
+            self.detail
+                .as_ref()
+                .map(|detail| detail.kind)
+                .unwrap_or(ErrorKind::Other)
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            ErrorKind::Other
+        }
+    }
+
+    /**
+    Get the message attached to this error, if there is one.
+    */
+    pub fn message(&self) -> Option<&str> {
+        #[cfg(feature = "alloc")]
+        {
+            self.detail
+                .as_ref()
+                .and_then(|detail| detail.message.as_deref())
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            None
+        }
+    }
+
+    /**
+    Get the path from the root of the value down to the value that failed.
+
+    The path will be empty if no stream attached it, or if the `alloc`
+    feature isn't enabled.
+    */
+    pub fn path(&self) -> ErrorPath<'_> {
+        #[cfg(feature = "alloc")]
+        {
+            ErrorPath {
+                segments: self
+                    .detail
+                    .as_ref()
+                    .map(|detail| &*detail.path)
+                    .unwrap_or(&[])
+                    .iter()
+                    .rev(),
+            }
+        }
+        #[cfg(not(feature = "alloc"))]
+        {
+            ErrorPath {
+                segments: [].iter().rev(),
+            }
+        }
+    }
+}
+
+/**
+The path from the root of a value down to the value that failed.
+
+This type is returned by [`Error::path`] and iterates over its segments from the root.
+*/
+#[derive(Clone)]
+pub struct ErrorPath<'a> {
+    segments: iter::Rev<slice::Iter<'a, PathSegment>>,
+}
+
+impl<'a> ErrorPath<'a> {
+    /**
+    Whether the path has any segments.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.segments.len() == 0
+    }
+}
+
+impl<'a> Iterator for ErrorPath<'a> {
+    type Item = &'a PathSegment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.segments.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.segments.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for ErrorPath<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.segments.next_back()
+    }
+}
+
+impl<'a> ExactSizeIterator for ErrorPath<'a> {}
+
+impl<'a> fmt::Debug for ErrorPath<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a> PartialEq<[PathSegment]> for ErrorPath<'a> {
+    fn eq(&self, other: &[PathSegment]) -> bool {
+        self.clone().eq(other.iter())
+    }
+}
+
+impl<'a, 'b> PartialEq<&'b [PathSegment]> for ErrorPath<'a> {
+    fn eq(&self, other: &&'b [PathSegment]) -> bool {
+        *self == **other
+    }
+}
+
+impl<'a, 'b, const N: usize> PartialEq<&'b [PathSegment; N]> for ErrorPath<'a> {
+    fn eq(&self, other: &&'b [PathSegment; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+impl Default for Error {
+    fn default() -> Self {
+        Error::new()
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "failed to stream data")
+        if let Some(message) = self.message() {
+            write!(f, "{}", message)?;
+        } else {
+            write!(f, "{}", self.kind())?;
+        }
+
+        let mut path = self.path();
+
+        if let Some(first) = path.next() {
+            write!(f, " at `{}", first)?;
+
+            for segment in path {
+                write!(f, "{}", segment)?;
+            }
+
+            write!(f, "`")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Error {
+        Error::from_kind(ErrorKind::Io).with_message("failed to write formatted data")
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::Other => write!(f, "failed to stream data"),
+            ErrorKind::Unsupported => write!(f, "the stream doesn't support this value"),
+            ErrorKind::InvalidProtocol => write!(f, "the stream was called in an invalid order"),
+            ErrorKind::InvalidValue => write!(f, "the value was invalid"),
+            ErrorKind::Custom => write!(f, "a custom error occurred"),
+            ErrorKind::Io => write!(f, "failed to read or write data"),
+        }
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSegment::Label(label) => write!(f, ".{}", label.as_str()),
+            PathSegment::Index(index) => match index.to_usize() {
+                Some(index) => write!(f, "[{}]", index),
+                None => write!(f, "[?]"),
+            },
+            PathSegment::Key(key) => write!(f, "[{:?}]", key.as_str()),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    impl Error {
+        pub(super) fn detail_mut(&mut self) -> &mut ErrorDetail {
+            self.detail.get_or_insert_with(|| {
+                Box::new(ErrorDetail {
+                    kind: ErrorKind::Other,
+                    message: None,
+                    path: Vec::new(),
+                    #[cfg(feature = "std")]
+                    source: None,
+                })
+            })
+        }
+
+        pub(super) fn at(mut self, segment: PathSegment) -> Self {
+            self.detail_mut().path.push(segment);
+            self
+        }
     }
 }
 
@@ -30,16 +430,79 @@ impl fmt::Display for Error {
 mod std_support {
     use super::*;
 
-    use crate::std::error;
+    use crate::std::{error, io};
+
+    impl Error {
+        /**
+        Attach an underlying cause to this error.
+        */
+        pub fn with_source(mut self, source: impl error::Error + Send + Sync + 'static) -> Self {
+            self.detail_mut().source = Some(Box::new(source));
+            self
+        }
+    }
+
+    impl From<io::Error> for Error {
+        fn from(err: io::Error) -> Error {
+            Error::from_kind(ErrorKind::Io).with_source(err)
+        }
+    }
 
-    impl error::Error for Error {}
+    impl error::Error for Error {
+        fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+            self.detail
+                .as_ref()
+                .and_then(|detail| detail.source.as_ref())
+                .map(|source| &**source as &(dyn error::Error + 'static))
+        }
+    }
 }
 
 /**
 A streaming result with a generic failure.
 
-More detailed diagnostic information will need to be stored elsewhere.
+More detailed diagnostic information can be attached to the error.
 */
 pub fn error<T>() -> crate::Result<T> {
     Err(Error::new())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn error_detail() {
+        let err = Error::unsupported()
+            .with_message("map keys must be text")
+            .at_key("b")
+            .at_index(&Index::new(1))
+            .at_label(&Label::new("a"));
+
+        assert_eq!(ErrorKind::Unsupported, err.kind());
+        assert_eq!(Some("map keys must be text"), err.message());
+        assert_eq!(
+            err.path(),
+            &[
+                PathSegment::Label(Label::new("a")),
+                PathSegment::Index(Index::new(1)),
+                PathSegment::Key(Label::new("b")),
+            ]
+        );
+
+        assert_eq!(
+            "map keys must be text at `.a[1][\"b\"]`",
+            format!("{}", err)
+        );
+    }
+
+    #[test]
+    fn error_generic() {
+        let err = Error::new();
+
+        assert_eq!(ErrorKind::Other, err.kind());
+        assert_eq!(None, err.message());
+        assert!(err.path().is_empty());
+    }
+}
@@ -0,0 +1,546 @@
+/*!
+Describe the shape of values without an instance of them.
+
+A [`Schema`] is a static description of the tokens a [`Value`] will produce
+when it's streamed. Types that implement [`Describe`] can return their schema
+up-front, which is useful for documentation, generating schemas for other
+formats, or binary formats that need to know the layout of a value ahead of time.
+
+Schemas mirror the [`Stream`] API, so the tags, labels, and indexes they carry
+are the same ones a value will pass to a stream. Compound schemas refer to the
+schemas of the values they contain through `'static` references, so they can
+be built in constants:
+
+```
+use sval::{schema::{Describe, Field, Schema}, Label};
+
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Describe for Point {
+    const SCHEMA: &'static Schema = &Schema::Record {
+        tag: None,
+        label: Some(Label::new("Point")),
+        index: None,
+        fields: &[
+            Field::record(Label::new("x"), i32::SCHEMA),
+            Field::record(Label::new("y"), i32::SCHEMA),
+        ],
+    };
+}
+```
+
+Schemas can't describe recursive types, because a type's schema would need to
+contain itself. Those types can use [`Schema::Any`] in place of the recursive value.
+
+[`Value`]: crate::Value
+[`Stream`]: crate::Stream
+*/
+
+use crate::{tags, Index, Label, Tag};
+
+/**
+A type that can describe the shape of its values.
+
+`Describe` can be derived when the `derive` feature is enabled.
+*/
+pub trait Describe {
+    /**
+    The schema for values of this type.
+    */
+    const SCHEMA: &'static Schema;
+}
+
+/**
+The static shape of a value.
+
+Each variant corresponds to a kind of value in `sval`'s data-model.
+*/
+#[derive(Debug, PartialEq)]
+pub enum Schema {
+    /**
+    Any value.
+
+    This schema is used when the shape of a value can't be known up-front.
+    */
+    Any,
+    /**
+    A null value.
+    */
+    Null,
+    /**
+    A boolean value.
+    */
+    Bool,
+    /**
+    A text blob.
+    */
+    Text,
+    /**
+    A binary blob.
+    */
+    Binary,
+    /**
+    An unsigned 8bit integer.
+    */
+    U8,
+    /**
+    An unsigned 16bit integer.
+    */
+    U16,
+    /**
+    An unsigned 32bit integer.
+    */
+    U32,
+    /**
+    An unsigned 64bit integer.
+    */
+    U64,
+    /**
+    An unsigned 128bit integer.
+    */
+    U128,
+    /**
+    A signed 8bit integer.
+    */
+    I8,
+    /**
+    A signed 16bit integer.
+    */
+    I16,
+    /**
+    A signed 32bit integer.
+    */
+    I32,
+    /**
+    A signed 64bit integer.
+    */
+    I64,
+    /**
+    A signed 128bit integer.
+    */
+    I128,
+    /**
+    A 32bit binary floating point.
+    */
+    F32,
+    /**
+    A 64bit binary floating point.
+    */
+    F64,
+    /**
+    A map of keys to values.
+    */
+    Map {
+        /**
+        The schema of each key.
+        */
+        key: &'static Schema,
+        /**
+        The schema of each value.
+        */
+        value: &'static Schema,
+    },
+    /**
+    A sequence of values.
+    */
+    Seq(&'static Schema),
+    /**
+    A Rust `Option`.
+
+    The value is either a [`tags::RUST_OPTION_NONE`] tag, or a value tagged with [`tags::RUST_OPTION_SOME`].
+    */
+    Option(&'static Schema),
+    /**
+    A record with labelled fields.
+    */
+    Record {
+        /**
+        The tag of the record.
+        */
+        tag: Option<Tag>,
+        /**
+        The label of the record.
+        */
+        label: Option<Label<'static>>,
+        /**
+        The index of the record.
+        */
+        index: Option<Index>,
+        /**
+        The fields of the record, in the order they're streamed.
+        */
+        fields: &'static [Field],
+    },
+    /**
+    A tuple with indexed fields.
+    */
+    Tuple {
+        /**
+        The tag of the tuple.
+        */
+        tag: Option<Tag>,
+        /**
+        The label of the tuple.
+        */
+        label: Option<Label<'static>>,
+        /**
+        The index of the tuple.
+        */
+        index: Option<Index>,
+        /**
+        The fields of the tuple, in the order they're streamed.
+        */
+        fields: &'static [Field],
+    },
+    /**
+    An enum where each value is one of a set of variants.
+    */
+    Enum {
+        /**
+        The tag of the enum.
+        */
+        tag: Option<Tag>,
+        /**
+        The label of the enum.
+        */
+        label: Option<Label<'static>>,
+        /**
+        The index of the enum.
+        */
+        index: Option<Index>,
+        /**
+        The schemas of each variant.
+
+        Variants are typically records, tuples, tagged values, or tags.
+        */
+        variants: &'static [Schema],
+    },
+    /**
+    A value with a tag, label, or index.
+    */
+    Tagged {
+        /**
+        The tag of the value.
+        */
+        tag: Option<Tag>,
+        /**
+        The label of the value.
+        */
+        label: Option<Label<'static>>,
+        /**
+        The index of the value.
+        */
+        index: Option<Index>,
+        /**
+        The schema of the tagged value.
+        */
+        value: &'static Schema,
+    },
+    /**
+    A tag without any value.
+    */
+    Tag {
+        /**
+        The tag.
+        */
+        tag: Option<Tag>,
+        /**
+        The label of the tag.
+        */
+        label: Option<Label<'static>>,
+        /**
+        The index of the tag.
+        */
+        index: Option<Index>,
+    },
+}
+
+impl Schema {
+    /**
+    Get the tag of this schema, if it has one.
+    */
+    pub fn tag(&self) -> Option<&Tag> {
+        match self {
+            Schema::Record { tag, .. }
+            | Schema::Tuple { tag, .. }
+            | Schema::Enum { tag, .. }
+            | Schema::Tagged { tag, .. }
+            | Schema::Tag { tag, .. } => tag.as_ref(),
+            _ => None,
+        }
+    }
+
+    /**
+    Get the label of this schema, if it has one.
+    */
+    pub fn label(&self) -> Option<&Label<'static>> {
+        match self {
+            Schema::Record { label, .. }
+            | Schema::Tuple { label, .. }
+            | Schema::Enum { label, .. }
+            | Schema::Tagged { label, .. }
+            | Schema::Tag { label, .. } => label.as_ref(),
+            _ => None,
+        }
+    }
+
+    /**
+    Get the index of this schema, if it has one.
+    */
+    pub fn index(&self) -> Option<&Index> {
+        match self {
+            Schema::Record { index, .. }
+            | Schema::Tuple { index, .. }
+            | Schema::Enum { index, .. }
+            | Schema::Tagged { index, .. }
+            | Schema::Tag { index, .. } => index.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/**
+A field in a record or tuple.
+*/
+#[derive(Debug, PartialEq)]
+pub struct Field {
+    /**
+    The tag of the field.
+    */
+    pub tag: Option<Tag>,
+    /**
+    The label of the field.
+
+    Fields of records always have a label.
+    */
+    pub label: Option<Label<'static>>,
+    /**
+    The index of the field.
+
+    Fields of tuples always have an index.
+    */
+    pub index: Option<Index>,
+    /**
+    The schema of the field's value.
+    */
+    pub value: &'static Schema,
+    /**
+    Whether the field may be left out of its record.
+    */
+    pub optional: bool,
+}
+
+impl Field {
+    /**
+    Create a field for a record.
+    */
+    pub const fn record(label: Label<'static>, value: &'static Schema) -> Self {
+        Field {
+            tag: None,
+            label: Some(label),
+            index: None,
+            value,
+            optional: false,
+        }
+    }
+
+    /**
+    Create a field for a tuple.
+    */
+    pub const fn tuple(index: Index, value: &'static Schema) -> Self {
+        Field {
+            tag: None,
+            label: None,
+            index: Some(index),
+            value,
+            optional: false,
+        }
+    }
+}
+
+macro_rules! primitive {
+    ($($ty:ty => $schema:ident,)*) => {
+        $(
+            impl Describe for $ty {
+                const SCHEMA: &'static Schema = &Schema::$schema;
+            }
+        )*
+    };
+}
+
+primitive!(
+    bool => Bool,
+    char => Text,
+    str => Text,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    f32 => F32,
+    f64 => F64,
+);
+
+impl Describe for () {
+    const SCHEMA: &'static Schema = &Schema::Tag {
+        tag: Some(tags::RUST_UNIT),
+        label: None,
+        index: None,
+    };
+}
+
+impl<T: Describe> Describe for Option<T> {
+    const SCHEMA: &'static Schema = &Schema::Option(T::SCHEMA);
+}
+
+impl<T: Describe> Describe for [T] {
+    const SCHEMA: &'static Schema = &Schema::Seq(T::SCHEMA);
+}
+
+impl<T: Describe, const N: usize> Describe for [T; N] {
+    const SCHEMA: &'static Schema = &Schema::Tagged {
+        tag: Some(tags::CONSTANT_SIZE),
+        label: None,
+        index: None,
+        value: &Schema::Seq(T::SCHEMA),
+    };
+}
+
+impl<T: Describe + ?Sized> Describe for &T {
+    const SCHEMA: &'static Schema = T::SCHEMA;
+}
+
+macro_rules! tuple {
+    ($(
+        ( $($i:tt: $ty:ident,)+ ),
+    )+) => {
+        $(
+            impl<$($ty: Describe),+> Describe for ($($ty,)+) {
+                const SCHEMA: &'static Schema = &Schema::Tuple {
+                    tag: None,
+                    label: None,
+                    index: None,
+                    fields: &[
+                        $(
+                            Field::tuple(Index::new($i), $ty::SCHEMA),
+                        )+
+                    ],
+                };
+            }
+        )+
+    }
+}
+
+tuple! {
+    (0: T0,),
+    (0: T0, 1: T1,),
+    (0: T0, 1: T1, 2: T2,),
+    (0: T0, 1: T1, 2: T2, 3: T3,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10, 11: T11,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10, 11: T11, 12: T12,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10, 11: T11, 12: T12, 13: T13,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10, 11: T11, 12: T12, 13: T13, 14: T14,),
+    (0: T0, 1: T1, 2: T2, 3: T3, 4: T4, 5: T5, 6: T6, 7: T7, 8: T8, 9: T9, 10: T10, 11: T11, 12: T12, 13: T13, 14: T14, 15: T15,),
+}
+
+#[cfg(feature = "alloc")]
+mod alloc_support {
+    use super::*;
+
+    use crate::std::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+
+    impl Describe for String {
+        const SCHEMA: &'static Schema = &Schema::Text;
+    }
+
+    impl<T: Describe + ?Sized> Describe for Box<T> {
+        const SCHEMA: &'static Schema = T::SCHEMA;
+    }
+
+    impl<T: Describe> Describe for Vec<T> {
+        const SCHEMA: &'static Schema = &Schema::Seq(T::SCHEMA);
+    }
+
+    impl<K: Describe, V: Describe> Describe for BTreeMap<K, V> {
+        const SCHEMA: &'static Schema = &Schema::Map {
+            key: K::SCHEMA,
+            value: V::SCHEMA,
+        };
+    }
+}
+
+#[cfg(feature = "std")]
+mod std_support {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    impl<K: Describe, V: Describe, H> Describe for HashMap<K, V, H> {
+        const SCHEMA: &'static Schema = &Schema::Map {
+            key: K::SCHEMA,
+            value: V::SCHEMA,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe_primitive() {
+        assert_eq!(&Schema::Bool, bool::SCHEMA);
+        assert_eq!(&Schema::Text, <&str>::SCHEMA);
+        assert_eq!(&Schema::U128, u128::SCHEMA);
+        assert_eq!(&Schema::F32, f32::SCHEMA);
+        assert_eq!(Some(&tags::RUST_UNIT), <()>::SCHEMA.tag());
+    }
+
+    #[test]
+    fn describe_compound() {
+        assert_eq!(&Schema::Option(&Schema::I32), <Option<i32>>::SCHEMA);
+        assert_eq!(&Schema::Seq(&Schema::U8), <[u8]>::SCHEMA);
+        assert_eq!(Some(&tags::CONSTANT_SIZE), <[u8; 4]>::SCHEMA.tag());
+
+        const TUPLE: &Schema = &Schema::Tuple {
+            tag: None,
+            label: None,
+            index: None,
+            fields: &[
+                Field::tuple(Index::new(0), &Schema::I32),
+                Field::tuple(Index::new(1), &Schema::Option(&Schema::Bool)),
+            ],
+        };
+
+        assert_eq!(TUPLE, <(i32, Option<bool>)>::SCHEMA);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn describe_collections() {
+        use crate::std::{collections::BTreeMap, string::String, vec::Vec};
+
+        assert_eq!(
+            &Schema::Map {
+                key: &Schema::Text,
+                value: &Schema::Seq(&Schema::Option(&Schema::I64)),
+            },
+            <BTreeMap<String, Vec<Option<i64>>>>::SCHEMA
+        );
+    }
+}
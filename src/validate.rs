@@ -0,0 +1,938 @@
+use crate::{
+    std::{fmt, vec::Vec},
+    Error, Index, Label, Result, Stream, Tag, Value,
+};
+
+/**
+Check that a value streams a well-formed sequence of tokens.
+
+The value is streamed through a [`Validate`] that discards its tokens.
+*/
+pub fn validate(value: &(impl Value + ?Sized)) -> Result {
+    let mut stream = Validate::new(Discard);
+
+    value.stream(&mut stream)?;
+
+    stream.finish().map(|_| ())
+}
+
+/**
+A stream that checks the tokens it receives are well-formed before forwarding them to another stream.
+
+Streams generally assume their callers pair tokens like `record_begin` and `record_end`
+correctly, and don't check them. `Validate` keeps track of the values it's inside of and fails
+with an [`ErrorKind::InvalidProtocol`](crate::ErrorKind::InvalidProtocol) error when a token
+is out of place. That includes:
+
+- Tokens that don't belong where they're used, like a text fragment outside of text,
+  or a map value without a key.
+- Ends that don't match their beginnings, like a `record_value_end` with a different
+  label to its `record_value_begin`.
+- Containers that receive more entries than their `num_entries` hint, or text and
+  binary that receive more bytes than their `num_bytes_hint`.
+- Values that contain more or fewer than a single value, like a tagged value with
+  nothing in it, or two values streamed at the root.
+
+`Validate` is meant for checking hand-written [`Value`] implementations in tests or
+debug builds. Errors include the path to the value that failed.
+*/
+pub struct Validate<S> {
+    stream: S,
+    stack: Vec<Frame>,
+    is_root_filled: bool,
+}
+
+struct Meta {
+    tag: Option<Tag>,
+    label: Option<Label<'static>>,
+    index: Option<Index>,
+}
+
+enum Frame {
+    Text {
+        hint: Option<usize>,
+        len: usize,
+    },
+    Binary {
+        hint: Option<usize>,
+        len: usize,
+    },
+    Map {
+        hint: Option<usize>,
+        len: usize,
+        is_value_next: bool,
+    },
+    MapKey {
+        is_filled: bool,
+    },
+    MapValue {
+        is_filled: bool,
+    },
+    Seq {
+        hint: Option<usize>,
+        len: usize,
+    },
+    SeqValue {
+        index: Index,
+        is_filled: bool,
+    },
+    Record {
+        meta: Meta,
+        hint: Option<usize>,
+        len: usize,
+    },
+    RecordValue {
+        tag: Option<Tag>,
+        label: Label<'static>,
+        is_filled: bool,
+    },
+    Tuple {
+        meta: Meta,
+        hint: Option<usize>,
+        len: usize,
+    },
+    TupleValue {
+        tag: Option<Tag>,
+        index: Index,
+        is_filled: bool,
+    },
+    Enum {
+        meta: Meta,
+        is_filled: bool,
+    },
+    Tagged {
+        meta: Meta,
+        is_filled: bool,
+    },
+}
+
+impl<S> Validate<S> {
+    /**
+    Wrap a stream, checking the tokens it receives.
+    */
+    pub fn new(stream: S) -> Self {
+        Validate {
+            stream,
+            stack: Vec::new(),
+            is_root_filled: false,
+        }
+    }
+
+    /**
+    Whether a complete value has been streamed.
+    */
+    pub fn is_complete(&self) -> bool {
+        self.is_root_filled && self.stack.is_empty()
+    }
+
+    /**
+    Get the underlying stream, without checking whether a complete value was streamed.
+    */
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /**
+    Get the underlying stream.
+
+    This method will fail if a complete value hasn't been streamed.
+    */
+    pub fn finish(self) -> Result<S> {
+        if self.is_complete() {
+            Ok(self.stream)
+        } else {
+            Err(self.error(format_args!(
+                "the stream ended early, expected {}",
+                self.expecting()
+            )))
+        }
+    }
+
+    fn value_begin(&mut self, token: &'static str) -> Result {
+        let slot = match self.stack.last_mut() {
+            None => &mut self.is_root_filled,
+            Some(Frame::MapKey { is_filled })
+            | Some(Frame::MapValue { is_filled })
+            | Some(Frame::SeqValue { is_filled, .. })
+            | Some(Frame::RecordValue { is_filled, .. })
+            | Some(Frame::TupleValue { is_filled, .. })
+            | Some(Frame::Enum { is_filled, .. })
+            | Some(Frame::Tagged { is_filled, .. }) => is_filled,
+            _ => return Err(self.unexpected(token)),
+        };
+
+        if *slot {
+            return Err(self.unexpected(token));
+        }
+
+        *slot = true;
+
+        Ok(())
+    }
+
+    fn fragment(&mut self, token: &'static str, fragment_len: usize) -> Result {
+        let exceeded = match (token, self.stack.last_mut()) {
+            ("text_fragment", Some(Frame::Text { hint, len }))
+            | ("binary_fragment", Some(Frame::Binary { hint, len })) => {
+                *len += fragment_len;
+                exceeded(*hint, *len)
+            }
+            _ => return Err(self.unexpected(token)),
+        };
+
+        self.check_hint(token, "num_bytes_hint", exceeded)
+    }
+
+    fn entry_begin(&mut self, token: &'static str) -> Result {
+        let exceeded = match (token, self.stack.last_mut()) {
+            (
+                "map_key_begin",
+                Some(Frame::Map {
+                    hint,
+                    len,
+                    is_value_next: false,
+                }),
+            )
+            | ("seq_value_begin", Some(Frame::Seq { hint, len }))
+            | ("record_value_begin", Some(Frame::Record { hint, len, .. }))
+            | ("tuple_value_begin", Some(Frame::Tuple { hint, len, .. })) => {
+                *len += 1;
+                exceeded(*hint, *len)
+            }
+            _ => return Err(self.unexpected(token)),
+        };
+
+        self.check_hint(token, "num_entries", exceeded)
+    }
+
+    fn check_hint(
+        &self,
+        token: &'static str,
+        hint_name: &'static str,
+        exceeded: Option<usize>,
+    ) -> Result {
+        match exceeded {
+            Some(hint) => Err(self.error(format_args!(
+                "`{}` exceeded the `{}` of {}",
+                token, hint_name, hint
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn is_filled(&self) -> bool {
+        match self.stack.last() {
+            Some(Frame::MapKey { is_filled })
+            | Some(Frame::MapValue { is_filled })
+            | Some(Frame::SeqValue { is_filled, .. })
+            | Some(Frame::RecordValue { is_filled, .. })
+            | Some(Frame::TupleValue { is_filled, .. })
+            | Some(Frame::Enum { is_filled, .. })
+            | Some(Frame::Tagged { is_filled, .. }) => *is_filled,
+            _ => true,
+        }
+    }
+
+    fn set_map_value_next(&mut self, is_value: bool) {
+        if let Some(Frame::Map { is_value_next, .. }) = self.stack.last_mut() {
+            *is_value_next = is_value;
+        }
+    }
+
+    fn check_meta(
+        &self,
+        end: &'static str,
+        begin: &'static str,
+        meta: &Meta,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.check_eq(end, begin, "tag", meta.tag.as_ref(), tag)?;
+        self.check_eq(end, begin, "label", meta.label.as_ref(), label)?;
+        self.check_eq(end, begin, "index", meta.index.as_ref(), index)
+    }
+
+    fn check_eq<T: PartialEq<U> + fmt::Debug, U: fmt::Debug>(
+        &self,
+        end: &'static str,
+        begin: &'static str,
+        name: &'static str,
+        expected: Option<&T>,
+        actual: Option<&U>,
+    ) -> Result {
+        let is_eq = match (expected, actual) {
+            (Some(expected), Some(actual)) => expected == actual,
+            (None, None) => true,
+            _ => false,
+        };
+
+        if is_eq {
+            Ok(())
+        } else {
+            Err(self.error(format_args!(
+                "the {} {:?} of `{}` doesn't match the {} {:?} of `{}`",
+                name, actual, end, name, expected, begin
+            )))
+        }
+    }
+
+    fn unexpected(&self, token: &'static str) -> Error {
+        self.error(format_args!(
+            "unexpected `{}`, expected {}",
+            token,
+            self.expecting()
+        ))
+    }
+
+    fn expecting(&self) -> &'static str {
+        if !self.is_filled() {
+            return match self.stack.last() {
+                Some(Frame::Enum { .. }) => "a variant",
+                _ => "a value",
+            };
+        }
+
+        match self.stack.last() {
+            None if self.is_root_filled => "the end of the stream",
+            None => "a value",
+            Some(Frame::Text { .. }) => "`text_fragment` or `text_end`",
+            Some(Frame::Binary { .. }) => "`binary_fragment` or `binary_end`",
+            Some(Frame::Map {
+                is_value_next: false,
+                ..
+            }) => "`map_key_begin` or `map_end`",
+            Some(Frame::Map {
+                is_value_next: true,
+                ..
+            }) => "`map_value_begin`",
+            Some(Frame::MapKey { .. }) => "`map_key_end`",
+            Some(Frame::MapValue { .. }) => "`map_value_end`",
+            Some(Frame::Seq { .. }) => "`seq_value_begin` or `seq_end`",
+            Some(Frame::SeqValue { .. }) => "`seq_value_end`",
+            Some(Frame::Record { .. }) => "`record_value_begin` or `record_end`",
+            Some(Frame::RecordValue { .. }) => "`record_value_end`",
+            Some(Frame::Tuple { .. }) => "`tuple_value_begin` or `tuple_end`",
+            Some(Frame::TupleValue { .. }) => "`tuple_value_end`",
+            Some(Frame::Enum { .. }) => "`enum_end`",
+            Some(Frame::Tagged { .. }) => "`tagged_end`",
+        }
+    }
+
+    fn error(&self, message: impl fmt::Display) -> Error {
+        let mut err = Error::invalid_protocol().with_message(message);
+
+        // Paths are built from the innermost value outwards
+        for frame in self.stack.iter().rev() {
+            err = match frame {
+                Frame::RecordValue { label, .. } => err.at_label(label),
+                Frame::SeqValue { index, .. } | Frame::TupleValue { index, .. } => {
+                    err.at_index(index)
+                }
+                _ => err,
+            };
+        }
+
+        err
+    }
+}
+
+fn exceeded(hint: Option<usize>, len: usize) -> Option<usize> {
+    hint.filter(|hint| len > *hint)
+}
+
+impl<S> fmt::Debug for Validate<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Validate")
+            .field("depth", &self.stack.len())
+            .field("is_complete", &self.is_complete())
+            .finish()
+    }
+}
+
+impl Meta {
+    fn new(tag: Option<&Tag>, label: Option<&Label>, index: Option<&Index>) -> Self {
+        Meta {
+            tag: tag.cloned(),
+            label: label.map(|label| label.to_owned()),
+            index: index.cloned(),
+        }
+    }
+}
+
+macro_rules! primitive {
+    ($($method:ident($($ty:ty)?),)*) => {
+        $(
+            fn $method(&mut self $(, value: $ty)?) -> Result {
+                self.value_begin(stringify!($method))?;
+                self.stream.$method($(primitive!(@value value $ty))?)
+            }
+        )*
+    };
+    (@value $value:ident $ty:ty) => { $value };
+}
+
+impl<'sval, S: Stream<'sval>> Stream<'sval> for Validate<S> {
+    primitive!(
+        null(),
+        bool(bool),
+        u8(u8),
+        u16(u16),
+        u32(u32),
+        u64(u64),
+        u128(u128),
+        i8(i8),
+        i16(i16),
+        i32(i32),
+        i64(i64),
+        i128(i128),
+        f32(f32),
+        f64(f64),
+    );
+
+    fn text_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        self.value_begin("text_begin")?;
+        self.stack.push(Frame::Text {
+            hint: num_bytes_hint,
+            len: 0,
+        });
+
+        self.stream.text_begin(num_bytes_hint)
+    }
+
+    fn text_fragment(&mut self, fragment: &'sval str) -> Result {
+        self.fragment("text_fragment", fragment.len())?;
+        self.stream.text_fragment(fragment)
+    }
+
+    fn text_fragment_computed(&mut self, fragment: &str) -> Result {
+        self.fragment("text_fragment", fragment.len())?;
+        self.stream.text_fragment_computed(fragment)
+    }
+
+    fn text_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::Text { .. }) => self.stack.pop(),
+            _ => return Err(self.unexpected("text_end")),
+        };
+
+        self.stream.text_end()
+    }
+
+    fn binary_begin(&mut self, num_bytes_hint: Option<usize>) -> Result {
+        self.value_begin("binary_begin")?;
+        self.stack.push(Frame::Binary {
+            hint: num_bytes_hint,
+            len: 0,
+        });
+
+        self.stream.binary_begin(num_bytes_hint)
+    }
+
+    fn binary_fragment(&mut self, fragment: &'sval [u8]) -> Result {
+        self.fragment("binary_fragment", fragment.len())?;
+        self.stream.binary_fragment(fragment)
+    }
+
+    fn binary_fragment_computed(&mut self, fragment: &[u8]) -> Result {
+        self.fragment("binary_fragment", fragment.len())?;
+        self.stream.binary_fragment_computed(fragment)
+    }
+
+    fn binary_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::Binary { .. }) => self.stack.pop(),
+            _ => return Err(self.unexpected("binary_end")),
+        };
+
+        self.stream.binary_end()
+    }
+
+    fn map_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        self.value_begin("map_begin")?;
+        self.stack.push(Frame::Map {
+            hint: num_entries_hint,
+            len: 0,
+            is_value_next: false,
+        });
+
+        self.stream.map_begin(num_entries_hint)
+    }
+
+    fn map_key_begin(&mut self) -> Result {
+        self.entry_begin("map_key_begin")?;
+        self.stack.push(Frame::MapKey { is_filled: false });
+
+        self.stream.map_key_begin()
+    }
+
+    fn map_key_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::MapKey { is_filled: true }) => self.stack.pop(),
+            _ => return Err(self.unexpected("map_key_end")),
+        };
+        self.set_map_value_next(true);
+
+        self.stream.map_key_end()
+    }
+
+    fn map_value_begin(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::Map {
+                is_value_next: true,
+                ..
+            }) => self.stack.push(Frame::MapValue { is_filled: false }),
+            _ => return Err(self.unexpected("map_value_begin")),
+        }
+
+        self.stream.map_value_begin()
+    }
+
+    fn map_value_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::MapValue { is_filled: true }) => self.stack.pop(),
+            _ => return Err(self.unexpected("map_value_end")),
+        };
+        self.set_map_value_next(false);
+
+        self.stream.map_value_end()
+    }
+
+    fn map_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::Map {
+                is_value_next: false,
+                ..
+            }) => self.stack.pop(),
+            _ => return Err(self.unexpected("map_end")),
+        };
+
+        self.stream.map_end()
+    }
+
+    fn seq_begin(&mut self, num_entries_hint: Option<usize>) -> Result {
+        self.value_begin("seq_begin")?;
+        self.stack.push(Frame::Seq {
+            hint: num_entries_hint,
+            len: 0,
+        });
+
+        self.stream.seq_begin(num_entries_hint)
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        self.entry_begin("seq_value_begin")?;
+
+        if let Some(Frame::Seq { len, .. }) = self.stack.last() {
+            let index = Index::new(*len - 1);
+
+            self.stack.push(Frame::SeqValue {
+                index,
+                is_filled: false,
+            });
+        }
+
+        self.stream.seq_value_begin()
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::SeqValue {
+                is_filled: true, ..
+            }) => self.stack.pop(),
+            _ => return Err(self.unexpected("seq_value_end")),
+        };
+
+        self.stream.seq_value_end()
+    }
+
+    fn seq_end(&mut self) -> Result {
+        match self.stack.last() {
+            Some(Frame::Seq { .. }) => self.stack.pop(),
+            _ => return Err(self.unexpected("seq_end")),
+        };
+
+        self.stream.seq_end()
+    }
+
+    fn enum_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.value_begin("enum_begin")?;
+        self.stack.push(Frame::Enum {
+            meta: Meta::new(tag, label, index),
+            is_filled: false,
+        });
+
+        self.stream.enum_begin(tag, label, index)
+    }
+
+    fn enum_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        match self.stack.last() {
+            Some(Frame::Enum {
+                meta,
+                is_filled: true,
+            }) => self.check_meta("enum_end", "enum_begin", meta, tag, label, index)?,
+            _ => return Err(self.unexpected("enum_end")),
+        }
+        self.stack.pop();
+
+        self.stream.enum_end(tag, label, index)
+    }
+
+    fn tagged_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        self.value_begin("tagged_begin")?;
+        self.stack.push(Frame::Tagged {
+            meta: Meta::new(tag, label, index),
+            is_filled: false,
+        });
+
+        self.stream.tagged_begin(tag, label, index)
+    }
+
+    fn tagged_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        match self.stack.last() {
+            Some(Frame::Tagged {
+                meta,
+                is_filled: true,
+            }) => self.check_meta("tagged_end", "tagged_begin", meta, tag, label, index)?,
+            _ => return Err(self.unexpected("tagged_end")),
+        }
+        self.stack.pop();
+
+        self.stream.tagged_end(tag, label, index)
+    }
+
+    fn tag(&mut self, tag: Option<&Tag>, label: Option<&Label>, index: Option<&Index>) -> Result {
+        self.value_begin("tag")?;
+        self.stream.tag(tag, label, index)
+    }
+
+    fn record_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries: Option<usize>,
+    ) -> Result {
+        self.value_begin("record_begin")?;
+        self.stack.push(Frame::Record {
+            meta: Meta::new(tag, label, index),
+            hint: num_entries,
+            len: 0,
+        });
+
+        self.stream.record_begin(tag, label, index, num_entries)
+    }
+
+    fn record_value_begin(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        self.entry_begin("record_value_begin")?;
+        self.stack.push(Frame::RecordValue {
+            tag: tag.cloned(),
+            label: label.to_owned(),
+            is_filled: false,
+        });
+
+        self.stream.record_value_begin(tag, label)
+    }
+
+    fn record_value_end(&mut self, tag: Option<&Tag>, label: &Label) -> Result {
+        match self.stack.last() {
+            Some(Frame::RecordValue {
+                tag: begin_tag,
+                label: begin_label,
+                is_filled: true,
+            }) => {
+                let (end, begin) = ("record_value_end", "record_value_begin");
+
+                self.check_eq(end, begin, "tag", begin_tag.as_ref(), tag)?;
+                self.check_eq(end, begin, "label", Some(begin_label), Some(label))?;
+            }
+            _ => return Err(self.unexpected("record_value_end")),
+        }
+        self.stack.pop();
+
+        self.stream.record_value_end(tag, label)
+    }
+
+    fn record_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        match self.stack.last() {
+            Some(Frame::Record { meta, .. }) => {
+                self.check_meta("record_end", "record_begin", meta, tag, label, index)?
+            }
+            _ => return Err(self.unexpected("record_end")),
+        }
+        self.stack.pop();
+
+        self.stream.record_end(tag, label, index)
+    }
+
+    fn tuple_begin(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+        num_entries: Option<usize>,
+    ) -> Result {
+        self.value_begin("tuple_begin")?;
+        self.stack.push(Frame::Tuple {
+            meta: Meta::new(tag, label, index),
+            hint: num_entries,
+            len: 0,
+        });
+
+        self.stream.tuple_begin(tag, label, index, num_entries)
+    }
+
+    fn tuple_value_begin(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        self.entry_begin("tuple_value_begin")?;
+        self.stack.push(Frame::TupleValue {
+            tag: tag.cloned(),
+            index: index.clone(),
+            is_filled: false,
+        });
+
+        self.stream.tuple_value_begin(tag, index)
+    }
+
+    fn tuple_value_end(&mut self, tag: Option<&Tag>, index: &Index) -> Result {
+        match self.stack.last() {
+            Some(Frame::TupleValue {
+                tag: begin_tag,
+                index: begin_index,
+                is_filled: true,
+            }) => {
+                let (end, begin) = ("tuple_value_end", "tuple_value_begin");
+
+                self.check_eq(end, begin, "tag", begin_tag.as_ref(), tag)?;
+                self.check_eq(end, begin, "index", Some(begin_index), Some(index))?;
+            }
+            _ => return Err(self.unexpected("tuple_value_end")),
+        }
+        self.stack.pop();
+
+        self.stream.tuple_value_end(tag, index)
+    }
+
+    fn tuple_end(
+        &mut self,
+        tag: Option<&Tag>,
+        label: Option<&Label>,
+        index: Option<&Index>,
+    ) -> Result {
+        match self.stack.last() {
+            Some(Frame::Tuple { meta, .. }) => {
+                self.check_meta("tuple_end", "tuple_begin", meta, tag, label, index)?
+            }
+            _ => return Err(self.unexpected("tuple_end")),
+        }
+        self.stack.pop();
+
+        self.stream.tuple_end(tag, label, index)
+    }
+}
+
+/**
+A stream that discards its tokens.
+*/
+#[derive(Debug)]
+struct Discard;
+
+impl<'sval> Stream<'sval> for Discard {
+    fn null(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn bool(&mut self, _: bool) -> Result {
+        Ok(())
+    }
+
+    fn text_begin(&mut self, _: Option<usize>) -> Result {
+        Ok(())
+    }
+
+    fn text_fragment_computed(&mut self, _: &str) -> Result {
+        Ok(())
+    }
+
+    fn text_end(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn i64(&mut self, _: i64) -> Result {
+        Ok(())
+    }
+
+    fn f64(&mut self, _: f64) -> Result {
+        Ok(())
+    }
+
+    fn seq_begin(&mut self, _: Option<usize>) -> Result {
+        Ok(())
+    }
+
+    fn seq_value_begin(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn seq_value_end(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn seq_end(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{std::vec, ErrorKind, PathSegment};
+
+    fn assert_invalid(message: &str, f: impl FnOnce(&mut Validate<Discard>) -> Result) -> Error {
+        let mut stream = Validate::new(Discard);
+
+        let err = f(&mut stream).unwrap_err();
+
+        assert_eq!(ErrorKind::InvalidProtocol, err.kind());
+        assert_eq!(Some(message), err.message());
+
+        err
+    }
+
+    #[test]
+    fn validate_valid() {
+        validate(&42).unwrap();
+        validate("a string").unwrap();
+        validate(&()).unwrap();
+        validate(&(1, Some(true), None::<i32>, [1.5, 2.5])).unwrap();
+        validate(&vec![vec!['a'], vec![]]).unwrap();
+
+        let mut map = crate::std::collections::BTreeMap::new();
+        map.insert("a", (u128::MAX, i128::MIN));
+        validate(&map).unwrap();
+    }
+
+    #[test]
+    fn validate_out_of_place() {
+        assert_invalid("unexpected `text_fragment`, expected a value", |stream| {
+            stream.text_fragment("a")
+        });
+
+        assert_invalid(
+            "unexpected `map_value_begin`, expected `map_key_begin` or `map_end`",
+            |stream| {
+                stream.map_begin(None)?;
+                stream.map_value_begin()
+            },
+        );
+
+        assert_invalid(
+            "unexpected `i32`, expected `text_fragment` or `text_end`",
+            |stream| {
+                stream.text_begin(None)?;
+                stream.i32(1)
+            },
+        );
+
+        assert_invalid("unexpected `tagged_end`, expected a value", |stream| {
+            stream.tagged_begin(None, None, None)?;
+            stream.tagged_end(None, None, None)
+        });
+
+        assert_invalid(
+            "unexpected `bool`, expected the end of the stream",
+            |stream| {
+                stream.bool(true)?;
+                stream.bool(false)
+            },
+        );
+
+        let mut stream = Validate::new(Discard);
+        stream.seq_begin(None).unwrap();
+        stream.seq_value_begin().unwrap();
+        stream.null().unwrap();
+
+        assert_eq!(
+            Some("the stream ended early, expected `seq_value_end`"),
+            stream.finish().unwrap_err().message()
+        );
+    }
+
+    #[test]
+    fn validate_mismatched() {
+        let err = assert_invalid(
+            "the label Some(Label(\"b\")) of `record_value_end` doesn't match the label Some(Label(\"a\")) of `record_value_begin`",
+            |stream| {
+                stream.record_begin(None, Some(&Label::new("Record")), None, Some(1))?;
+                stream.record_value_begin(None, &Label::new("a"))?;
+                stream.i32(1)?;
+                stream.record_value_end(None, &Label::new("b"))
+            },
+        );
+        assert_eq!(err.path(), &[PathSegment::Label(Label::new("a"))]);
+
+        assert_invalid(
+            "the index Some(Index(1)) of `enum_end` doesn't match the index None of `enum_begin`",
+            |stream| {
+                stream.enum_begin(None, Some(&Label::new("Enum")), None)?;
+                stream.tag(None, Some(&Label::new("A")), Some(&Index::new(0)))?;
+                stream.enum_end(None, Some(&Label::new("Enum")), Some(&Index::new(1)))
+            },
+        );
+    }
+
+    #[test]
+    fn validate_hints() {
+        let err = assert_invalid(
+            "`seq_value_begin` exceeded the `num_entries` of 1",
+            |stream| {
+                stream.tuple_begin(None, None, None, Some(1))?;
+                stream.tuple_value_begin(None, &Index::new(0))?;
+                stream.seq_begin(Some(1))?;
+
+                for _ in 0..2 {
+                    stream.seq_value_begin()?;
+                    stream.u8(1)?;
+                    stream.seq_value_end()?;
+                }
+
+                Ok(())
+            },
+        );
+        assert_eq!(err.path(), &[PathSegment::Index(Index::new(0))]);
+
+        assert_invalid(
+            "`text_fragment` exceeded the `num_bytes_hint` of 3",
+            |stream| {
+                stream.text_begin(Some(3))?;
+                stream.text_fragment("ab")?;
+                stream.text_fragment("cd")
+            },
+        );
+    }
+}
@@ -0,0 +1,649 @@
+/*!
+A conformance suite for [`sval::Stream`] implementations.
+
+The suite is a set of named [`Case`]s that each stream a specific sequence of tokens.
+Together they cover every token a stream can receive and the ways they combine:
+empty and nested collections, borrowed and computed fragments, records in maps,
+tagged values, and enum variants of every shape.
+
+Use [`Suite::assert`] to check that a stream accepts every case without failing,
+or [`Suite::run`] to also make assertions on what the stream produced.
+*/
+
+use std::panic::{self, AssertUnwindSafe};
+
+use sval::tags;
+
+use crate::{
+    panic_message, replay,
+    Token::{self, *},
+};
+
+/**
+A named sequence of tokens in the conformance suite.
+
+A `Case` is also a [`sval::Value`] that streams its tokens.
+*/
+#[derive(Debug)]
+pub struct Case {
+    name: &'static str,
+    tokens: Vec<Token<'static>>,
+}
+
+impl Case {
+    /**
+    The name of the case.
+    */
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /**
+    The tokens the case streams.
+    */
+    pub fn tokens(&self) -> &[Token<'static>] {
+        &self.tokens
+    }
+}
+
+impl sval::Value for Case {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        replay(stream, &self.tokens)
+    }
+}
+
+/**
+A set of [`Case`]s to drive a [`sval::Stream`] through.
+*/
+#[derive(Debug)]
+pub struct Suite {
+    cases: Vec<Case>,
+}
+
+impl Default for Suite {
+    fn default() -> Self {
+        Suite::new()
+    }
+}
+
+impl Suite {
+    /**
+    Create a suite with every case.
+    */
+    pub fn new() -> Self {
+        Suite { cases: cases() }
+    }
+
+    /**
+    The cases in the suite.
+    */
+    pub fn cases(&self) -> &[Case] {
+        &self.cases
+    }
+
+    /**
+    Remove a case from the suite.
+
+    This is for streams that deliberately don't support some kinds of values.
+    It panics if there's no case with the given name.
+    */
+    pub fn skip(mut self, name: &str) -> Self {
+        let len = self.cases.len();
+        self.cases.retain(|case| case.name != name);
+
+        assert_ne!(len, self.cases.len(), "there is no case named `{}`", name);
+
+        self
+    }
+
+    /**
+    Stream every case into a fresh stream, and pass it to `check` along with its case.
+
+    The tokens are checked by a [`sval::Validate`] on their way to the stream.
+    This method panics if the stream fails or panics on any case, naming the case that failed.
+    */
+    pub fn run<S: sval::Stream<'static>>(
+        &self,
+        mut stream: impl FnMut() -> S,
+        mut check: impl FnMut(&Case, S),
+    ) {
+        for case in &self.cases {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut validate = sval::Validate::new(stream());
+
+                replay(&mut validate, &case.tokens)?;
+                check(case, validate.finish()?);
+
+                Ok::<(), sval::Error>(())
+            }));
+
+            match result {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => panic!("case `{}` failed: {}", case.name, e),
+                Err(panic) => panic!("case `{}` panicked: {}", case.name, panic_message(&*panic)),
+            }
+        }
+    }
+
+    /**
+    Stream every case into a fresh stream, checking only that none of them fail.
+    */
+    pub fn assert<S: sval::Stream<'static>>(&self, stream: impl FnMut() -> S) {
+        self.run(stream, |_, _| ())
+    }
+}
+
+fn cases() -> Vec<Case> {
+    let mut cases = Vec::new();
+
+    let mut case = |name: &'static str, tokens: Vec<Token<'static>>| {
+        cases.push(Case { name, tokens });
+    };
+
+    case("null", vec![Null]);
+    case("bool", vec![Bool(true)]);
+    case("u8", vec![U8(u8::MAX)]);
+    case("u16", vec![U16(u16::MAX)]);
+    case("u32", vec![U32(u32::MAX)]);
+    case("u64", vec![U64(u64::MAX)]);
+    case("u128", vec![U128(42)]);
+    case("u128_large", vec![U128(u128::MAX)]);
+    case("i8", vec![I8(i8::MIN)]);
+    case("i16", vec![I16(i16::MIN)]);
+    case("i32", vec![I32(i32::MIN)]);
+    case("i64", vec![I64(i64::MIN)]);
+    case("i128", vec![I128(-42)]);
+    case("i128_large", vec![I128(i128::MIN)]);
+    case("f32", vec![F32(1.5)]);
+    case("f64", vec![F64(-1.5)]);
+
+    case("text_empty", vec![TextBegin(Some(0)), TextEnd]);
+    case("text", text("a string"));
+    case(
+        "text_computed",
+        vec![
+            TextBegin(Some(8)),
+            TextFragmentComputed("a string".to_owned()),
+            TextEnd,
+        ],
+    );
+    case(
+        "text_fragmented",
+        vec![
+            TextBegin(Some(16)),
+            TextFragment("a "),
+            TextFragmentComputed("fragmented".to_owned()),
+            TextFragment(""),
+            TextFragment(" ✓"),
+            TextEnd,
+        ],
+    );
+    case(
+        "text_unhinted",
+        vec![TextBegin(None), TextFragment("a string"), TextEnd],
+    );
+    case("text_escaped", text("\"quoted\"\\\n\t\r\u{0}\u{7f}"));
+    case("text_unicode", text("ゆなす 🦀 ñ"));
+
+    case("binary_empty", vec![BinaryBegin(Some(0)), BinaryEnd]);
+    case(
+        "binary",
+        vec![
+            BinaryBegin(Some(3)),
+            BinaryFragment(&[0, 127, 255]),
+            BinaryEnd,
+        ],
+    );
+    case(
+        "binary_computed",
+        vec![
+            BinaryBegin(Some(3)),
+            BinaryFragmentComputed(vec![0, 127, 255]),
+            BinaryEnd,
+        ],
+    );
+    case(
+        "binary_fragmented",
+        vec![
+            BinaryBegin(Some(4)),
+            BinaryFragment(&[0, 1]),
+            BinaryFragment(&[]),
+            BinaryFragmentComputed(vec![2, 3]),
+            BinaryEnd,
+        ],
+    );
+    case(
+        "binary_unhinted",
+        vec![BinaryBegin(None), BinaryFragment(&[0, 1, 2]), BinaryEnd],
+    );
+
+    case("map_empty", vec![MapBegin(Some(0)), MapEnd]);
+    case(
+        "map",
+        map(vec![(text("a"), vec![I32(1)]), (text("b"), vec![I32(2)])]),
+    );
+    case(
+        "map_unhinted",
+        unhinted(map(vec![(text("a"), vec![I32(1)])])),
+    );
+    case(
+        "map_non_text_keys",
+        map(vec![
+            (vec![I32(1)], text("a")),
+            (vec![Bool(false)], text("b")),
+            (vec![F64(1.5)], text("c")),
+        ]),
+    );
+    case(
+        "map_nested",
+        map(vec![
+            (text("a"), map(vec![(text("b"), map(vec![]))])),
+            (text("c"), seq(vec![vec![I32(1)], map(vec![])])),
+        ]),
+    );
+    case(
+        "map_of_records",
+        map(vec![
+            (text("a"), point(Meta::label("Point"), 1, 2)),
+            (text("b"), point(Meta::label("Point"), 3, 4)),
+        ]),
+    );
+
+    case("seq_empty", vec![SeqBegin(Some(0)), SeqEnd]);
+    case("seq", seq(vec![vec![I32(1)], vec![I32(2)], vec![I32(3)]]));
+    case("seq_unhinted", unhinted(seq(vec![vec![I32(1)]])));
+    case(
+        "seq_nested",
+        seq(vec![
+            seq(vec![seq(vec![]), seq(vec![vec![I32(1)]])]),
+            seq(vec![]),
+        ]),
+    );
+    case(
+        "seq_mixed",
+        seq(vec![
+            vec![Null],
+            vec![Bool(true)],
+            vec![I64(-1)],
+            vec![F64(1.5)],
+            text("a"),
+            map(vec![(text("a"), vec![I32(1)])]),
+            point(Meta::default(), 1, 2),
+        ]),
+    );
+
+    case("record_empty", record(Meta::label("Empty"), vec![]));
+    case("record", point(Meta::label("Point"), 1, 2));
+    case("record_anonymous", point(Meta::default(), 1, 2));
+    case(
+        "record_computed_labels",
+        record(
+            Meta {
+                label: Some(Name::Computed("Point")),
+                ..Default::default()
+            },
+            vec![
+                (None, Name::Computed("x"), vec![I32(1)]),
+                (None, Name::Computed("y"), vec![I32(2)]),
+            ],
+        ),
+    );
+    case(
+        "record_field_tags",
+        record(
+            Meta::label("Tagged"),
+            vec![
+                (
+                    Some(sval::Tag::new("field")),
+                    Name::Static("a"),
+                    vec![I32(1)],
+                ),
+                (Some(tags::NUMBER), Name::Static("b"), number("1.5")),
+            ],
+        ),
+    );
+    case(
+        "record_nested",
+        record(
+            Meta::label("Line"),
+            vec![
+                (
+                    None,
+                    Name::Static("from"),
+                    point(Meta::label("Point"), 1, 2),
+                ),
+                (None, Name::Static("to"), point(Meta::label("Point"), 3, 4)),
+                (None, Name::Static("empty"), record(Meta::default(), vec![])),
+            ],
+        ),
+    );
+
+    case("tuple_empty", tuple(Meta::label("Empty"), vec![]));
+    case(
+        "tuple",
+        tuple(Meta::label("Pair"), vec![vec![I32(1)], text("a")]),
+    );
+    case(
+        "tuple_anonymous",
+        tuple(Meta::default(), vec![vec![I32(1)]]),
+    );
+    case(
+        "tuple_nested",
+        tuple(
+            Meta::default(),
+            vec![
+                tuple(Meta::default(), vec![]),
+                tuple(Meta::label("Pair"), vec![vec![I32(1)], vec![I32(2)]]),
+            ],
+        ),
+    );
+
+    case("tag", tag(Meta::label("Tag")));
+    case("tag_anonymous", tag(Meta::default()));
+    case("tagged", tagged(Meta::label("Tagged"), vec![I32(1)]));
+    case(
+        "tagged_nested",
+        tagged(
+            Meta::label("Outer"),
+            tagged(Meta::default(), tagged(Meta::label("Inner"), text("a"))),
+        ),
+    );
+    case("unit", tag(Meta::tag(tags::RUST_UNIT)));
+    case(
+        "option_none",
+        tag(Meta {
+            tag: Some(tags::RUST_OPTION_NONE),
+            label: Some(Name::Static("None")),
+            index: Some(0),
+        }),
+    );
+    case(
+        "option_some",
+        tagged(
+            Meta {
+                tag: Some(tags::RUST_OPTION_SOME),
+                label: Some(Name::Static("Some")),
+                index: Some(1),
+            },
+            vec![I32(1)],
+        ),
+    );
+    case("number", number("-1.5e10"));
+    case(
+        "constant_size",
+        tagged(
+            Meta::tag(tags::CONSTANT_SIZE),
+            seq(vec![vec![U8(1)], vec![U8(2)]]),
+        ),
+    );
+
+    case(
+        "enum_tag",
+        enum_(Meta::label("Enum"), tag(Meta::variant("Tag", 0))),
+    );
+    case(
+        "enum_tagged",
+        enum_(
+            Meta::label("Enum"),
+            tagged(Meta::variant("Tagged", 1), vec![I32(1)]),
+        ),
+    );
+    case(
+        "enum_record",
+        enum_(Meta::label("Enum"), point(Meta::variant("Record", 2), 1, 2)),
+    );
+    case(
+        "enum_tuple",
+        enum_(
+            Meta::label("Enum"),
+            tuple(Meta::variant("Tuple", 3), vec![vec![I32(1)], vec![I32(2)]]),
+        ),
+    );
+    case(
+        "enum_nested",
+        enum_(
+            Meta::label("Outer"),
+            enum_(
+                Meta::variant("Inner", 0),
+                enum_(Meta::variant("Innermost", 1), tag(Meta::variant("Tag", 0))),
+            ),
+        ),
+    );
+    case(
+        "enum_anonymous",
+        enum_(
+            Meta::default(),
+            tagged(
+                Meta {
+                    index: Some(0),
+                    ..Default::default()
+                },
+                vec![I32(1)],
+            ),
+        ),
+    );
+    case(
+        "enum_in_seq",
+        seq(vec![
+            enum_(Meta::label("Enum"), tag(Meta::variant("Tag", 0))),
+            enum_(
+                Meta::label("Enum"),
+                tagged(Meta::variant("Tagged", 1), vec![I32(1)]),
+            ),
+            enum_(Meta::label("Enum"), point(Meta::variant("Record", 2), 1, 2)),
+        ]),
+    );
+
+    case(
+        "nested",
+        seq(vec![map(vec![(
+            text("a"),
+            record(
+                Meta::label("Record"),
+                vec![
+                    (
+                        None,
+                        Name::Static("a"),
+                        enum_(
+                            Meta::label("Enum"),
+                            tuple(
+                                Meta::variant("Tuple", 0),
+                                vec![
+                                    tagged(Meta::label("Tagged"), seq(vec![vec![Null], text("b")])),
+                                    map(vec![]),
+                                ],
+                            ),
+                        ),
+                    ),
+                    (
+                        None,
+                        Name::Static("b"),
+                        vec![
+                            BinaryBegin(None),
+                            BinaryFragmentComputed(vec![1]),
+                            BinaryEnd,
+                        ],
+                    ),
+                ],
+            ),
+        )])]),
+    );
+
+    cases
+}
+
+#[derive(Clone, Copy)]
+enum Name {
+    Static(&'static str),
+    Computed(&'static str),
+}
+
+impl Name {
+    fn label(self) -> sval::Label<'static> {
+        match self {
+            Name::Static(label) => sval::Label::new(label),
+            Name::Computed(label) => sval::Label::from_owned(label.to_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Meta {
+    tag: Option<sval::Tag>,
+    label: Option<Name>,
+    index: Option<usize>,
+}
+
+type MetaTokens = (
+    Option<sval::Tag>,
+    Option<sval::Label<'static>>,
+    Option<sval::Index>,
+);
+
+impl Meta {
+    fn label(label: &'static str) -> Self {
+        Meta {
+            label: Some(Name::Static(label)),
+            ..Default::default()
+        }
+    }
+
+    fn variant(label: &'static str, index: usize) -> Self {
+        Meta {
+            label: Some(Name::Static(label)),
+            index: Some(index),
+            ..Default::default()
+        }
+    }
+
+    fn tag(tag: sval::Tag) -> Self {
+        Meta {
+            tag: Some(tag),
+            ..Default::default()
+        }
+    }
+
+    fn tokens(&self) -> MetaTokens {
+        (
+            self.tag.clone(),
+            self.label.map(Name::label),
+            self.index.map(sval::Index::new),
+        )
+    }
+}
+
+fn text(text: &'static str) -> Vec<Token<'static>> {
+    vec![TextBegin(Some(text.len())), TextFragment(text), TextEnd]
+}
+
+fn number(number: &'static str) -> Vec<Token<'static>> {
+    tagged(Meta::tag(tags::NUMBER), text(number))
+}
+
+fn unhinted(mut tokens: Vec<Token<'static>>) -> Vec<Token<'static>> {
+    match tokens.first_mut() {
+        Some(MapBegin(num_entries)) | Some(SeqBegin(num_entries)) => *num_entries = None,
+        _ => unreachable!(),
+    }
+
+    tokens
+}
+
+fn map(entries: Vec<(Vec<Token<'static>>, Vec<Token<'static>>)>) -> Vec<Token<'static>> {
+    let mut tokens = vec![MapBegin(Some(entries.len()))];
+
+    for (key, value) in entries {
+        tokens.push(MapKeyBegin);
+        tokens.extend(key);
+        tokens.push(MapKeyEnd);
+        tokens.push(MapValueBegin);
+        tokens.extend(value);
+        tokens.push(MapValueEnd);
+    }
+
+    tokens.push(MapEnd);
+    tokens
+}
+
+fn seq(values: Vec<Vec<Token<'static>>>) -> Vec<Token<'static>> {
+    let mut tokens = vec![SeqBegin(Some(values.len()))];
+
+    for value in values {
+        tokens.push(SeqValueBegin);
+        tokens.extend(value);
+        tokens.push(SeqValueEnd);
+    }
+
+    tokens.push(SeqEnd);
+    tokens
+}
+
+fn record(
+    meta: Meta,
+    fields: Vec<(Option<sval::Tag>, Name, Vec<Token<'static>>)>,
+) -> Vec<Token<'static>> {
+    let (tag, label, index) = meta.tokens();
+    let mut tokens = vec![RecordBegin(tag, label, index, Some(fields.len()))];
+
+    for (tag, label, value) in fields {
+        tokens.push(RecordValueBegin(tag.clone(), label.label()));
+        tokens.extend(value);
+        tokens.push(RecordValueEnd(tag, label.label()));
+    }
+
+    let (tag, label, index) = meta.tokens();
+    tokens.push(RecordEnd(tag, label, index));
+    tokens
+}
+
+fn point(meta: Meta, x: i32, y: i32) -> Vec<Token<'static>> {
+    record(
+        meta,
+        vec![
+            (None, Name::Static("x"), vec![I32(x)]),
+            (None, Name::Static("y"), vec![I32(y)]),
+        ],
+    )
+}
+
+fn tuple(meta: Meta, values: Vec<Vec<Token<'static>>>) -> Vec<Token<'static>> {
+    let (tag, label, index) = meta.tokens();
+    let mut tokens = vec![TupleBegin(tag, label, index, Some(values.len()))];
+
+    for (index, value) in values.into_iter().enumerate() {
+        tokens.push(TupleValueBegin(None, sval::Index::new(index)));
+        tokens.extend(value);
+        tokens.push(TupleValueEnd(None, sval::Index::new(index)));
+    }
+
+    let (tag, label, index) = meta.tokens();
+    tokens.push(TupleEnd(tag, label, index));
+    tokens
+}
+
+fn tag(meta: Meta) -> Vec<Token<'static>> {
+    let (tag, label, index) = meta.tokens();
+    vec![Tag(tag, label, index)]
+}
+
+fn tagged(meta: Meta, value: Vec<Token<'static>>) -> Vec<Token<'static>> {
+    let (tag, label, index) = meta.tokens();
+    let mut tokens = vec![TaggedBegin(tag, label, index)];
+
+    tokens.extend(value);
+
+    let (tag, label, index) = meta.tokens();
+    tokens.push(TaggedEnd(tag, label, index));
+    tokens
+}
+
+fn enum_(meta: Meta, variant: Vec<Token<'static>>) -> Vec<Token<'static>> {
+    let (tag, label, index) = meta.tokens();
+    let mut tokens = vec![EnumBegin(tag, label, index)];
+
+    tokens.extend(variant);
+
+    let (tag, label, index) = meta.tokens();
+    tokens.push(EnumEnd(tag, label, index));
+    tokens
+}
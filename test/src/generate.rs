@@ -0,0 +1,656 @@
+/*!
+Property-based generation of arbitrary values.
+
+A [`Generator`] produces [`Recording`]s from a seed. Each recording is a well-formed
+tree of tokens that's weighted towards the edges streams tend to get wrong:
+empty and fragmented text, computed and borrowed fragments, huge size hints,
+deep nesting, and every tag in [`sval::tags`].
+
+Use [`check`] to assert a property holds for a number of generated values.
+*/
+
+use std::{
+    borrow::Cow,
+    panic::{self, AssertUnwindSafe},
+};
+
+use sval::tags;
+
+use crate::{panic_message, OwnedToken as Token, Recording};
+
+/**
+Check a property against `num_values` generated values.
+
+This is the same as calling [`Generator::check`] on a default generator.
+*/
+pub fn check(num_values: u64, property: impl FnMut(&Recording)) {
+    Generator::new(0).check(num_values, property)
+}
+
+/**
+A generator for arbitrary values.
+
+Generators are deterministic: the same seed and configuration always
+produce the same values.
+*/
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: u64,
+    max_depth: usize,
+    max_len: usize,
+    text_keys: bool,
+}
+
+const CUSTOM_TAG: sval::Tag = sval::Tag::new("svaltestcustom");
+
+const LABELS: &[&str] = &[
+    "a",
+    "field",
+    "",
+    "with space",
+    "\"quoted\"",
+    "ünïcödé",
+    "🦀",
+    "a_longer_label_than_most",
+];
+
+const TEXT: &[&str] = &[
+    "",
+    "a",
+    "a string",
+    "\"quoted\"\\\n\t\r\u{0}\u{1f}\u{7f}",
+    "</script>&'",
+    "ゆなす 🦀 ñ",
+    "\u{2028}\u{2029}\u{feff}",
+];
+
+const NUMBERS: &[&str] = &[
+    "0",
+    "-0",
+    "1",
+    "-1.5",
+    "1e10",
+    "-1.5E-10",
+    "123456789012345678901234567890",
+    "1.7976931348623157e308",
+    "5e-324",
+];
+
+const HUGE_HINT: usize = usize::MAX;
+
+impl Generator {
+    /**
+    Create a generator from a seed.
+    */
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            rng: seed,
+            max_depth: 16,
+            max_len: 8,
+            text_keys: false,
+        }
+    }
+
+    /**
+    Set the maximum depth of nested values.
+
+    The default is `16`.
+    */
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /**
+    Set the maximum number of entries in collections and fragments in text or binary.
+
+    The default is `8`.
+    */
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /**
+    Only generate text map keys.
+
+    Many formats, like JSON, don't support other kinds of keys.
+    By default, map keys may be any primitive value.
+    */
+    pub fn with_text_keys(mut self) -> Self {
+        self.text_keys = true;
+        self
+    }
+
+    /**
+    Check a property against `num_values` values generated with this configuration.
+
+    Each value is generated with a seed from `0` up to `num_values`.
+    If the property panics, this method panics with the seed that produced the failing
+    value, so it can be reproduced with [`Generator::new`] and the same configuration.
+    */
+    pub fn check(&self, num_values: u64, mut property: impl FnMut(&Recording)) {
+        for seed in 0..num_values {
+            let value = Generator {
+                rng: seed,
+                ..self.clone()
+            }
+            .generate();
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| property(&value)));
+
+            if let Err(panic) = result {
+                panic!(
+                    "property failed for seed {}: {}\n{}",
+                    seed,
+                    panic_message(&*panic),
+                    value
+                );
+            }
+        }
+    }
+
+    /**
+    Generate an arbitrary value.
+    */
+    pub fn generate(&mut self) -> Recording {
+        let mut parts = Vec::new();
+
+        // Occasionally build a chain of nested values to reach the maximum depth
+        if self.chance(16) {
+            self.deep(&mut parts, self.max_depth);
+        } else {
+            self.value(&mut parts, 0);
+        }
+
+        Recording::from(parts)
+    }
+
+    fn next(&mut self) -> u64 {
+        // SplitMix64
+        self.rng = self.rng.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, one_in: usize) -> bool {
+        self.below(one_in) == 0
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    fn len(&mut self) -> usize {
+        // Empty collections are weighted heavily
+        if self.chance(4) {
+            0
+        } else {
+            self.below(self.max_len + 1)
+        }
+    }
+
+    fn hint(&mut self, len: usize) -> Option<usize> {
+        match self.below(8) {
+            0 => None,
+            1 => Some(HUGE_HINT),
+            _ => Some(len),
+        }
+    }
+
+    fn label(&mut self, label: &'static str) -> Cow<'static, str> {
+        if self.chance(4) {
+            Cow::Owned(label.to_owned())
+        } else {
+            Cow::Borrowed(label)
+        }
+    }
+
+    fn token(parts: &mut Vec<Token>, token: Token) {
+        parts.push(token);
+    }
+
+    fn value(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        // Compound values get less likely the deeper we are so values stay small.
+        // The `deep` chains are what reach the maximum depth
+        if depth >= self.max_depth || self.below(4) < depth {
+            return self.primitive(parts);
+        }
+
+        match self.below(16) {
+            0..=5 => self.primitive(parts),
+            6 => self.map(parts, depth),
+            7 | 8 => self.seq(parts, depth),
+            9 | 10 => self.record(parts, None, None, depth),
+            11 => self.tuple(parts, None, None, depth),
+            12 => self.enum_(parts, depth),
+            13 => self.tagged(parts, depth),
+            _ => self.tagged_with(parts, depth),
+        }
+    }
+
+    fn deep(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        if depth == 0 {
+            return self.primitive(parts);
+        }
+
+        match self.below(3) {
+            0 => {
+                Self::token(parts, Token::SeqBegin(Some(1)));
+                Self::token(parts, Token::SeqValueBegin);
+                self.deep(parts, depth - 1);
+                Self::token(parts, Token::SeqValueEnd);
+                Self::token(parts, Token::SeqEnd);
+            }
+            1 => {
+                Self::token(parts, Token::MapBegin(Some(1)));
+                Self::token(parts, Token::MapKeyBegin);
+                self.text(parts);
+                Self::token(parts, Token::MapKeyEnd);
+                Self::token(parts, Token::MapValueBegin);
+                self.deep(parts, depth - 1);
+                Self::token(parts, Token::MapValueEnd);
+                Self::token(parts, Token::MapEnd);
+            }
+            _ => {
+                let label = *self.pick(LABELS);
+
+                Self::token(
+                    parts,
+                    Token::RecordBegin(None, Some(Cow::Borrowed("Deep")), None, Some(1)),
+                );
+                Self::token(parts, Token::RecordValueBegin(None, Cow::Borrowed(label)));
+                self.deep(parts, depth - 1);
+                Self::token(parts, Token::RecordValueEnd(None, Cow::Borrowed(label)));
+                Self::token(
+                    parts,
+                    Token::RecordEnd(None, Some(Cow::Borrowed("Deep")), None),
+                );
+            }
+        }
+    }
+
+    fn primitive(&mut self, parts: &mut Vec<Token>) {
+        let r = self.next();
+
+        let token = match self.below(20) {
+            0 => Token::Null,
+            1 => Token::Bool(self.chance(2)),
+            2 => Token::U8(*self.pick(&[0, 1, u8::MAX, r as u8])),
+            3 => Token::U16(*self.pick(&[0, u16::MAX, r as u16])),
+            4 => Token::U32(*self.pick(&[0, u32::MAX, r as u32])),
+            5 => Token::U64(*self.pick(&[0, u64::MAX, r])),
+            6 => Token::U128(*self.pick(&[0, u64::MAX as u128 + 1, u128::MAX, r as u128])),
+            7 => Token::I8(*self.pick(&[0, -1, i8::MIN, i8::MAX, r as i8])),
+            8 => Token::I16(*self.pick(&[i16::MIN, i16::MAX, r as i16])),
+            9 => Token::I32(*self.pick(&[i32::MIN, i32::MAX, r as i32])),
+            10 => Token::I64(*self.pick(&[i64::MIN, i64::MAX, r as i64])),
+            11 => Token::I128(*self.pick(&[
+                i64::MIN as i128 - 1,
+                i128::MIN,
+                i128::MAX,
+                r as i64 as i128,
+            ])),
+            12 => Token::F32(*self.pick(&[
+                0.0,
+                -0.0,
+                1.5,
+                f32::MIN_POSITIVE,
+                f32::MAX,
+                f32::NAN,
+                f32::INFINITY,
+            ])),
+            13 => Token::F64(*self.pick(&[
+                0.0,
+                -0.0,
+                -1.5,
+                f64::MIN_POSITIVE,
+                f64::MAX,
+                f64::NAN,
+                f64::NEG_INFINITY,
+                r as f64 / 3.0,
+            ])),
+            14..=16 => return self.text(parts),
+            17 => return self.binary(parts),
+            18 => return self.number(parts),
+            _ => return self.tag(parts),
+        };
+
+        Self::token(parts, token);
+    }
+
+    fn text(&mut self, parts: &mut Vec<Token>) {
+        let text = if self.chance(32) {
+            // A long string built from repeated fragments
+            TEXT[5].repeat(self.below(512) + 1)
+        } else if self.chance(2) {
+            (*self.pick(TEXT)).to_owned()
+        } else {
+            (0..self.below(self.max_len * 4))
+                .map(|_| (b' ' + self.below(95) as u8) as char)
+                .collect()
+        };
+
+        self.text_fragments(parts, &text);
+    }
+
+    fn text_fragments(&mut self, parts: &mut Vec<Token>, text: &str) {
+        let hint = self.hint(text.len());
+        Self::token(parts, Token::TextBegin(hint));
+
+        let mut rest = text;
+        while !rest.is_empty() || self.chance(8) {
+            let mut mid = if rest.is_empty() || self.chance(2) {
+                rest.len()
+            } else {
+                self.below(rest.len() + 1)
+            };
+
+            while !rest.is_char_boundary(mid) {
+                mid += 1;
+            }
+
+            let (fragment, tail) = rest.split_at(mid);
+            rest = tail;
+
+            parts.push(if self.chance(2) {
+                Token::TextFragment(fragment.to_owned())
+            } else {
+                Token::TextFragmentComputed(fragment.to_owned())
+            });
+        }
+
+        Self::token(parts, Token::TextEnd);
+    }
+
+    fn binary(&mut self, parts: &mut Vec<Token>) {
+        let binary: Vec<u8> = (0..self.len() * 4).map(|_| self.next() as u8).collect();
+
+        let hint = self.hint(binary.len());
+        Self::token(parts, Token::BinaryBegin(hint));
+
+        let mut rest = &*binary;
+        while !rest.is_empty() || self.chance(8) {
+            let mid = if rest.is_empty() || self.chance(2) {
+                rest.len()
+            } else {
+                self.below(rest.len() + 1)
+            };
+
+            let (fragment, tail) = rest.split_at(mid);
+            rest = tail;
+
+            parts.push(if self.chance(2) {
+                Token::BinaryFragment(fragment.to_owned())
+            } else {
+                Token::BinaryFragmentComputed(fragment.to_owned())
+            });
+        }
+
+        Self::token(parts, Token::BinaryEnd);
+    }
+
+    fn number(&mut self, parts: &mut Vec<Token>) {
+        let number = *self.pick(NUMBERS);
+
+        Self::token(parts, Token::TaggedBegin(Some(tags::NUMBER), None, None));
+        self.text_fragments(parts, number);
+        Self::token(parts, Token::TaggedEnd(Some(tags::NUMBER), None, None));
+    }
+
+    fn tag(&mut self, parts: &mut Vec<Token>) {
+        let token = match self.below(4) {
+            0 => Token::Tag(Some(tags::RUST_UNIT), Some(Cow::Borrowed("()")), None),
+            1 => Token::Tag(
+                Some(tags::RUST_OPTION_NONE),
+                Some(Cow::Borrowed("None")),
+                Some(sval::Index::new(0)),
+            ),
+            2 => Token::Tag(Some(CUSTOM_TAG), None, None),
+            _ => {
+                let label = *self.pick(LABELS);
+                Token::Tag(None, Some(self.label(label)), None)
+            }
+        };
+
+        Self::token(parts, token);
+    }
+
+    fn key(&mut self, parts: &mut Vec<Token>) {
+        // Keys are text or other primitives, but never compound values
+        if !self.text_keys && self.chance(4) {
+            self.primitive(parts)
+        } else {
+            self.text(parts)
+        }
+    }
+
+    fn map(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        let len = self.len();
+
+        let hint = self.hint(len);
+        Self::token(parts, Token::MapBegin(hint));
+
+        for _ in 0..len {
+            Self::token(parts, Token::MapKeyBegin);
+            self.key(parts);
+            Self::token(parts, Token::MapKeyEnd);
+
+            Self::token(parts, Token::MapValueBegin);
+            self.value(parts, depth + 1);
+            Self::token(parts, Token::MapValueEnd);
+        }
+
+        Self::token(parts, Token::MapEnd);
+    }
+
+    fn seq(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        let len = self.len();
+
+        let hint = self.hint(len);
+        Self::token(parts, Token::SeqBegin(hint));
+
+        for _ in 0..len {
+            Self::token(parts, Token::SeqValueBegin);
+            self.value(parts, depth + 1);
+            Self::token(parts, Token::SeqValueEnd);
+        }
+
+        Self::token(parts, Token::SeqEnd);
+    }
+
+    fn record(
+        &mut self,
+        parts: &mut Vec<Token>,
+        label: Option<&'static str>,
+        index: Option<usize>,
+        depth: usize,
+    ) {
+        // Field labels are unique within a record
+        let len = self.len().min(LABELS.len());
+        let start = self.below(LABELS.len());
+
+        let label = label.map(|label| self.label(label));
+        let hint = self.hint(len);
+        Self::token(
+            parts,
+            Token::RecordBegin(None, label.clone(), index.map(sval::Index::new), hint),
+        );
+
+        for i in 0..len {
+            let field = LABELS[(start + i) % LABELS.len()];
+            let tag = if self.chance(8) {
+                Some(CUSTOM_TAG)
+            } else {
+                None
+            };
+
+            let field_label = self.label(field);
+            Self::token(
+                parts,
+                Token::RecordValueBegin(tag.clone(), field_label.clone()),
+            );
+            self.value(parts, depth + 1);
+            Self::token(parts, Token::RecordValueEnd(tag, field_label));
+        }
+
+        Self::token(
+            parts,
+            Token::RecordEnd(None, label, index.map(sval::Index::new)),
+        );
+    }
+
+    fn tuple(
+        &mut self,
+        parts: &mut Vec<Token>,
+        label: Option<&'static str>,
+        index: Option<usize>,
+        depth: usize,
+    ) {
+        let len = self.len();
+
+        let label = label.map(|label| self.label(label));
+        let hint = self.hint(len);
+        Self::token(
+            parts,
+            Token::TupleBegin(None, label.clone(), index.map(sval::Index::new), hint),
+        );
+
+        for i in 0..len {
+            Self::token(parts, Token::TupleValueBegin(None, sval::Index::new(i)));
+            self.value(parts, depth + 1);
+            Self::token(parts, Token::TupleValueEnd(None, sval::Index::new(i)));
+        }
+
+        Self::token(
+            parts,
+            Token::TupleEnd(None, label, index.map(sval::Index::new)),
+        );
+    }
+
+    fn enum_(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        let label = if self.chance(8) {
+            None
+        } else {
+            Some(Cow::Borrowed("Enum"))
+        };
+        Self::token(parts, Token::EnumBegin(None, label.clone(), None));
+
+        let variant = *self.pick(LABELS);
+        let index = self.below(4);
+
+        match self.below(5) {
+            0 => {
+                let variant = self.label(variant);
+                Self::token(
+                    parts,
+                    Token::Tag(None, Some(variant), Some(sval::Index::new(index))),
+                )
+            }
+            1 => {
+                let begin = self.label(variant);
+                Self::token(
+                    parts,
+                    Token::TaggedBegin(None, Some(begin), Some(sval::Index::new(index))),
+                );
+                self.value(parts, depth + 1);
+                Self::token(
+                    parts,
+                    Token::TaggedEnd(
+                        None,
+                        Some(Cow::Borrowed(variant)),
+                        Some(sval::Index::new(index)),
+                    ),
+                );
+            }
+            2 => self.record(parts, Some(variant), Some(index), depth + 1),
+            3 => self.tuple(parts, Some(variant), Some(index), depth + 1),
+            _ if depth + 1 < self.max_depth => self.enum_(parts, depth + 1),
+            _ => Self::token(parts, Token::Tag(None, None, Some(sval::Index::new(index)))),
+        }
+
+        Self::token(parts, Token::EnumEnd(None, label, None));
+    }
+
+    fn tagged(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        match self.below(3) {
+            0 => {
+                let some = || {
+                    (
+                        Some(tags::RUST_OPTION_SOME),
+                        Some(Cow::Borrowed("Some")),
+                        Some(sval::Index::new(1)),
+                    )
+                };
+
+                let (tag, label, index) = some();
+                Self::token(parts, Token::TaggedBegin(tag, label, index));
+                self.value(parts, depth + 1);
+                let (tag, label, index) = some();
+                Self::token(parts, Token::TaggedEnd(tag, label, index));
+            }
+            1 => {
+                let len = self.len();
+
+                Self::token(
+                    parts,
+                    Token::TaggedBegin(Some(tags::CONSTANT_SIZE), None, None),
+                );
+                Self::token(parts, Token::SeqBegin(Some(len)));
+                for _ in 0..len {
+                    Self::token(parts, Token::SeqValueBegin);
+                    Self::token(parts, Token::U8(self.next() as u8));
+                    Self::token(parts, Token::SeqValueEnd);
+                }
+                Self::token(parts, Token::SeqEnd);
+                Self::token(
+                    parts,
+                    Token::TaggedEnd(Some(tags::CONSTANT_SIZE), None, None),
+                );
+            }
+            _ => {
+                Self::token(
+                    parts,
+                    Token::TaggedBegin(Some(tags::CONSTANT_SIZE), None, None),
+                );
+                self.binary(parts);
+                Self::token(
+                    parts,
+                    Token::TaggedEnd(Some(tags::CONSTANT_SIZE), None, None),
+                );
+            }
+        }
+    }
+
+    fn tagged_with(&mut self, parts: &mut Vec<Token>, depth: usize) {
+        let tag = if self.chance(2) {
+            Some(CUSTOM_TAG)
+        } else {
+            None
+        };
+        let label = *self.pick(LABELS);
+        let index = if self.chance(2) {
+            Some(sval::Index::new(self.below(4)))
+        } else {
+            None
+        };
+
+        let begin = self.label(label);
+        Self::token(
+            parts,
+            Token::TaggedBegin(tag.clone(), Some(begin), index.clone()),
+        );
+        self.value(parts, depth + 1);
+        Self::token(
+            parts,
+            Token::TaggedEnd(tag, Some(Cow::Borrowed(label)), index),
+        );
+    }
+}
@@ -1,3 +1,11 @@
+pub mod conformance;
+pub mod generate;
+
+mod recording;
+mod snapshot;
+
+pub use self::{recording::*, snapshot::*};
+
 pub fn assert_tokens<'sval>(value: &'sval (impl sval::Value + ?Sized), tokens: &[Token<'sval>]) {
     let mut stream = Stream(Vec::new());
 
@@ -93,6 +101,96 @@ pub enum Token<'a> {
     ),
 }
 
+/**
+Get the message from the payload of a caught panic.
+*/
+fn panic_message(panic: &(dyn std::any::Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(|message| &**message))
+        .unwrap_or("Box<dyn Any>")
+}
+
+fn replay<'sval, S: sval::Stream<'sval> + ?Sized>(
+    stream: &mut S,
+    tokens: &[Token<'sval>],
+) -> sval::Result {
+    use Token::*;
+
+    for token in tokens {
+        match *token {
+            U8(v) => stream.u8(v)?,
+            U16(v) => stream.u16(v)?,
+            U32(v) => stream.u32(v)?,
+            U64(v) => stream.u64(v)?,
+            U128(v) => stream.u128(v)?,
+            I8(v) => stream.i8(v)?,
+            I16(v) => stream.i16(v)?,
+            I32(v) => stream.i32(v)?,
+            I64(v) => stream.i64(v)?,
+            I128(v) => stream.i128(v)?,
+            F32(v) => stream.f32(v)?,
+            F64(v) => stream.f64(v)?,
+            Bool(v) => stream.bool(v)?,
+            Null => stream.null()?,
+            Tag(ref tag, ref label, ref index) => {
+                stream.tag(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            TextBegin(num_bytes) => stream.text_begin(num_bytes)?,
+            TextFragment(fragment) => stream.text_fragment(fragment)?,
+            TextFragmentComputed(ref fragment) => stream.text_fragment_computed(fragment)?,
+            TextEnd => stream.text_end()?,
+            BinaryBegin(num_bytes) => stream.binary_begin(num_bytes)?,
+            BinaryFragment(fragment) => stream.binary_fragment(fragment)?,
+            BinaryFragmentComputed(ref fragment) => stream.binary_fragment_computed(fragment)?,
+            BinaryEnd => stream.binary_end()?,
+            MapBegin(num_entries) => stream.map_begin(num_entries)?,
+            MapKeyBegin => stream.map_key_begin()?,
+            MapKeyEnd => stream.map_key_end()?,
+            MapValueBegin => stream.map_value_begin()?,
+            MapValueEnd => stream.map_value_end()?,
+            MapEnd => stream.map_end()?,
+            SeqBegin(num_entries) => stream.seq_begin(num_entries)?,
+            SeqValueBegin => stream.seq_value_begin()?,
+            SeqValueEnd => stream.seq_value_end()?,
+            SeqEnd => stream.seq_end()?,
+            EnumBegin(ref tag, ref label, ref index) => {
+                stream.enum_begin(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            EnumEnd(ref tag, ref label, ref index) => {
+                stream.enum_end(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            TaggedBegin(ref tag, ref label, ref index) => {
+                stream.tagged_begin(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            TaggedEnd(ref tag, ref label, ref index) => {
+                stream.tagged_end(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            RecordBegin(ref tag, ref label, ref index, num_entries) => {
+                stream.record_begin(tag.as_ref(), label.as_ref(), index.as_ref(), num_entries)?
+            }
+            RecordValueBegin(ref tag, ref label) => {
+                stream.record_value_begin(tag.as_ref(), label)?
+            }
+            RecordValueEnd(ref tag, ref label) => stream.record_value_end(tag.as_ref(), label)?,
+            RecordEnd(ref tag, ref label, ref index) => {
+                stream.record_end(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+            TupleBegin(ref tag, ref label, ref index, num_entries) => {
+                stream.tuple_begin(tag.as_ref(), label.as_ref(), index.as_ref(), num_entries)?
+            }
+            TupleValueBegin(ref tag, ref index) => stream.tuple_value_begin(tag.as_ref(), index)?,
+            TupleValueEnd(ref tag, ref index) => stream.tuple_value_end(tag.as_ref(), index)?,
+            TupleEnd(ref tag, ref label, ref index) => {
+                stream.tuple_end(tag.as_ref(), label.as_ref(), index.as_ref())?
+            }
+        }
+    }
+
+    Ok(())
+}
+
 struct Stream<'a>(Vec<Token<'a>>);
 
 impl<'a> Stream<'a> {
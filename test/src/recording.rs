@@ -0,0 +1,361 @@
+use std::borrow::Cow;
+
+use crate::Token;
+
+/**
+An owned version of [`Token`] that can be stored and sent across threads.
+
+Fragments and labels are owned rather than borrowed. Labels that were created from
+static strings are kept as [`Cow::Borrowed`], so they can be replayed as static labels.
+*/
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedToken {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    Bool(bool),
+    Null,
+    Tag(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    TextBegin(Option<usize>),
+    TextFragment(String),
+    TextFragmentComputed(String),
+    TextEnd,
+    BinaryBegin(Option<usize>),
+    BinaryFragment(Vec<u8>),
+    BinaryFragmentComputed(Vec<u8>),
+    BinaryEnd,
+    MapBegin(Option<usize>),
+    MapKeyBegin,
+    MapKeyEnd,
+    MapValueBegin,
+    MapValueEnd,
+    MapEnd,
+    SeqBegin(Option<usize>),
+    SeqValueBegin,
+    SeqValueEnd,
+    SeqEnd,
+    EnumBegin(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    EnumEnd(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    TaggedBegin(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    TaggedEnd(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    RecordBegin(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+        Option<usize>,
+    ),
+    RecordValueBegin(Option<sval::Tag>, Cow<'static, str>),
+    RecordValueEnd(Option<sval::Tag>, Cow<'static, str>),
+    RecordEnd(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+    TupleBegin(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+        Option<usize>,
+    ),
+    TupleValueBegin(Option<sval::Tag>, sval::Index),
+    TupleValueEnd(Option<sval::Tag>, sval::Index),
+    TupleEnd(
+        Option<sval::Tag>,
+        Option<Cow<'static, str>>,
+        Option<sval::Index>,
+    ),
+}
+
+fn owned_label(label: &sval::Label) -> Cow<'static, str> {
+    match label.as_static_str() {
+        Some(label) => Cow::Borrowed(label),
+        None => Cow::Owned(label.as_str().to_owned()),
+    }
+}
+
+trait ToLabel {
+    fn to_label(&self) -> sval::Label<'_>;
+}
+
+impl ToLabel for Cow<'static, str> {
+    fn to_label(&self) -> sval::Label<'_> {
+        match self {
+            Cow::Borrowed(label) => sval::Label::new(label),
+            Cow::Owned(label) => sval::Label::from_computed(label),
+        }
+    }
+}
+
+impl<'a> From<Token<'a>> for OwnedToken {
+    fn from(token: Token<'a>) -> Self {
+        match token {
+            Token::U8(v) => OwnedToken::U8(v),
+            Token::U16(v) => OwnedToken::U16(v),
+            Token::U32(v) => OwnedToken::U32(v),
+            Token::U64(v) => OwnedToken::U64(v),
+            Token::U128(v) => OwnedToken::U128(v),
+            Token::I8(v) => OwnedToken::I8(v),
+            Token::I16(v) => OwnedToken::I16(v),
+            Token::I32(v) => OwnedToken::I32(v),
+            Token::I64(v) => OwnedToken::I64(v),
+            Token::I128(v) => OwnedToken::I128(v),
+            Token::F32(v) => OwnedToken::F32(v),
+            Token::F64(v) => OwnedToken::F64(v),
+            Token::Bool(v) => OwnedToken::Bool(v),
+            Token::Null => OwnedToken::Null,
+            Token::Tag(tag, label, index) => {
+                OwnedToken::Tag(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::TextBegin(num_bytes) => OwnedToken::TextBegin(num_bytes),
+            Token::TextFragment(fragment) => OwnedToken::TextFragment(fragment.to_owned()),
+            Token::TextFragmentComputed(fragment) => OwnedToken::TextFragmentComputed(fragment),
+            Token::TextEnd => OwnedToken::TextEnd,
+            Token::BinaryBegin(num_bytes) => OwnedToken::BinaryBegin(num_bytes),
+            Token::BinaryFragment(fragment) => OwnedToken::BinaryFragment(fragment.to_owned()),
+            Token::BinaryFragmentComputed(fragment) => OwnedToken::BinaryFragmentComputed(fragment),
+            Token::BinaryEnd => OwnedToken::BinaryEnd,
+            Token::MapBegin(num_entries) => OwnedToken::MapBegin(num_entries),
+            Token::MapKeyBegin => OwnedToken::MapKeyBegin,
+            Token::MapKeyEnd => OwnedToken::MapKeyEnd,
+            Token::MapValueBegin => OwnedToken::MapValueBegin,
+            Token::MapValueEnd => OwnedToken::MapValueEnd,
+            Token::MapEnd => OwnedToken::MapEnd,
+            Token::SeqBegin(num_entries) => OwnedToken::SeqBegin(num_entries),
+            Token::SeqValueBegin => OwnedToken::SeqValueBegin,
+            Token::SeqValueEnd => OwnedToken::SeqValueEnd,
+            Token::SeqEnd => OwnedToken::SeqEnd,
+            Token::EnumBegin(tag, label, index) => {
+                OwnedToken::EnumBegin(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::EnumEnd(tag, label, index) => {
+                OwnedToken::EnumEnd(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::TaggedBegin(tag, label, index) => {
+                OwnedToken::TaggedBegin(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::TaggedEnd(tag, label, index) => {
+                OwnedToken::TaggedEnd(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::RecordBegin(tag, label, index, num_entries) => {
+                OwnedToken::RecordBegin(tag, label.as_ref().map(owned_label), index, num_entries)
+            }
+            Token::RecordValueBegin(tag, label) => {
+                OwnedToken::RecordValueBegin(tag, owned_label(&label))
+            }
+            Token::RecordValueEnd(tag, label) => {
+                OwnedToken::RecordValueEnd(tag, owned_label(&label))
+            }
+            Token::RecordEnd(tag, label, index) => {
+                OwnedToken::RecordEnd(tag, label.as_ref().map(owned_label), index)
+            }
+            Token::TupleBegin(tag, label, index, num_entries) => {
+                OwnedToken::TupleBegin(tag, label.as_ref().map(owned_label), index, num_entries)
+            }
+            Token::TupleValueBegin(tag, index) => OwnedToken::TupleValueBegin(tag, index),
+            Token::TupleValueEnd(tag, index) => OwnedToken::TupleValueEnd(tag, index),
+            Token::TupleEnd(tag, label, index) => {
+                OwnedToken::TupleEnd(tag, label.as_ref().map(owned_label), index)
+            }
+        }
+    }
+}
+
+/**
+A log of [`OwnedToken`]s captured from a value.
+
+A `Recording` is also a [`sval::Value`] that replays the exact sequence of calls
+it captured, including whether fragments were borrowed or computed.
+*/
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Recording {
+    tokens: Vec<OwnedToken>,
+}
+
+impl Recording {
+    /**
+    Create an empty recording.
+    */
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /**
+    Capture the tokens streamed by a value.
+    */
+    pub fn record(value: &(impl sval::Value + ?Sized)) -> sval::Result<Self> {
+        let mut stream = crate::Stream(Vec::new());
+        value.stream(&mut stream)?;
+
+        Ok(stream.0.into_iter().map(OwnedToken::from).collect())
+    }
+
+    /**
+    Append a token to the recording.
+    */
+    pub fn push(&mut self, token: OwnedToken) {
+        self.tokens.push(token);
+    }
+
+    /**
+    The tokens in the recording.
+    */
+    pub fn tokens(&self) -> &[OwnedToken] {
+        &self.tokens
+    }
+
+    /**
+    Convert the recording into its tokens.
+    */
+    pub fn into_tokens(self) -> Vec<OwnedToken> {
+        self.tokens
+    }
+}
+
+impl From<Vec<OwnedToken>> for Recording {
+    fn from(tokens: Vec<OwnedToken>) -> Self {
+        Recording { tokens }
+    }
+}
+
+impl FromIterator<OwnedToken> for Recording {
+    fn from_iter<I: IntoIterator<Item = OwnedToken>>(iter: I) -> Self {
+        Recording {
+            tokens: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl sval::Value for Recording {
+    fn stream<'sval, S: sval::Stream<'sval> + ?Sized>(&'sval self, stream: &mut S) -> sval::Result {
+        for token in &self.tokens {
+            match token {
+                OwnedToken::U8(v) => stream.u8(*v)?,
+                OwnedToken::U16(v) => stream.u16(*v)?,
+                OwnedToken::U32(v) => stream.u32(*v)?,
+                OwnedToken::U64(v) => stream.u64(*v)?,
+                OwnedToken::U128(v) => stream.u128(*v)?,
+                OwnedToken::I8(v) => stream.i8(*v)?,
+                OwnedToken::I16(v) => stream.i16(*v)?,
+                OwnedToken::I32(v) => stream.i32(*v)?,
+                OwnedToken::I64(v) => stream.i64(*v)?,
+                OwnedToken::I128(v) => stream.i128(*v)?,
+                OwnedToken::F32(v) => stream.f32(*v)?,
+                OwnedToken::F64(v) => stream.f64(*v)?,
+                OwnedToken::Bool(v) => stream.bool(*v)?,
+                OwnedToken::Null => stream.null()?,
+                OwnedToken::Tag(tag, l, index) => stream.tag(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::TextBegin(num_bytes) => stream.text_begin(*num_bytes)?,
+                OwnedToken::TextFragment(fragment) => stream.text_fragment(fragment)?,
+                OwnedToken::TextFragmentComputed(fragment) => {
+                    stream.text_fragment_computed(fragment)?
+                }
+                OwnedToken::TextEnd => stream.text_end()?,
+                OwnedToken::BinaryBegin(num_bytes) => stream.binary_begin(*num_bytes)?,
+                OwnedToken::BinaryFragment(fragment) => stream.binary_fragment(fragment)?,
+                OwnedToken::BinaryFragmentComputed(fragment) => {
+                    stream.binary_fragment_computed(fragment)?
+                }
+                OwnedToken::BinaryEnd => stream.binary_end()?,
+                OwnedToken::MapBegin(num_entries) => stream.map_begin(*num_entries)?,
+                OwnedToken::MapKeyBegin => stream.map_key_begin()?,
+                OwnedToken::MapKeyEnd => stream.map_key_end()?,
+                OwnedToken::MapValueBegin => stream.map_value_begin()?,
+                OwnedToken::MapValueEnd => stream.map_value_end()?,
+                OwnedToken::MapEnd => stream.map_end()?,
+                OwnedToken::SeqBegin(num_entries) => stream.seq_begin(*num_entries)?,
+                OwnedToken::SeqValueBegin => stream.seq_value_begin()?,
+                OwnedToken::SeqValueEnd => stream.seq_value_end()?,
+                OwnedToken::SeqEnd => stream.seq_end()?,
+                OwnedToken::EnumBegin(tag, l, index) => stream.enum_begin(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::EnumEnd(tag, l, index) => stream.enum_end(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::TaggedBegin(tag, l, index) => stream.tagged_begin(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::TaggedEnd(tag, l, index) => stream.tagged_end(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::RecordBegin(tag, l, index, num_entries) => stream.record_begin(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                    *num_entries,
+                )?,
+                OwnedToken::RecordValueBegin(tag, l) => {
+                    stream.record_value_begin(tag.as_ref(), &l.to_label())?
+                }
+                OwnedToken::RecordValueEnd(tag, l) => {
+                    stream.record_value_end(tag.as_ref(), &l.to_label())?
+                }
+                OwnedToken::RecordEnd(tag, l, index) => stream.record_end(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+                OwnedToken::TupleBegin(tag, l, index, num_entries) => stream.tuple_begin(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                    *num_entries,
+                )?,
+                OwnedToken::TupleValueBegin(tag, index) => {
+                    stream.tuple_value_begin(tag.as_ref(), index)?
+                }
+                OwnedToken::TupleValueEnd(tag, index) => {
+                    stream.tuple_value_end(tag.as_ref(), index)?
+                }
+                OwnedToken::TupleEnd(tag, l, index) => stream.tuple_end(
+                    tag.as_ref(),
+                    l.as_ref().map(ToLabel::to_label).as_ref(),
+                    index.as_ref(),
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use std::{borrow::Cow, error, fmt, str::FromStr};
+
+use sval::tags;
+
+use crate::{OwnedToken, Recording};
+
+/**
+Assert that a value streams the tokens in a snapshot.
+
+The snapshot uses the format of [`Recording`]'s `Display` implementation.
+Indentation and blank lines in the snapshot aren't significant.
+*/
+pub fn assert_snapshot(value: &(impl sval::Value + ?Sized), snapshot: &str) {
+    let actual = Recording::record(value)
+        .expect("infallible stream")
+        .to_string();
+
+    fn normalize(snapshot: &str) -> Vec<&str> {
+        snapshot
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    if normalize(snapshot) != normalize(&actual) {
+        panic!(
+            "the value doesn't match its snapshot\n\nexpected:\n{}\n\nactual:\n{}",
+            snapshot.trim(),
+            actual.trim()
+        );
+    }
+}
+
+/**
+An error parsing a [`Recording`] from a snapshot.
+*/
+#[derive(Debug)]
+pub struct SnapshotError {
+    line: usize,
+    message: String,
+}
+
+impl SnapshotError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        SnapshotError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid snapshot on line {}: {}",
+            self.line, self.message
+        )
+    }
+}
+
+impl error::Error for SnapshotError {}
+
+/**
+Write a recording as a snapshot.
+
+Snapshots contain one token per line, named after the [`sval::Stream`] method
+that produced it, and indented by how deeply nested it is:
+
+```text
+record_begin label="Point" num_entries=2
+  record_value_begin label="x"
+    i32 1
+  record_value_end label="x"
+  record_value_begin label="y"
+    text_begin num_bytes=1
+      text_fragment "a"
+    text_end
+  record_value_end label="y"
+record_end label="Point"
+```
+
+Tags, labels, indexes, and size hints are written as `key=value` arguments, and are left out when they're `None`.
+Text is quoted and escaped like a Rust string. Binary is written as hex, like `0x00ff`.
+*/
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut depth = 0usize;
+
+        for token in self.tokens() {
+            let name = name(token);
+
+            if name.ends_with("_end") {
+                depth = depth.saturating_sub(1);
+            }
+
+            write!(f, "{:width$}{}", "", name, width = depth * 2)?;
+            write_args(f, token)?;
+            writeln!(f)?;
+
+            if name.ends_with("_begin") {
+                depth += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+Parse a recording from a snapshot.
+
+See the `Display` implementation on [`Recording`] for the format.
+Lines that are empty or start with `#` are ignored.
+
+Labels parsed from a snapshot are always owned.
+Tags that aren't in [`sval::tags`] are leaked, because tags can only be created from static strings.
+*/
+impl FromStr for Recording {
+    type Err = SnapshotError;
+
+    fn from_str(snapshot: &str) -> Result<Self, Self::Err> {
+        let mut recording = Recording::new();
+
+        for (i, line) in snapshot.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let token = parse_line(line).map_err(|message| SnapshotError::new(i + 1, message))?;
+
+            recording.push(token);
+        }
+
+        Ok(recording)
+    }
+}
+
+fn name(token: &OwnedToken) -> &'static str {
+    match token {
+        OwnedToken::U8(_) => "u8",
+        OwnedToken::U16(_) => "u16",
+        OwnedToken::U32(_) => "u32",
+        OwnedToken::U64(_) => "u64",
+        OwnedToken::U128(_) => "u128",
+        OwnedToken::I8(_) => "i8",
+        OwnedToken::I16(_) => "i16",
+        OwnedToken::I32(_) => "i32",
+        OwnedToken::I64(_) => "i64",
+        OwnedToken::I128(_) => "i128",
+        OwnedToken::F32(_) => "f32",
+        OwnedToken::F64(_) => "f64",
+        OwnedToken::Bool(_) => "bool",
+        OwnedToken::Null => "null",
+        OwnedToken::Tag(..) => "tag",
+        OwnedToken::TextBegin(_) => "text_begin",
+        OwnedToken::TextFragment(_) => "text_fragment",
+        OwnedToken::TextFragmentComputed(_) => "text_fragment_computed",
+        OwnedToken::TextEnd => "text_end",
+        OwnedToken::BinaryBegin(_) => "binary_begin",
+        OwnedToken::BinaryFragment(_) => "binary_fragment",
+        OwnedToken::BinaryFragmentComputed(_) => "binary_fragment_computed",
+        OwnedToken::BinaryEnd => "binary_end",
+        OwnedToken::MapBegin(_) => "map_begin",
+        OwnedToken::MapKeyBegin => "map_key_begin",
+        OwnedToken::MapKeyEnd => "map_key_end",
+        OwnedToken::MapValueBegin => "map_value_begin",
+        OwnedToken::MapValueEnd => "map_value_end",
+        OwnedToken::MapEnd => "map_end",
+        OwnedToken::SeqBegin(_) => "seq_begin",
+        OwnedToken::SeqValueBegin => "seq_value_begin",
+        OwnedToken::SeqValueEnd => "seq_value_end",
+        OwnedToken::SeqEnd => "seq_end",
+        OwnedToken::EnumBegin(..) => "enum_begin",
+        OwnedToken::EnumEnd(..) => "enum_end",
+        OwnedToken::TaggedBegin(..) => "tagged_begin",
+        OwnedToken::TaggedEnd(..) => "tagged_end",
+        OwnedToken::RecordBegin(..) => "record_begin",
+        OwnedToken::RecordValueBegin(..) => "record_value_begin",
+        OwnedToken::RecordValueEnd(..) => "record_value_end",
+        OwnedToken::RecordEnd(..) => "record_end",
+        OwnedToken::TupleBegin(..) => "tuple_begin",
+        OwnedToken::TupleValueBegin(..) => "tuple_value_begin",
+        OwnedToken::TupleValueEnd(..) => "tuple_value_end",
+        OwnedToken::TupleEnd(..) => "tuple_end",
+    }
+}
+
+fn write_args(f: &mut fmt::Formatter, token: &OwnedToken) -> fmt::Result {
+    fn hint(f: &mut fmt::Formatter, key: &str, hint: &Option<usize>) -> fmt::Result {
+        match hint {
+            Some(hint) => write!(f, " {}={}", key, hint),
+            None => Ok(()),
+        }
+    }
+
+    fn tag(f: &mut fmt::Formatter, tag: &Option<sval::Tag>) -> fmt::Result {
+        match tag {
+            // The `Debug` format of a tag is its quoted name wrapped in `Tag()`
+            Some(tag) => {
+                let debug = format!("{:?}", tag);
+                write!(f, " tag={}", &debug["Tag(".len()..debug.len() - 1])
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn label(f: &mut fmt::Formatter, label: &str) -> fmt::Result {
+        write!(f, " label={:?}", label)
+    }
+
+    fn index(f: &mut fmt::Formatter, index: &sval::Index) -> fmt::Result {
+        match index.to_usize() {
+            Some(index) => write!(f, " index={}", index),
+            None => write!(f, " index={:?}", index),
+        }
+    }
+
+    fn meta(
+        f: &mut fmt::Formatter,
+        t: &Option<sval::Tag>,
+        l: &Option<Cow<'static, str>>,
+        i: &Option<sval::Index>,
+    ) -> fmt::Result {
+        tag(f, t)?;
+
+        if let Some(l) = l {
+            label(f, l)?;
+        }
+
+        if let Some(i) = i {
+            index(f, i)?;
+        }
+
+        Ok(())
+    }
+
+    fn binary(f: &mut fmt::Formatter, binary: &[u8]) -> fmt::Result {
+        write!(f, " 0x")?;
+
+        for b in binary {
+            write!(f, "{:02x}", b)?;
+        }
+
+        Ok(())
+    }
+
+    match token {
+        OwnedToken::U8(v) => write!(f, " {}", v),
+        OwnedToken::U16(v) => write!(f, " {}", v),
+        OwnedToken::U32(v) => write!(f, " {}", v),
+        OwnedToken::U64(v) => write!(f, " {}", v),
+        OwnedToken::U128(v) => write!(f, " {}", v),
+        OwnedToken::I8(v) => write!(f, " {}", v),
+        OwnedToken::I16(v) => write!(f, " {}", v),
+        OwnedToken::I32(v) => write!(f, " {}", v),
+        OwnedToken::I64(v) => write!(f, " {}", v),
+        OwnedToken::I128(v) => write!(f, " {}", v),
+        OwnedToken::F32(v) => write!(f, " {:?}", v),
+        OwnedToken::F64(v) => write!(f, " {:?}", v),
+        OwnedToken::Bool(v) => write!(f, " {}", v),
+        OwnedToken::TextFragment(fragment) | OwnedToken::TextFragmentComputed(fragment) => {
+            write!(f, " {:?}", fragment)
+        }
+        OwnedToken::BinaryFragment(fragment) | OwnedToken::BinaryFragmentComputed(fragment) => {
+            binary(f, fragment)
+        }
+        OwnedToken::TextBegin(num_bytes) | OwnedToken::BinaryBegin(num_bytes) => {
+            hint(f, "num_bytes", num_bytes)
+        }
+        OwnedToken::MapBegin(num_entries) | OwnedToken::SeqBegin(num_entries) => {
+            hint(f, "num_entries", num_entries)
+        }
+        OwnedToken::Tag(t, l, i)
+        | OwnedToken::EnumBegin(t, l, i)
+        | OwnedToken::EnumEnd(t, l, i)
+        | OwnedToken::TaggedBegin(t, l, i)
+        | OwnedToken::TaggedEnd(t, l, i)
+        | OwnedToken::RecordEnd(t, l, i)
+        | OwnedToken::TupleEnd(t, l, i) => meta(f, t, l, i),
+        OwnedToken::RecordBegin(t, l, i, num_entries)
+        | OwnedToken::TupleBegin(t, l, i, num_entries) => {
+            meta(f, t, l, i)?;
+            hint(f, "num_entries", num_entries)
+        }
+        OwnedToken::RecordValueBegin(t, l) | OwnedToken::RecordValueEnd(t, l) => {
+            tag(f, t)?;
+            label(f, l)
+        }
+        OwnedToken::TupleValueBegin(t, i) | OwnedToken::TupleValueEnd(t, i) => {
+            tag(f, t)?;
+            index(f, i)
+        }
+        OwnedToken::Null
+        | OwnedToken::TextEnd
+        | OwnedToken::BinaryEnd
+        | OwnedToken::MapKeyBegin
+        | OwnedToken::MapKeyEnd
+        | OwnedToken::MapValueBegin
+        | OwnedToken::MapValueEnd
+        | OwnedToken::MapEnd
+        | OwnedToken::SeqValueBegin
+        | OwnedToken::SeqValueEnd
+        | OwnedToken::SeqEnd => Ok(()),
+    }
+}
+
+enum Arg<'a> {
+    Bare(&'a str),
+    Quoted(String),
+}
+
+struct Args<'a> {
+    positional: Option<Arg<'a>>,
+    keyed: Vec<(&'a str, Arg<'a>)>,
+}
+
+impl<'a> Args<'a> {
+    fn parse(mut args: &'a str) -> Result<Self, String> {
+        let mut parsed = Args {
+            positional: None,
+            keyed: Vec::new(),
+        };
+
+        loop {
+            args = args.trim_start();
+
+            if args.is_empty() {
+                return Ok(parsed);
+            }
+
+            let (key, rest) = match args.find(|c: char| c == '=' || c == '"' || c.is_whitespace()) {
+                Some(i) if args[i..].starts_with('=') => (Some(&args[..i]), &args[i + 1..]),
+                _ => (None, args),
+            };
+
+            let (arg, rest) = if rest.starts_with('"') {
+                let (arg, rest) = unquote(rest)?;
+                (Arg::Quoted(arg), rest)
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                (Arg::Bare(&rest[..end]), &rest[end..])
+            };
+
+            match key {
+                Some(key) => parsed.keyed.push((key, arg)),
+                None if parsed.positional.is_none() => parsed.positional = Some(arg),
+                None => return Err("unexpected extra argument".into()),
+            }
+
+            args = rest;
+        }
+    }
+
+    fn value<T: FromStr>(&mut self) -> Result<T, String> {
+        match self.positional.take() {
+            Some(Arg::Bare(arg)) => arg.parse().map_err(|_| format!("invalid value `{}`", arg)),
+            _ => Err("expected a value".into()),
+        }
+    }
+
+    fn text(&mut self) -> Result<String, String> {
+        match self.positional.take() {
+            Some(Arg::Quoted(arg)) => Ok(arg),
+            _ => Err("expected a quoted string".into()),
+        }
+    }
+
+    fn binary(&mut self) -> Result<Vec<u8>, String> {
+        let hex = match self.positional.take() {
+            Some(Arg::Bare(arg)) if arg.starts_with("0x") && arg.len() % 2 == 0 => &arg[2..],
+            _ => return Err("expected hex bytes like `0x00ff`".into()),
+        };
+
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| {
+                u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map_err(|_| format!("invalid hex bytes `{}`", hex))
+            })
+            .collect()
+    }
+
+    fn take(&mut self, key: &str) -> Option<Arg<'a>> {
+        let i = self.keyed.iter().position(|(k, _)| *k == key)?;
+        Some(self.keyed.remove(i).1)
+    }
+
+    fn usize(&mut self, key: &str) -> Result<Option<usize>, String> {
+        match self.take(key) {
+            Some(Arg::Bare(arg)) => arg
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid `{}` value `{}`", key, arg)),
+            Some(Arg::Quoted(_)) => Err(format!("expected a number for `{}`", key)),
+            None => Ok(None),
+        }
+    }
+
+    fn index(&mut self) -> Result<Option<sval::Index>, String> {
+        Ok(self.usize("index")?.map(sval::Index::new))
+    }
+
+    fn label(&mut self) -> Result<Option<Cow<'static, str>>, String> {
+        match self.take("label") {
+            Some(Arg::Quoted(label)) => Ok(Some(Cow::Owned(label))),
+            Some(Arg::Bare(_)) => Err("expected a quoted string for `label`".into()),
+            None => Ok(None),
+        }
+    }
+
+    fn tag(&mut self) -> Result<Option<sval::Tag>, String> {
+        match self.take("tag") {
+            Some(Arg::Quoted(tag)) => Ok(Some(parse_tag(tag))),
+            Some(Arg::Bare(_)) => Err("expected a quoted string for `tag`".into()),
+            None => Ok(None),
+        }
+    }
+
+    fn finish(self, token: OwnedToken) -> Result<OwnedToken, String> {
+        if self.positional.is_some() {
+            return Err("unexpected argument".into());
+        }
+
+        if let Some((key, _)) = self.keyed.first() {
+            return Err(format!("unexpected argument `{}`", key));
+        }
+
+        Ok(token)
+    }
+}
+
+fn parse_tag(tag: String) -> sval::Tag {
+    const KNOWN: &[sval::Tag] = &[
+        tags::RUST_OPTION_SOME,
+        tags::RUST_OPTION_NONE,
+        tags::RUST_UNIT,
+        tags::NUMBER,
+        tags::CONSTANT_SIZE,
+    ];
+
+    let debug = format!("Tag({:?})", tag);
+
+    for known in KNOWN {
+        if format!("{:?}", known) == debug {
+            return known.clone();
+        }
+    }
+
+    sval::Tag::new(Box::leak(tag.into_boxed_str()))
+}
+
+fn parse_line(line: &str) -> Result<OwnedToken, String> {
+    let (name, args) = line.split_at(line.find(char::is_whitespace).unwrap_or(line.len()));
+    let mut args = Args::parse(args)?;
+
+    let token = match name {
+        "u8" => OwnedToken::U8(args.value()?),
+        "u16" => OwnedToken::U16(args.value()?),
+        "u32" => OwnedToken::U32(args.value()?),
+        "u64" => OwnedToken::U64(args.value()?),
+        "u128" => OwnedToken::U128(args.value()?),
+        "i8" => OwnedToken::I8(args.value()?),
+        "i16" => OwnedToken::I16(args.value()?),
+        "i32" => OwnedToken::I32(args.value()?),
+        "i64" => OwnedToken::I64(args.value()?),
+        "i128" => OwnedToken::I128(args.value()?),
+        "f32" => OwnedToken::F32(args.value()?),
+        "f64" => OwnedToken::F64(args.value()?),
+        "bool" => OwnedToken::Bool(args.value()?),
+        "null" => OwnedToken::Null,
+        "tag" => OwnedToken::Tag(args.tag()?, args.label()?, args.index()?),
+        "text_begin" => OwnedToken::TextBegin(args.usize("num_bytes")?),
+        "text_fragment" => OwnedToken::TextFragment(args.text()?),
+        "text_fragment_computed" => OwnedToken::TextFragmentComputed(args.text()?),
+        "text_end" => OwnedToken::TextEnd,
+        "binary_begin" => OwnedToken::BinaryBegin(args.usize("num_bytes")?),
+        "binary_fragment" => OwnedToken::BinaryFragment(args.binary()?),
+        "binary_fragment_computed" => OwnedToken::BinaryFragmentComputed(args.binary()?),
+        "binary_end" => OwnedToken::BinaryEnd,
+        "map_begin" => OwnedToken::MapBegin(args.usize("num_entries")?),
+        "map_key_begin" => OwnedToken::MapKeyBegin,
+        "map_key_end" => OwnedToken::MapKeyEnd,
+        "map_value_begin" => OwnedToken::MapValueBegin,
+        "map_value_end" => OwnedToken::MapValueEnd,
+        "map_end" => OwnedToken::MapEnd,
+        "seq_begin" => OwnedToken::SeqBegin(args.usize("num_entries")?),
+        "seq_value_begin" => OwnedToken::SeqValueBegin,
+        "seq_value_end" => OwnedToken::SeqValueEnd,
+        "seq_end" => OwnedToken::SeqEnd,
+        "enum_begin" => OwnedToken::EnumBegin(args.tag()?, args.label()?, args.index()?),
+        "enum_end" => OwnedToken::EnumEnd(args.tag()?, args.label()?, args.index()?),
+        "tagged_begin" => OwnedToken::TaggedBegin(args.tag()?, args.label()?, args.index()?),
+        "tagged_end" => OwnedToken::TaggedEnd(args.tag()?, args.label()?, args.index()?),
+        "record_begin" => OwnedToken::RecordBegin(
+            args.tag()?,
+            args.label()?,
+            args.index()?,
+            args.usize("num_entries")?,
+        ),
+        "record_value_begin" => {
+            OwnedToken::RecordValueBegin(args.tag()?, args.label()?.ok_or("expected a `label`")?)
+        }
+        "record_value_end" => {
+            OwnedToken::RecordValueEnd(args.tag()?, args.label()?.ok_or("expected a `label`")?)
+        }
+        "record_end" => OwnedToken::RecordEnd(args.tag()?, args.label()?, args.index()?),
+        "tuple_begin" => OwnedToken::TupleBegin(
+            args.tag()?,
+            args.label()?,
+            args.index()?,
+            args.usize("num_entries")?,
+        ),
+        "tuple_value_begin" => {
+            OwnedToken::TupleValueBegin(args.tag()?, args.index()?.ok_or("expected an `index`")?)
+        }
+        "tuple_value_end" => {
+            OwnedToken::TupleValueEnd(args.tag()?, args.index()?.ok_or("expected an `index`")?)
+        }
+        "tuple_end" => OwnedToken::TupleEnd(args.tag()?, args.label()?, args.index()?),
+        name => return Err(format!("unknown token `{}`", name)),
+    };
+
+    args.finish(token)
+}
+
+/**
+Parse a string quoted and escaped like Rust's `Debug` format for `str`.
+
+Returns the unescaped string and the input that follows it.
+*/
+fn unquote(quoted: &str) -> Result<(String, &str), String> {
+    let mut unquoted = String::new();
+    let mut chars = quoted.char_indices().skip(1);
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((unquoted, &quoted[i + 1..])),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('n') => unquoted.push('\n'),
+                Some('r') => unquoted.push('\r'),
+                Some('t') => unquoted.push('\t'),
+                Some('0') => unquoted.push('\0'),
+                Some(c @ ('\\' | '"' | '\'')) => unquoted.push(c),
+                Some('u') => {
+                    let mut hex = String::new();
+
+                    if chars.next().map(|(_, c)| c) != Some('{') {
+                        return Err("invalid unicode escape".into());
+                    }
+
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            break;
+                        }
+
+                        hex.push(c);
+                    }
+
+                    let c = u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("invalid unicode escape `{}`", hex))?;
+
+                    unquoted.push(c);
+                }
+                _ => return Err("invalid escape".into()),
+            },
+            c => unquoted.push(c),
+        }
+    }
+
+    Err("unterminated string".into())
+}